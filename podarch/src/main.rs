@@ -76,7 +76,8 @@ async fn main() {
                     .collect();
                 if filepath.as_path().exists() {
                     println!("File already exists, skipping: [{:?}]", filepath);
-                    return
+                    let size = fs::metadata(&filepath).map(|metadata| metadata.len()).ok();
+                    return racer::ArchivedEpisode::new(item, &filename, size);
                 }
                 let mut fp = fs::File::create(&filepath).expect(&format!("Creating {:#?}", &filepath));
 
@@ -102,12 +103,26 @@ async fn main() {
                     let mut fp = fs::File::create(&desc_filepath).expect(&format!("Creating {:#?}", &desc_filepath));
                     fp.write_all(item.description().unwrap_or("").as_bytes()).unwrap();
                 }
+
+                let size = fs::metadata(&filepath).map(|metadata| metadata.len()).ok();
+                racer::ArchivedEpisode::new(item, &filename, size)
             }
         })
         .buffer_unordered(parallel_gets);
 
     let items = results.collect::<Vec<_>>().await;
 
+    // Record what's here so racers can serve it. Episodes from earlier runs are kept.
+    let mut manifest = racer::ArchiveManifest::read(&dir)
+        .unwrap_or_else(|_| racer::ArchiveManifest::new(&opt.url));
+    manifest.feed_url = opt.url.clone();
+    for episode in &items {
+        manifest.insert(episode.clone());
+    }
+    if let Err(e) = manifest.write(&dir) {
+        println!("Error writing the archive manifest: {}", e);
+    }
+
     println!("Done. Downloaded {} episodes to {}", items.len(), &opt.dir);
 }
 
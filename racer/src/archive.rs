@@ -0,0 +1,130 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   archive.rs
+//
+//  © Zach Nielsen 2020
+//  The manifest podarch writes next to the episodes it downloads, so racers can serve them
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use serde::{Deserialize, Serialize};
+
+use crate::order::item_id;
+
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+pub const ARCHIVE_MANIFEST_FILE: &str = "podarch.json";
+pub const ARCHIVE_MANIFEST_VERSION: u32 = 1;
+
+// Everything podarch has downloaded into one directory
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ArchiveManifest {
+    pub version: u32,
+    // The feed the episodes came from
+    pub feed_url: String,
+    pub episodes: Vec<ArchivedEpisode>,
+}
+
+// One downloaded episode
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ArchivedEpisode {
+    // The episode's GUID, or what stands in for one. See ArchivedEpisode::new.
+    pub guid: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    // The audio file, relative to the archive directory
+    pub file: String,
+    pub mime_type: String,
+    // Where the audio was downloaded from
+    pub original_url: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+impl ArchivedEpisode {
+    // An episode downloaded to `file`. The guid is worked out the same way racers identify
+    // episodes (GUID, then enclosure url, then title) so the two always agree.
+    pub fn new(item: &rss::Item, file: &str, size: Option<u64>) -> ArchivedEpisode {
+        ArchivedEpisode {
+            guid: item_id(item),
+            title: item.title().map(str::to_owned),
+            file: file.to_owned(),
+            mime_type: item.enclosure().map(|enclosure| enclosure.mime_type().to_owned()).unwrap_or_default(),
+            original_url: item.enclosure().map(|enclosure| enclosure.url().to_owned()).unwrap_or_default(),
+            size,
+        }
+    }
+}
+
+impl ArchiveManifest {
+    pub fn new(feed_url: &str) -> ArchiveManifest {
+        ArchiveManifest {
+            version: ARCHIVE_MANIFEST_VERSION,
+            feed_url: feed_url.to_owned(),
+            episodes: Vec::new(),
+        }
+    }
+
+    // Reads the manifest in an archive directory
+    pub fn read(dir: &Path) -> Result<ArchiveManifest, String> {
+        let path = dir.join(ARCHIVE_MANIFEST_FILE);
+        let file = match File::open(&path) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Unable to open {:?}: {}", path, e)),
+        };
+        let manifest: ArchiveManifest = match serde_json::from_reader(file) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Unable to read {:?}: {}", path, e)),
+        };
+        if manifest.version > ARCHIVE_MANIFEST_VERSION {
+            return Err(format!("{:?} is version {}, newer than this PodRacer knows ({})",
+                               path, manifest.version, ARCHIVE_MANIFEST_VERSION));
+        }
+        Ok(manifest)
+    }
+
+    // Writes the manifest into an archive directory
+    pub fn write(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(ARCHIVE_MANIFEST_FILE);
+        let file = match File::create(&path) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Unable to create {:?}: {}", path, e)),
+        };
+        match serde_json::to_writer_pretty(file, self) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Unable to write {:?}: {}", path, e)),
+        }
+    }
+
+    pub fn find(&self, guid: &str) -> Option<&ArchivedEpisode> {
+        self.episodes.iter().find(|episode| episode.guid == guid)
+    }
+
+    // Adds an episode, replacing any earlier download of it
+    pub fn insert(&mut self, episode: ArchivedEpisode) {
+        match self.episodes.iter_mut().find(|existing| existing.guid == episode.guid) {
+            Some(existing) => *existing = episode,
+            None => self.episodes.push(episode),
+        }
+    }
+}
+
+// Where an archived episode's audio is, if it's there. Files must stay inside the archive
+// directory, so a manifest can't be used to serve anything else off the disk.
+pub(crate) fn episode_path(dir: &Path, episode: &ArchivedEpisode) -> Option<PathBuf> {
+    let file = Path::new(&episode.file);
+    if !file.components().all(|component| matches!(component, Component::Normal(_))) {
+        println!("Ignoring archived file {:?} outside of {:?}", file, dir);
+        return None;
+    }
+    let path = dir.join(file);
+    match path.is_file() {
+        true => Some(path),
+        false => None,
+    }
+}
@@ -0,0 +1,223 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   artwork.rs
+//
+//  © Zach Nielsen 2020
+//  Badged copies of a podcast's artwork, so racers of the same show look different in apps
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use image::{imageops, ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::decoration::ArtworkBadge;
+use crate::RacerType;
+
+use std::io::Cursor;
+use std::path::Path;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+pub const ARTWORK_MIME_TYPE: &str = "image/png";
+// Apple's upper limit for podcast artwork
+const MAX_ARTWORK_SIZE: u32 = 3000;
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+// Horizontal space a glyph takes up, including the gap after it
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+// What the current badged artwork was made from, so it's only rebuilt when that changes
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct BadgeArtwork {
+    pub source_url: String,
+    pub text: String,
+}
+
+// The text for a badge, or None if there shouldn't be one
+pub(crate) fn badge_text(badge: &ArtworkBadge, rate: &RacerType) -> Option<String> {
+    match badge {
+        ArtworkBadge::Off => None,
+        ArtworkBadge::PodRacer => Some(String::from("PODRACER")),
+        ArtworkBadge::Text(text) => Some(text.trim().to_uppercase()),
+        ArtworkBadge::Rate => Some(match rate {
+            RacerType::Ratio(ratio) => format!("{}X", ratio),
+            RacerType::Days(1) => String::from("EVERY DAY"),
+            RacerType::Days(days) => format!("EVERY {} DAYS", days),
+            RacerType::Delay(days) => format!("{} DAYS LATE", days),
+        }),
+    }
+}
+
+// The artwork players would show for this channel
+pub(crate) fn channel_artwork_url(channel: &rss::Channel) -> Option<String> {
+    channel.itunes_ext()
+        .and_then(|itunes| itunes.image())
+        .or(channel.image().map(|image| image.url()))
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_owned)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   write_badge_artwork
+//
+//  NOTES:
+//      Downloads the original artwork, stamps the badge on it and saves it as a png.
+//  ARGS:
+//      client - For downloading the artwork
+//      badge - The artwork to use and the text to put on it
+//      path - Where to write the png
+//  RETURN: An error if the artwork couldn't be downloaded, read or written
+//
+pub(crate) async fn write_badge_artwork(client: &reqwest::Client, badge: &BadgeArtwork, path: &Path)
+    -> Result<(), String> {
+    let response = match client.get(&badge.source_url).send().await {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Error downloading {}: {}", badge.source_url, e)),
+    };
+    if !response.status().is_success() {
+        return Err(format!("Error downloading {}: {}", badge.source_url, response.status()));
+    }
+    let original = match response.bytes().await {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Error downloading {}: {}", badge.source_url, e)),
+    };
+    let png = badge_artwork(&original, &badge.text)?;
+    match std::fs::write(path, png) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Unable to write {:?}: {}", path, e)),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   badge_artwork
+//
+//  NOTES:
+//      Darkens a band along the bottom of the artwork and writes the text across it in white.
+//      Artwork bigger than Apple allows is scaled down first.
+//  ARGS:
+//      original - The original artwork, as png or jpeg
+//      text - What the badge says
+//  RETURN: The badged artwork as a png
+//
+pub fn badge_artwork(original: &[u8], text: &str) -> Result<Vec<u8>, String> {
+    let mut artwork = match image::load_from_memory(original) {
+        Ok(val) => val.to_rgba8(),
+        Err(e) => return Err(format!("Error reading artwork: {}", e)),
+    };
+    if artwork.width() > MAX_ARTWORK_SIZE || artwork.height() > MAX_ARTWORK_SIZE {
+        let scale = MAX_ARTWORK_SIZE as f64 / std::cmp::max(artwork.width(), artwork.height()) as f64;
+        artwork = imageops::resize(
+            &artwork,
+            ((artwork.width() as f64 * scale) as u32).max(1),
+            ((artwork.height() as f64 * scale) as u32).max(1),
+            imageops::FilterType::Triangle,
+        );
+    }
+    draw_badge(&mut artwork, text);
+
+    let mut png = Vec::new();
+    match artwork.write_to(&mut Cursor::new(&mut png), ImageFormat::Png) {
+        Ok(_) => Ok(png),
+        Err(e) => Err(format!("Error writing artwork: {}", e)),
+    }
+}
+
+// The band takes up the bottom fifth of the artwork. The text is scaled up in whole pixels to
+// fill most of it, or as much as fits across.
+fn draw_badge(artwork: &mut RgbaImage, text: &str) {
+    let (width, height) = artwork.dimensions();
+    let band_height = std::cmp::max(height / 5, GLYPH_HEIGHT + 2);
+    let band_top = height.saturating_sub(band_height);
+    for y in band_top..height {
+        for x in 0..width {
+            let pixel = artwork.get_pixel_mut(x, y);
+            for channel in 0..3 {
+                pixel[channel] = (pixel[channel] as u32 * 2 / 5) as u8;
+            }
+            pixel[3] = 255;
+        }
+    }
+
+    let num_chars = text.chars().count() as u32;
+    if num_chars == 0 {
+        return;
+    }
+    let text_units = num_chars * GLYPH_ADVANCE - 1;
+    let scale = std::cmp::min(band_height * 3 / 5 / GLYPH_HEIGHT, width * 9 / 10 / text_units).max(1);
+    let left = width.saturating_sub(text_units * scale) / 2;
+    let top = band_top + band_height.saturating_sub(GLYPH_HEIGHT * scale) / 2;
+    let white = Rgba([255, 255, 255, 255]);
+    for (idx, c) in text.chars().enumerate() {
+        let glyph_left = left + idx as u32 * GLYPH_ADVANCE * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = glyph_left + col * scale + dx;
+                        let y = top + row as u32 * scale + dy;
+                        if x < width && y < height {
+                            artwork.put_pixel(x, y, white);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// A 5x7 pixel font, one byte per row. Anything without a glyph is drawn as '?'.
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        ' ' => [0; 7],
+        '.' => [0, 0, 0, 0, 0, 0b01100, 0b01100],
+        ',' => [0, 0, 0, 0, 0b01100, 0b00100, 0b01000],
+        '/' => [0, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0],
+        '-' => [0, 0, 0, 0b11111, 0, 0, 0],
+        '+' => [0, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0],
+        ':' => [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '!' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0, 0b00100],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0, 0b00100],
+    }
+}
@@ -0,0 +1,74 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   catchup.rs
+//
+//  © Zach Nielsen 2020
+//  What a racer does once it has caught up to the live feed
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// How episodes that come out after a racer catches up get published
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum CatchUpPolicy {
+    // Publish them when upstream does
+    #[default]
+    Live,
+    // Publish them this many days after upstream does
+    Delay(u32),
+    // Don't publish them at all
+    Stop,
+}
+
+// When a racer caught up, and how far along it was
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct CaughtUp {
+    pub date: DateTime<chrono::Utc>,
+    // Episodes published when we caught up. Everything after these follows the policy.
+    pub num_published: usize,
+}
+
+impl std::str::FromStr for CatchUpPolicy {
+    type Err = String;
+    // "live", "stop", or "delay:<days>"
+    fn from_str(s: &str) -> Result<CatchUpPolicy, String> {
+        let s = s.trim();
+        match s {
+            "live" => Ok(CatchUpPolicy::Live),
+            "stop" => Ok(CatchUpPolicy::Stop),
+            _ => match s.strip_prefix("delay:") {
+                Some(days) => match days.trim().parse() {
+                    Ok(days) if days > 0 => Ok(CatchUpPolicy::Delay(days)),
+                    _ => Err(format!("Delay must be a whole number of days > 0, not {}", days)),
+                },
+                None => Err(format!("Unknown catch up policy {}", s)),
+            },
+        }
+    }
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for CatchUpPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatchUpPolicy::Live => write!(f, "live"),
+            CatchUpPolicy::Delay(days) => write!(f, "{} days behind live", days),
+            CatchUpPolicy::Stop => write!(f, "stop"),
+        }
+    }
+}
+impl fmt::Display for CaughtUp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} with {} episodes published", self.date.to_rfc2822(), self.num_published)
+    }
+}
@@ -0,0 +1,189 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   decoration.rs
+//
+//  © Zach Nielsen 2020
+//  What a racer changes about the podcast itself - its title, description and artwork
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+pub const TITLE_SUFFIX: &str = " - PodRacer";
+
+// How the racer feed is named
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum TitleStyle {
+    // "<title> - PodRacer"
+    #[default]
+    Suffix,
+    // A name of the user's choosing
+    Custom(String),
+    // The podcast's own title
+    Original,
+}
+
+// Text stamped on the podcast's artwork, so racers of the same show can be told apart
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum ArtworkBadge {
+    // The artwork is left alone
+    #[default]
+    Off,
+    // The racer's rate, e.g. "2X" or "EVERY 7 DAYS"
+    Rate,
+    // "PODRACER"
+    PodRacer,
+    Text(String),
+}
+
+// Everything the racer adds to the channel
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct ChannelDecoration {
+    pub title: TitleStyle,
+    // Whether the description says when the next episode is coming
+    pub next_episode_line: bool,
+    // Replaces the podcast's artwork, if set
+    pub artwork: Option<String>,
+    pub badge: ArtworkBadge,
+}
+impl Default for ChannelDecoration {
+    fn default() -> Self {
+        ChannelDecoration {
+            title: TitleStyle::default(),
+            next_episode_line: true,
+            artwork: None,
+            badge: ArtworkBadge::default(),
+        }
+    }
+}
+
+// Where a racer is at, for the line in the podcast's description
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeedStatus {
+    NextEpisode(DateTime<Tz>),
+    Paused,
+    CaughtUp,
+    // Caught up, and the catch up policy keeps new episodes back
+    Stopped,
+}
+
+impl ChannelDecoration {
+    // Checks the parts a user can type in
+    pub fn validate(&self) -> Result<(), String> {
+        if let TitleStyle::Custom(title) = &self.title {
+            if title.trim().is_empty() {
+                return Err(String::from("Custom titles can't be empty"));
+            }
+        }
+        if let Some(artwork) = &self.artwork {
+            let artwork = artwork.trim();
+            if !artwork.starts_with("http://") && !artwork.starts_with("https://") {
+                return Err(format!("Artwork must be an http(s) link, not {}", artwork));
+            }
+        }
+        if let ArtworkBadge::Text(text) = &self.badge {
+            if text.trim().is_empty() {
+                return Err(String::from("Badges need some text"));
+            }
+        }
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   ChannelDecoration::apply
+    //
+    //  NOTES:
+    //      Renames the channel and its image, swaps in the artwork and adds the status line to
+    //      the description.
+    //  ARGS:
+    //      channel - The channel being published
+    //      status - Where the racer is at
+    //      uuid - The racer's uuid
+    //  RETURN: None
+    //
+    pub(crate) fn apply(&self, channel: &mut rss::Channel, status: &FeedStatus, uuid: &str) {
+        if let Some(artwork) = &self.artwork {
+            set_channel_artwork(channel, artwork.trim());
+        }
+
+        let title = match &self.title {
+            TitleStyle::Suffix => format!("{}{}", channel.title(), TITLE_SUFFIX),
+            TitleStyle::Custom(title) => title.trim().to_owned(),
+            TitleStyle::Original => channel.title().to_owned(),
+        };
+        channel.set_title(title.as_str());
+
+        if let Some(image) = &mut channel.image {
+            match &self.title {
+                TitleStyle::Suffix => image.set_title(format!("{}{}", image.title(), TITLE_SUFFIX)),
+                TitleStyle::Custom(_) => image.set_title(title.as_str()),
+                TitleStyle::Original => (),
+            }
+        }
+
+        if self.next_episode_line {
+            let description = format!("{} -- {} -- PodRacer UUID: {}", channel.description(), status, uuid);
+            channel.set_description(description);
+        }
+    }
+}
+
+// Points the rss image and the iTunes image at `url`. Both are set since players disagree on
+// which one they show.
+pub(crate) fn set_channel_artwork(channel: &mut rss::Channel, url: &str) {
+    let image = match channel.image() {
+        Some(image) => rss::Image { url: url.to_owned(), ..image.clone() },
+        None => rss::Image {
+            url: url.to_owned(),
+            title: channel.title().to_owned(),
+            link: channel.link().to_owned(),
+            ..Default::default()
+        },
+    };
+    channel.set_image(image);
+    let mut itunes = channel.itunes_ext().cloned().unwrap_or_default();
+    itunes.set_image(url.to_owned());
+    channel.set_itunes_ext(itunes);
+}
+
+//
+// Display implementations
+//
+impl fmt::Display for TitleStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TitleStyle::Suffix => write!(f, "original title{}", TITLE_SUFFIX),
+            TitleStyle::Custom(title) => write!(f, "\"{}\"", title),
+            TitleStyle::Original => write!(f, "original title"),
+        }
+    }
+}
+impl fmt::Display for ArtworkBadge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtworkBadge::Off => write!(f, "no badge"),
+            ArtworkBadge::Rate => write!(f, "rate badge"),
+            ArtworkBadge::PodRacer => write!(f, "PodRacer badge"),
+            ArtworkBadge::Text(text) => write!(f, "\"{}\" badge", text),
+        }
+    }
+}
+impl fmt::Display for FeedStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedStatus::NextEpisode(date) => write!(f, "Next episode publishes {}", date.format("%a, %d %b %Y at %I:%M%P %Z")),
+            FeedStatus::Paused => write!(f, "Feed paused"),
+            FeedStatus::CaughtUp => write!(f, "PodRacer feed has caught up"),
+            FeedStatus::Stopped => write!(f, "PodRacer feed has caught up and stopped publishing new episodes"),
+        }
+    }
+}
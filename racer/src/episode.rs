@@ -0,0 +1,86 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   episode.rs
+//
+//  © Zach Nielsen 2020
+//  Per-episode metadata from the iTunes extension, and what to do with it
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// <itunes:episodeType>. Anything missing or unrecognized is a full episode, per Apple's spec.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum EpisodeType {
+    #[default]
+    Full,
+    Trailer,
+    Bonus,
+}
+
+// What the feed says about an episode
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct EpisodeInfo {
+    #[serde(default)]
+    pub episode_type: EpisodeType,
+    #[serde(default)]
+    pub season: Option<u32>,
+    #[serde(default)]
+    pub episode: Option<u32>,
+}
+
+impl EpisodeInfo {
+    pub fn from_item(item: &rss::Item) -> EpisodeInfo {
+        let itunes = match item.itunes_ext() {
+            Some(val) => val,
+            None => return EpisodeInfo::default(),
+        };
+        let episode_type = match itunes.episode_type().map(|t| t.trim().to_lowercase()) {
+            Some(t) if t == "trailer" => EpisodeType::Trailer,
+            Some(t) if t == "bonus" => EpisodeType::Bonus,
+            _ => EpisodeType::Full,
+        };
+        EpisodeInfo {
+            episode_type,
+            season: itunes.season().and_then(|s| s.trim().parse().ok()),
+            episode: itunes.episode().and_then(|e| e.trim().parse().ok()),
+        }
+    }
+}
+
+// How a racer treats trailers and bonus episodes. The default schedules everything like a full
+// episode.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct EpisodeTypePolicy {
+    // Trailers are left out entirely
+    #[serde(default)]
+    pub skip_trailers: bool,
+    // Bonus episodes come out at the same time as the episode before them, rather than taking
+    // a slot of their own
+    #[serde(default)]
+    pub bonus_with_neighbour: bool,
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for EpisodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpisodeType::Full => write!(f, "full"),
+            EpisodeType::Trailer => write!(f, "trailer"),
+            EpisodeType::Bonus => write!(f, "bonus"),
+        }
+    }
+}
+impl fmt::Display for EpisodeTypePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skip trailers: {}, bonus with neighbour: {}", self.skip_trailers, self.bonus_with_neighbour)
+    }
+}
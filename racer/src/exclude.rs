@@ -0,0 +1,132 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   exclude.rs
+//
+//  © Zach Nielsen 2020
+//  Rules for leaving episodes out of a racer feed
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// Items matching any of these are skipped entirely - they never get a release date
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ExcludeRules {
+    // Exact GUIDs to skip
+    #[serde(default)]
+    pub guids: Vec<String>,
+    // Regexes matched against the item title
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    // Skip anything shorter than this many seconds (per itunes:duration)
+    #[serde(default)]
+    pub min_duration_secs: Option<u64>,
+}
+
+impl ExcludeRules {
+    pub fn is_empty(&self) -> bool {
+        self.guids.is_empty() && self.title_patterns.is_empty() && self.min_duration_secs.is_none()
+    }
+
+    // Makes sure every title pattern compiles
+    pub fn validate(&self) -> Result<(), String> {
+        for pattern in &self.title_patterns {
+            if let Err(e) = Regex::new(pattern) {
+                return Err(format!("Invalid title pattern {}: {}", pattern, e));
+            }
+        }
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   ExcludeRules::matcher
+    //
+    //  NOTES:
+    //      Compiles the rules so they can be checked against a whole feed. Patterns that don't
+    //      compile are skipped - validate() should have caught them already. The matcher doesn't
+    //      borrow the rules, so it can be kept around for as long as they don't change.
+    //  ARGS:   None
+    //  RETURN: Something that can check items
+    //
+    pub fn matcher(&self) -> ExcludeMatcher {
+        let title_patterns = self.title_patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(val) => Some(val),
+                Err(e) => {
+                    println!("Error compiling title pattern {}, ignoring it: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        ExcludeMatcher {
+            guids: self.guids.clone(),
+            title_patterns,
+            min_duration_secs: self.min_duration_secs,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExcludeMatcher {
+    guids: Vec<String>,
+    title_patterns: Vec<Regex>,
+    min_duration_secs: Option<u64>,
+}
+
+impl ExcludeMatcher {
+    pub fn is_excluded(&self, item: &rss::Item) -> bool {
+        if let Some(guid) = item.guid() {
+            if self.guids.iter().any(|excluded| excluded == guid.value()) {
+                return true;
+            }
+        }
+        if let Some(title) = item.title() {
+            if self.title_patterns.iter().any(|pattern| pattern.is_match(title)) {
+                return true;
+            }
+        }
+        if let Some(min_duration_secs) = self.min_duration_secs {
+            // Items without a duration are given the benefit of the doubt
+            let duration = item.itunes_ext()
+                .and_then(|ext| ext.duration())
+                .and_then(parse_duration);
+            if let Some(duration) = duration {
+                if duration < min_duration_secs {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+// itunes:duration is either plain seconds, MM:SS or HH:MM:SS
+pub(crate) fn parse_duration(duration: &str) -> Option<u64> {
+    let mut secs: u64 = 0;
+    for part in duration.trim().split(':') {
+        let part: u64 = part.trim().parse().ok()?;
+        secs = secs.checked_mul(60)?.checked_add(part)?;
+    }
+    Some(secs)
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for ExcludeRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "guids: [{}], titles: [{}]", self.guids.join(", "), self.title_patterns.join(", "))?;
+        if let Some(min_duration_secs) = self.min_duration_secs {
+            write!(f, ", shorter than {}s", min_duration_secs)?;
+        }
+        Ok(())
+    }
+}
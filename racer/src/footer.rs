@@ -0,0 +1,166 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   footer.rs
+//
+//  © Zach Nielsen 2020
+//  The footer added to each published episode's description, rendered from a Tera template
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// Lives in the podracer dir, next to the racer dirs. Written by the server on boot.
+pub const FOOTER_SETTINGS_FILE: &str = "footer.json";
+// Used when the server doesn't set its own template
+pub const DEFAULT_FOOTER_TEMPLATE: &str = "<hr>\n\n\
+    Originally published on {{ original_date }} ({{ time_shift }} from PodRacer publish date)\n\n\
+    Feed UUID: <a href=\"{{ edit_url }}\">{{ uuid }}</a>";
+const TEMPLATE_NAME: &str = "footer";
+
+// Server wide footer settings
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct FooterSettings {
+    // Where the server is reachable, for links back to it
+    pub host: String,
+    // Tera template for the footer. None for DEFAULT_FOOTER_TEMPLATE.
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+// What footer a feed gets
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum FeedFooter {
+    // The server's template
+    #[default]
+    Server,
+    // This feed's own Tera template
+    Custom(String),
+    // No footer at all
+    Disabled,
+}
+
+// Everything a footer template can use
+#[derive(Serialize, Debug)]
+pub struct FooterContext<'a> {
+    pub host: &'a str,
+    pub uuid: &'a str,
+    pub edit_url: String,
+    pub subscribe_url: &'a str,
+    pub podcast_title: &'a str,
+    pub episode_title: &'a str,
+    // When the episode came out upstream, e.g. "05 Mar 2019"
+    pub original_date: String,
+    // How far the racer date is from the original one, e.g. "1year 2months"
+    pub time_shift: String,
+}
+
+// A compiled footer template
+pub(crate) struct FooterRenderer {
+    tera: Tera,
+}
+
+impl FooterRenderer {
+    pub(crate) fn new(template: &str) -> Result<FooterRenderer, String> {
+        let mut tera = Tera::default();
+        match tera.add_raw_template(TEMPLATE_NAME, template) {
+            Ok(_) => Ok(FooterRenderer { tera }),
+            Err(e) => Err(format!("Invalid footer template: {}", tera_error(&e))),
+        }
+    }
+
+    pub(crate) fn render(&self, context: &FooterContext<'_>) -> Result<String, String> {
+        let context = match Context::from_serialize(context) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error building footer context: {}", e)),
+        };
+        match self.tera.render(TEMPLATE_NAME, &context) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(format!("Error rendering footer: {}", tera_error(&e))),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   validate_footer_template
+//
+//  NOTES:
+//      Checks a template compiles and renders with example values, so mistakes show up when
+//      the template is set rather than on every update.
+//  ARGS:   template - The Tera template
+//  RETURN: Why the template can't be used, if it can't
+//
+pub fn validate_footer_template(template: &str) -> Result<(), String> {
+    let renderer = FooterRenderer::new(template)?;
+    let example = FooterContext {
+        host: "http://localhost",
+        uuid: "00000000-0000-0000-0000-000000000000",
+        edit_url: String::from("http://localhost/edit_feed/00000000-0000-0000-0000-000000000000"),
+        subscribe_url: "http://localhost/podcasts/example/racer.rss",
+        podcast_title: "Example",
+        episode_title: "Example Episode",
+        original_date: String::from("01 Jan 2020"),
+        time_shift: String::from("1year"),
+    };
+    renderer.render(&example).map(|_| ())
+}
+
+// Saves the server's footer settings into the podracer dir
+pub fn write_footer_settings(podracer_dir: &str, settings: &FooterSettings) -> Result<(), String> {
+    if let Some(template) = &settings.template {
+        validate_footer_template(template)?;
+    }
+    let path = Path::new(podracer_dir).join(FOOTER_SETTINGS_FILE);
+    let file = match File::create(&path) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Unable to create {:?}: {}", path, e)),
+    };
+    match serde_json::to_writer_pretty(file, settings) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Unable to write {:?}: {}", path, e)),
+    }
+}
+
+// The server's footer settings, if it has saved any
+pub(crate) fn read_footer_settings(podracer_dir: &Path) -> Option<FooterSettings> {
+    let file = File::open(podracer_dir.join(FOOTER_SETTINGS_FILE)).ok()?;
+    match serde_json::from_reader(file) {
+        Ok(val) => Some(val),
+        Err(e) => {
+            println!("Error reading {}, using the default footer: {}", FOOTER_SETTINGS_FILE, e);
+            None
+        },
+    }
+}
+
+// Tera hides the useful part of the error in its source
+fn tera_error(e: &tera::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(inner) = source {
+        message += &format!(": {}", inner);
+        source = inner.source();
+    }
+    message
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for FeedFooter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedFooter::Server => write!(f, "server template"),
+            FeedFooter::Custom(_) => write!(f, "custom template"),
+            FeedFooter::Disabled => write!(f, "disabled"),
+        }
+    }
+}
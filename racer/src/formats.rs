@@ -0,0 +1,249 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   formats.rs
+//
+//  © Zach Nielsen 2020
+//  The published episodes as Atom and JSON Feed, for readers that don't do rss
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use atom_syndication::{Content, Entry, Feed, Link, Person, Text};
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::order::item_id;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+pub const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+pub const ATOM_MIME_TYPE: &str = "application/atom+xml";
+pub const JSON_FEED_MIME_TYPE: &str = "application/feed+json";
+
+// https://www.jsonfeed.org/version/1.1/
+#[derive(Serialize, Debug)]
+pub struct JsonFeed {
+    pub version: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_page_url: Option<String>,
+    pub feed_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<JsonAuthor>,
+    pub items: Vec<JsonItem>,
+}
+#[derive(Serialize, Debug)]
+pub struct JsonAuthor {
+    pub name: String,
+}
+#[derive(Serialize, Debug)]
+pub struct JsonItem {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub content_html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_published: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<JsonAuthor>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<JsonAttachment>,
+}
+#[derive(Serialize, Debug)]
+pub struct JsonAttachment {
+    pub url: String,
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_in_bytes: Option<u64>,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   atom_feed
+//
+//  NOTES:
+//      Builds an Atom feed out of a finished racer channel. Atom needs an author on the feed
+//      unless every entry has one, so the feed falls back to the podcast's name. Atom ids must be
+//      IRIs, which guids often aren't - those are turned into a urn under the racer's uuid.
+//  ARGS:
+//      channel - The racer channel, as written to racer.rss
+//      feed_url - Where the Atom feed is served
+//      uuid - The racer's uuid
+//  RETURN: The Atom feed
+//
+pub(crate) fn atom_feed(channel: &rss::Channel, feed_url: &str, uuid: &str) -> Feed {
+    let entries: Vec<Entry> = channel.items().iter()
+        .map(|item| atom_entry(item, uuid))
+        .collect();
+    let updated = entries.iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_else(|| chrono::Utc::now().into());
+
+    let mut links = vec![Link {
+        href: feed_url.to_owned(),
+        rel: String::from("self"),
+        mime_type: Some(ATOM_MIME_TYPE.to_owned()),
+        ..Default::default()
+    }];
+    if !channel.link().is_empty() {
+        links.push(Link {
+            href: channel.link().to_owned(),
+            rel: String::from("alternate"),
+            ..Default::default()
+        });
+    }
+
+    Feed {
+        title: Text::plain(channel.title()),
+        id: format!("urn:uuid:{}", uuid),
+        updated,
+        authors: vec![Person { name: channel_author(channel), ..Default::default() }],
+        links,
+        icon: channel_image(channel),
+        subtitle: Some(Text::html(channel.description())),
+        entries,
+        ..Default::default()
+    }
+}
+
+fn atom_entry(item: &rss::Item, uuid: &str) -> Entry {
+    let published = item.pub_date().and_then(|date| DateTime::parse_from_rfc2822(date).ok());
+    let mut links = Vec::new();
+    if let Some(link) = item.link() {
+        links.push(Link {
+            href: link.to_owned(),
+            rel: String::from("alternate"),
+            ..Default::default()
+        });
+    }
+    if let Some(enclosure) = item.enclosure() {
+        links.push(Link {
+            href: enclosure.url().to_owned(),
+            rel: String::from("enclosure"),
+            mime_type: Some(enclosure.mime_type().to_owned()),
+            length: Some(enclosure.length().to_owned()),
+            ..Default::default()
+        });
+    }
+
+    Entry {
+        title: Text::plain(item.title().unwrap_or("[no title]")),
+        id: atom_id(&item_id(item), uuid),
+        updated: published.unwrap_or_else(|| chrono::Utc::now().into()),
+        published,
+        authors: item_author(item).map(|name| vec![Person { name, ..Default::default() }]).unwrap_or_default(),
+        links,
+        summary: item.description().map(Text::html),
+        content: item.content().map(|content| Content {
+            value: Some(content.to_owned()),
+            content_type: Some(String::from("html")),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   json_feed
+//
+//  NOTES:
+//      Builds a JSON Feed 1.1 out of a finished racer channel. Every item needs content, so the
+//      description stands in when there's no separate content.
+//  ARGS:
+//      channel - The racer channel, as written to racer.rss
+//      feed_url - Where the JSON Feed is served
+//  RETURN: The JSON Feed
+//
+pub(crate) fn json_feed(channel: &rss::Channel, feed_url: &str) -> JsonFeed {
+    let items = channel.items().iter()
+        .map(|item| JsonItem {
+            id: item_id(item),
+            url: item.link().map(str::to_owned),
+            title: item.title().map(str::to_owned),
+            content_html: item.content().or(item.description()).unwrap_or("").to_owned(),
+            image: item.itunes_ext().and_then(|itunes| itunes.image()).map(str::to_owned),
+            date_published: item.pub_date()
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                .map(|date| date.to_rfc3339()),
+            authors: item_author(item).map(|name| vec![JsonAuthor { name }]).unwrap_or_default(),
+            attachments: item.enclosure()
+                .map(|enclosure| vec![JsonAttachment {
+                    url: enclosure.url().to_owned(),
+                    mime_type: enclosure.mime_type().to_owned(),
+                    size_in_bytes: enclosure.length().trim().parse().ok(),
+                }])
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    JsonFeed {
+        version: JSON_FEED_VERSION.to_owned(),
+        title: channel.title().to_owned(),
+        home_page_url: match channel.link().is_empty() {
+            true => None,
+            false => Some(channel.link().to_owned()),
+        },
+        feed_url: feed_url.to_owned(),
+        description: Some(channel.description().to_owned()),
+        icon: channel_image(channel),
+        authors: vec![JsonAuthor { name: channel_author(channel) }],
+        items,
+    }
+}
+
+// The iTunes author, then the managing editor, then the podcast's name
+fn channel_author(channel: &rss::Channel) -> String {
+    channel.itunes_ext()
+        .and_then(|itunes| itunes.author())
+        .or(channel.managing_editor())
+        .unwrap_or(channel.title())
+        .to_owned()
+}
+
+fn item_author(item: &rss::Item) -> Option<String> {
+    item.itunes_ext()
+        .and_then(|itunes| itunes.author())
+        .or(item.author())
+        .map(str::to_owned)
+}
+
+fn channel_image(channel: &rss::Channel) -> Option<String> {
+    channel.itunes_ext()
+        .and_then(|itunes| itunes.image())
+        .or(channel.image().map(|image| image.url()))
+        .map(str::to_owned)
+}
+
+// Ids that already look like a URI are kept. Anything else goes under the racer's urn.
+fn atom_id(id: &str, uuid: &str) -> String {
+    if id.contains("://") || id.starts_with("urn:") || id.starts_with("tag:") {
+        return id.to_owned();
+    }
+    namespaced_id(id, uuid)
+}
+
+// `id` under the racer's urn. The same id and uuid always give the same urn.
+pub(crate) fn namespaced_id(id: &str, uuid: &str) -> String {
+    format!("urn:podracer:{}:{}", uuid, percent_encode(id))
+}
+
+// Everything but unreserved characters percent-encoded, so it's safe in a urn or url path segment
+pub(crate) fn percent_encode(id: &str) -> String {
+    let mut encoded = String::new();
+    for byte in id.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,55 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   history.rs
+//
+//  © Zach Nielsen 2020
+//  A short journal of feed edits, so they can be undone
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::RacerType;
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// How many edits we remember. The oldest ones fall off the end.
+pub const MAX_HISTORY: usize = 20;
+
+// Where the feed was before an edit, and what the edit was
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<chrono::Utc>,
+    pub action: String,
+    pub anchor_date: DateTime<chrono::Utc>,
+    pub rate: RacerType,
+    pub pause_date: Option<DateTime<chrono::Utc>>,
+}
+
+impl HistoryEntry {
+    // True if both entries describe the same feed position, regardless of when or why
+    pub fn same_state(&self, other: &HistoryEntry) -> bool {
+        self.anchor_date == other.anchor_date
+            && self.pause_date == other.pause_date
+            && self.rate.to_string() == other.rate.to_string()
+    }
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} (anchor: {}, rate: {}", self.timestamp.to_rfc2822(), self.action,
+               self.anchor_date.to_rfc2822(), self.rate)?;
+        if let Some(pause_date) = self.pause_date {
+            write!(f, ", paused {}", pause_date.to_rfc2822())?;
+        }
+        write!(f, ")")
+    }
+}
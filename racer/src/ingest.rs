@@ -0,0 +1,244 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   ingest.rs
+//
+//  © Zach Nielsen 2020
+//  Reading upstream feeds in whatever format they come in. Everything is turned into an
+//  rss::Channel, which is what the rest of the racer works with.
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::DateTime;
+use serde::Deserialize;
+
+use crate::scrub_xml_content;
+
+use std::io::BufReader;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// The formats we can race
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    JsonFeed,
+}
+
+impl FeedFormat {
+    // Guesses the format from the start of the document. None if it's none of ours.
+    pub fn detect(content: &[u8]) -> Option<FeedFormat> {
+        let text = String::from_utf8_lossy(&content[..std::cmp::min(content.len(), 4096)]);
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('{') {
+            return Some(FeedFormat::JsonFeed);
+        }
+        // Skip the prolog, comments and doctype to get to the root element
+        let mut rest = text;
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            if rest.starts_with('?') || rest.starts_with('!') {
+                continue;
+            }
+            let name: String = rest.chars().take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/').collect();
+            // Prefixed roots (<atom:feed>, <rdf:RDF>) go by their local name
+            return match name.rsplit(':').next().unwrap_or("") {
+                // RSS 1.0 is RDF, which the rss crate reads too
+                "rss" | "RDF" => Some(FeedFormat::Rss),
+                "feed" => Some(FeedFormat::Atom),
+                _ => None,
+            };
+        }
+        None
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   parse_feed
+//
+//  NOTES:
+//      Reads a downloaded feed of any supported format into a channel. Atom entries and JSON Feed
+//      items become rss items: ids become guids, enclosure links/attachments become enclosures
+//      and dates are turned into rfc2822. Entries without any date can't be raced, so they're
+//      left out.
+//  ARGS:   content - The downloaded feed
+//  RETURN: The feed as a channel, or why it couldn't be read
+//
+pub fn parse_feed(content: &[u8]) -> Result<rss::Channel, String> {
+    match FeedFormat::detect(content) {
+        Some(FeedFormat::Rss) => {
+            let scrubbed_content = scrub_xml_content(BufReader::new(content));
+            match rss::Channel::read_from(BufReader::new(&scrubbed_content[..])) {
+                Ok(val) => Ok(val),
+                Err(e) => Err(format!("Error reading rss: {}", e)),
+            }
+        },
+        Some(FeedFormat::Atom) => match atom_syndication::Feed::read_from(BufReader::new(content)) {
+            Ok(val) => Ok(channel_from_atom(&val)),
+            Err(e) => Err(format!("Error reading Atom: {}", e)),
+        },
+        Some(FeedFormat::JsonFeed) => match serde_json::from_slice::<JsonFeedIn>(content) {
+            Ok(val) => channel_from_json_feed(&val),
+            Err(e) => Err(format!("Error reading JSON Feed: {}", e)),
+        },
+        None => Err(String::from("Not an rss, Atom or JSON Feed document")),
+    }
+}
+
+//
+// Atom
+//
+fn channel_from_atom(feed: &atom_syndication::Feed) -> rss::Channel {
+    let mut channel = rss::Channel::default();
+    channel.set_title(feed.title().as_str());
+    channel.set_link(atom_link(feed.links(), "alternate").unwrap_or(feed.id()));
+    channel.set_description(feed.subtitle().map(|subtitle| subtitle.as_str()).unwrap_or(""));
+    if let Some(url) = feed.logo().or(feed.icon()) {
+        channel.set_image(channel_image(url, &channel));
+    }
+
+    let mut items = Vec::new();
+    for entry in feed.entries() {
+        let date = entry.published().unwrap_or(entry.updated());
+        let mut item = rss::Item::default();
+        item.set_title(entry.title().as_str().to_owned());
+        item.set_guid(rss::Guid { value: entry.id().to_owned(), permalink: false });
+        item.set_pub_date(date.to_rfc2822());
+        item.set_link(atom_link(entry.links(), "alternate").map(str::to_owned));
+        item.set_author(entry.authors().first().map(|author| author.name().to_owned()));
+        item.set_description(entry.summary().map(|summary| summary.as_str().to_owned()));
+        item.set_content(entry.content().and_then(|content| content.value()).map(str::to_owned));
+        if let Some(link) = entry.links().iter().find(|link| link.rel() == "enclosure") {
+            item.set_enclosure(rss::Enclosure {
+                url: link.href().to_owned(),
+                length: link.length().unwrap_or("0").to_owned(),
+                mime_type: link.mime_type().unwrap_or("application/octet-stream").to_owned(),
+            });
+        }
+        items.push(item);
+    }
+    channel.set_items(items);
+    channel
+}
+
+// The href of the first link with relation `rel`. Links without a rel are alternates.
+fn atom_link<'a>(links: &'a [atom_syndication::Link], rel: &str) -> Option<&'a str> {
+    links.iter()
+        .find(|link| link.rel() == rel)
+        .map(|link| link.href())
+}
+
+//
+// JSON Feed
+//
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct JsonFeedIn {
+    version: String,
+    title: String,
+    home_page_url: Option<String>,
+    feed_url: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    items: Vec<JsonItemIn>,
+}
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct JsonItemIn {
+    // Should be a string, but some feeds use numbers
+    id: serde_json::Value,
+    url: Option<String>,
+    title: Option<String>,
+    content_html: Option<String>,
+    content_text: Option<String>,
+    summary: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    // `author` is from version 1.0, `authors` from 1.1
+    author: Option<JsonAuthorIn>,
+    authors: Vec<JsonAuthorIn>,
+    attachments: Vec<JsonAttachmentIn>,
+}
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct JsonAuthorIn {
+    name: Option<String>,
+}
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct JsonAttachmentIn {
+    url: String,
+    mime_type: String,
+    size_in_bytes: Option<u64>,
+    duration_in_seconds: Option<f64>,
+}
+
+fn channel_from_json_feed(feed: &JsonFeedIn) -> Result<rss::Channel, String> {
+    if !feed.version.starts_with("https://jsonfeed.org/version/") {
+        return Err(format!("Unknown JSON Feed version {}", feed.version));
+    }
+    let mut channel = rss::Channel::default();
+    channel.set_title(feed.title.as_str());
+    channel.set_link(feed.home_page_url.as_deref().or(feed.feed_url.as_deref()).unwrap_or(""));
+    channel.set_description(feed.description.as_deref().unwrap_or(""));
+    if let Some(url) = &feed.icon {
+        channel.set_image(channel_image(url, &channel));
+    }
+
+    let mut items = Vec::new();
+    for json_item in &feed.items {
+        let id = match &json_item.id {
+            serde_json::Value::String(id) => id.to_owned(),
+            serde_json::Value::Null => json_item.url.clone().unwrap_or_default(),
+            other => other.to_string(),
+        };
+        let date = json_item.date_published.as_deref()
+            .or(json_item.date_modified.as_deref())
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok());
+        let date = match date {
+            Some(val) => val,
+            None => {
+                println!("Skipping JSON Feed item {} with no publish date", id);
+                continue;
+            },
+        };
+
+        let mut item = rss::Item::default();
+        item.set_title(json_item.title.clone());
+        item.set_guid(rss::Guid { value: id, permalink: false });
+        item.set_pub_date(date.to_rfc2822());
+        item.set_link(json_item.url.clone());
+        item.set_author(json_item.authors.iter().chain(json_item.author.iter())
+            .find_map(|author| author.name.clone()));
+        item.set_description(json_item.summary.clone().or(json_item.content_text.clone()));
+        item.set_content(json_item.content_html.clone());
+        if let Some(attachment) = json_item.attachments.first() {
+            item.set_enclosure(rss::Enclosure {
+                url: attachment.url.to_owned(),
+                length: attachment.size_in_bytes.unwrap_or(0).to_string(),
+                mime_type: attachment.mime_type.to_owned(),
+            });
+            // Lets duration based exclude rules work on JSON Feeds too
+            if let Some(duration) = attachment.duration_in_seconds {
+                let mut itunes = rss::extension::itunes::ITunesItemExtension::default();
+                itunes.set_duration((duration as u64).to_string());
+                item.set_itunes_ext(itunes);
+            }
+        }
+        items.push(item);
+    }
+    channel.set_items(items);
+    Ok(channel)
+}
+
+fn channel_image(url: &str, channel: &rss::Channel) -> rss::Image {
+    rss::Image {
+        url: url.to_owned(),
+        title: channel.title().to_owned(),
+        link: channel.link().to_owned(),
+        ..Default::default()
+    }
+}
+
@@ -10,36 +10,82 @@
 ////////////////////////////////////////////////////////////////////////////////
 //  Included Modules
 ////////////////////////////////////////////////////////////////////////////////
+mod schedule;
+mod history;
+mod exclude;
+mod episode;
+mod season;
+mod order;
+mod source;
+mod catchup;
+mod metadata;
+mod formats;
+mod ingest;
+mod footer;
+mod decoration;
+mod artwork;
+mod media;
+mod archive;
+mod local;
 
 ////////////////////////////////////////////////////////////////////////////////
 //  Namespaces
 ////////////////////////////////////////////////////////////////////////////////
 use futures::{stream, StreamExt};
-use chrono::{DateTime, Duration, Local};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::io::{BufRead, BufReader, Write};
 use std::fs::File;
 use std::fmt;
+use std::sync::OnceLock;
+
+pub use schedule::{BlackoutRange, QuietHours, ScheduleConstraints, ScheduledPause};
+pub use history::{HistoryEntry, MAX_HISTORY};
+pub use exclude::{ExcludeMatcher, ExcludeRules};
+pub use episode::{EpisodeInfo, EpisodeType, EpisodeTypePolicy};
+pub use season::{SeasonConfig, SeasonRate, SeasonSource};
+pub use order::{EpisodeOrder, NewEpisodePlacement, Playlist};
+pub use source::FeedSource;
+pub use catchup::{CatchUpPolicy, CaughtUp};
+pub use formats::{ATOM_MIME_TYPE, JSON_FEED_MIME_TYPE};
+pub use ingest::{parse_feed, FeedFormat};
+pub use footer::{validate_footer_template, write_footer_settings, FeedFooter, FooterSettings, DEFAULT_FOOTER_TEMPLATE};
+pub use decoration::{ArtworkBadge, ChannelDecoration, FeedStatus, TitleStyle, TITLE_SUFFIX};
+pub use artwork::ARTWORK_MIME_TYPE;
+pub use media::{parse_range, ByteRange, Media, MediaBody, MediaSource, MEDIA_DIR};
+pub use archive::{ArchiveManifest, ArchivedEpisode, ARCHIVE_MANIFEST_FILE, ARCHIVE_MANIFEST_VERSION};
+pub use local::{check_local_dir, read_local_channel, read_tags, AudioTags, LOCAL_SCHEME};
 
 ////////////////////////////////////////////////////////////////////////////////
 //  Code
 ////////////////////////////////////////////////////////////////////////////////
-pub const SCHEMA_VERSION: &'static str = "1.1";
+pub const SCHEMA_VERSION: &'static str = "1.3";
 // pub const PODRACER_DIR: &'static str = "/etc/podracer/podcasts";
 
 pub const ORIGINAL_RSS_FILE: &'static str = "original.rss";
 pub const RACER_RSS_FILE: &'static str = "racer.rss";
+pub const RACER_ATOM_FILE: &str = "racer.atom";
+pub const RACER_JSON_FILE: &str = "racer.json";
+pub const RACER_ARTWORK_FILE: &str = "racer.png";
 pub const RACER_FILE: &'static str = "racer.file";
+// The archive for the single source of a racer from before schema 1.3
+pub const MIGRATED_SOURCE_ARCHIVE_FILE: &str = "source_0.rss";
 pub const INDENT_AMOUNT: usize = 2; // For pretty printing rss files
 pub const SPACE_CHAR: u8 = 32; // ASCII ' '
+// Used when a feed has no (or an unparseable) timezone saved
+pub const DEFAULT_TIMEZONE: &str = "America/Los_Angeles";
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum RacerType {
     Ratio(f64),
-    Days(u32)
+    Days(u32),
+    // Mirror the original feed this many days late
+    Delay(u32),
 }
 
 // All parameters we need to create a PodRacer feed
@@ -48,15 +94,31 @@ pub struct RacerCreationParams {
     pub podracer_dir: String,
     pub host: String,
     pub url: String,
+    // More feeds to merge in with `url`, e.g. an old host or a separate archive feed
+    pub extra_urls: Vec<String>,
     pub start_ep: usize,
     pub port: u32,
     pub rate: RacerType,
+    pub timezone: String,
+    pub publish_time: Option<NaiveTime>,
+    // Race season by season. Optional.
+    pub seasons: Option<SeasonConfig>,
+    // Start from the first episode of this season instead of start_ep. Needs `seasons`.
+    pub start_season: Option<u32>,
+    pub order: EpisodeOrder,
+    // Seed, curated GUIDs and new episode placement, for shuffled and curated orders
+    pub playlist: Playlist,
+    pub catch_up_policy: CatchUpPolicy,
+    // Local directory sources have to be inside this directory. None means they aren't allowed.
+    pub local_media_root: Option<String>,
 }
 
 pub struct UpdateMetadata {
     pub num_updated: u64,
     pub time: std::time::Duration,
     pub num_with_new_eps: u64,
+    // Racers that caught up to the live feed this time around
+    pub num_caught_up: u64,
 }
 
 // Should we attempt to download the original RSS file, or just look at what we have?
@@ -72,6 +134,32 @@ pub struct RacerEpisode {
     ep_num: i64,
     date: String,
     title: String,
+    #[serde(default)]
+    info: EpisodeInfo,
+}
+
+// Where the feed landed after moving by some number of episodes
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavigationResult {
+    // How many episodes we were asked to move
+    pub requested: usize,
+    // How many episodes we actually moved
+    pub moved: usize,
+    // How many episodes are published now
+    pub num_published: usize,
+    // True if we hit the first or last episode before moving the full amount
+    pub clamped: bool,
+}
+impl NavigationResult {
+    fn new(requested: usize, previously_published: usize, num_published: usize) -> NavigationResult {
+        let moved = num_published.abs_diff(previously_published);
+        NavigationResult {
+            requested,
+            moved,
+            num_published,
+            clamped: moved < requested,
+        }
+    }
 }
 
 // All the fields of our racer file. Info we might want across sessions.
@@ -81,14 +169,85 @@ pub struct FeedRacer {
     podcast_title: String,
     uuid: String,
     rate: RacerType,
+    // Only present in racer files from before pausing was tracked separately from the rate.
+    // See update_to_current_schema_version.
+    #[serde(default, skip_serializing)]
     old_rate: Option<RacerType>,
     racer_path: PathBuf,
+    // The url of the first source
     source_url: String,
     subscribe_url: String,
     anchor_date: DateTime<chrono::Utc>,
     pause_date: Option<DateTime<chrono::Utc>>,
     first_pubdate: DateTime<chrono::FixedOffset>,
-    release_dates: Vec<RacerEpisode>
+    release_dates: Vec<RacerEpisode>,
+    // IANA name, e.g. "America/Los_Angeles"
+    #[serde(default = "default_timezone")]
+    timezone: String,
+    // Preferred local time of day for episodes to drop
+    #[serde(default)]
+    publish_time: Option<NaiveTime>,
+    // Windows where nothing is allowed to publish
+    #[serde(default)]
+    constraints: ScheduleConstraints,
+    // Pauses to apply + lift automatically from the update thread
+    #[serde(default)]
+    scheduled_pauses: Vec<ScheduledPause>,
+    // Set when the feed was paused by hand, so the end of a scheduled pause doesn't lift it
+    #[serde(default)]
+    manual_pause: bool,
+    // Recent edits, oldest first, so they can be undone
+    #[serde(default)]
+    history: Vec<HistoryEntry>,
+    // Episodes that never get scheduled
+    #[serde(default)]
+    exclusions: ExcludeRules,
+    // `exclusions` compiled the first time they're needed. Reset whenever they change.
+    #[serde(skip)]
+    exclude_matcher: OnceLock<ExcludeMatcher>,
+    // What to do with trailers and bonus episodes
+    #[serde(default)]
+    episode_type_policy: EpisodeTypePolicy,
+    // Per-season rates and breaks, if racing season by season
+    #[serde(default)]
+    seasons: Option<SeasonConfig>,
+    // Which order episodes go out in
+    #[serde(default)]
+    order: EpisodeOrder,
+    // The fixed order for shuffled and curated orders
+    #[serde(default)]
+    playlist: Playlist,
+    // What happens to episodes that come out after we catch up
+    #[serde(default)]
+    catch_up_policy: CatchUpPolicy,
+    // Set once every episode is published, cleared if the feed is rewound past that point
+    #[serde(default)]
+    caught_up: Option<CaughtUp>,
+    // Every feed merged into this racer, most important first. Filled in from source_url for
+    // racer files from before 1.3.
+    #[serde(default)]
+    sources: Vec<FeedSource>,
+    // The footer added to each episode's description
+    #[serde(default)]
+    footer: FeedFooter,
+    // The title, status line and artwork of the racer feed
+    #[serde(default)]
+    decoration: ChannelDecoration,
+    // What racer.png was last made from
+    #[serde(default)]
+    badge_artwork: Option<artwork::BadgeArtwork>,
+    // Whether published items get GUIDs under this racer's uuid instead of the upstream ones
+    #[serde(default)]
+    namespace_guids: bool,
+    // Whether enclosures point at this server's media route instead of the upstream host
+    #[serde(default)]
+    proxy_enclosures: bool,
+    // A podarch directory to serve episodes from
+    #[serde(default)]
+    archive_dir: Option<PathBuf>,
+}
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_owned()
 }
 // Basic getter/setter functions
 impl FeedRacer {
@@ -103,6 +262,22 @@ impl FeedRacer {
     pub fn get_subscribe_url(&self) -> &str {
         &self.subscribe_url
     }
+    // The same feed, as Atom
+    pub fn get_atom_url(&self) -> String {
+        self.sibling_url(RACER_ATOM_FILE)
+    }
+    // The same feed, as JSON Feed
+    pub fn get_json_feed_url(&self) -> String {
+        self.sibling_url(RACER_JSON_FILE)
+    }
+    // The badged artwork, if the feed has a badge
+    pub fn get_artwork_url(&self) -> String {
+        self.sibling_url(RACER_ARTWORK_FILE)
+    }
+    // Where the episode with GUID `id` is served from when enclosures are proxied
+    pub fn get_media_url(&self, id: &str) -> String {
+        self.sibling_url(&format!("{}/{}", MEDIA_DIR, formats::percent_encode(id)))
+    }
     pub fn get_anchor_date(&self) -> DateTime<chrono::Utc> {
         self.anchor_date
     }
@@ -133,11 +308,14 @@ impl FeedRacer {
     pub fn get_source_url(&self) -> &str {
         &self.source_url
     }
+    pub fn get_sources(&self) -> &Vec<FeedSource> {
+        &self.sources
+    }
     pub fn get_rate(&self) -> RacerType {
         self.rate.clone()
     }
-    pub fn get_old_rate(&self) -> Option<RacerType> {
-        self.old_rate.clone()
+    pub fn is_paused(&self) -> bool {
+        self.pause_date.is_some()
     }
     pub fn get_pause_date(&self) -> Option<DateTime<chrono::Utc>> {
         self.pause_date
@@ -152,74 +330,420 @@ impl FeedRacer {
         //     None => String::from("No UUID"),
         // }
     }
-    pub fn get_current_ep_title(&self) -> String {
-        self.release_dates[self.get_next_episode_num()-1].title.clone()
+    // None if nothing is published yet
+    pub fn get_current_ep_title(&self) -> Option<String> {
+        let num_published = self.get_next_episode_num();
+        if num_published == 0 {
+            return None;
+        }
+        self.release_dates.get(num_published - 1).map(|ep| ep.title.clone())
+    }
+    // None if everything is published
+    pub fn get_next_ep_title(&self) -> Option<String> {
+        self.release_dates.get(self.get_next_episode_num()).map(|ep| ep.title.clone())
+    }
+    pub fn get_timezone(&self) -> Tz {
+        match self.timezone.parse::<Tz>() {
+            Ok(tz) => tz,
+            Err(e) => {
+                println!("Error parsing saved timezone {}: {}. Using {}.", self.timezone, e, DEFAULT_TIMEZONE);
+                DEFAULT_TIMEZONE.parse::<Tz>().expect("Default timezone is valid")
+            }
+        }
+    }
+    pub fn get_publish_time(&self) -> Option<NaiveTime> {
+        self.publish_time
+    }
+    pub fn get_schedule_constraints(&self) -> &ScheduleConstraints {
+        &self.constraints
+    }
+    pub fn get_exclude_rules(&self) -> &ExcludeRules {
+        &self.exclusions
+    }
+    pub fn get_episode_order(&self) -> EpisodeOrder {
+        self.order
+    }
+    pub fn get_playlist(&self) -> &Playlist {
+        &self.playlist
+    }
+    pub fn get_catch_up_policy(&self) -> CatchUpPolicy {
+        self.catch_up_policy
+    }
+    // None until the racer has caught up to the live feed
+    pub fn get_caught_up(&self) -> Option<&CaughtUp> {
+        self.caught_up.as_ref()
+    }
+    pub fn get_footer(&self) -> &FeedFooter {
+        &self.footer
+    }
+    pub fn get_decoration(&self) -> &ChannelDecoration {
+        &self.decoration
+    }
+    pub fn get_namespace_guids(&self) -> bool {
+        self.namespace_guids
+    }
+    pub fn get_proxy_enclosures(&self) -> bool {
+        self.proxy_enclosures
+    }
+    pub fn get_archive_dir(&self) -> Option<&Path> {
+        self.archive_dir.as_deref()
+    }
+    pub fn get_season_config(&self) -> Option<&SeasonConfig> {
+        self.seasons.as_ref()
+    }
+    pub fn get_episode_type_policy(&self) -> &EpisodeTypePolicy {
+        &self.episode_type_policy
+    }
+    // Episode info for episode `num` (0 indexed), as read from the original feed
+    pub fn get_episode_info(&self, num: usize) -> Option<&EpisodeInfo> {
+        self.release_dates.get(num).map(|ep| &ep.info)
     }
-    pub fn get_next_ep_title(&self) -> String {
-        self.release_dates[self.get_next_episode_num()].title.clone()
+    // Titles of the stored episodes that are being skipped, either by the exclude rules or
+    // because they're trailers
+    pub fn get_excluded_titles(&self) -> Vec<String> {
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => {
+                println!("Error reading stored items to list exclusions: {}", e);
+                return Vec::new();
+            }
+        };
+        let scheduled = self.scheduled_indices(&items);
+        items.iter()
+            .enumerate()
+            .filter(|(idx, _)| !scheduled.contains(idx))
+            .map(|(_, item)| item.title().unwrap_or("[no title]").to_owned())
+            .collect()
+    }
+    pub fn get_history(&self) -> &Vec<HistoryEntry> {
+        &self.history
+    }
+    pub fn get_scheduled_pauses(&self) -> &Vec<ScheduledPause> {
+        &self.scheduled_pauses
     }
 
     ////////////////////////////////////////////////////////////////////////////////
     // Setters
     ////////////////////////////////////////////////////////////////////////////////
     pub async fn set_rate_ratio(&mut self, new_rate: f64) -> Result<(), String> {
-        // Adjust the anchor date to keep the same episode count published
-        let current_rate = match self.rate {
-            RacerType::Ratio(rate) => rate,
-            RacerType::Days(_) => {
-                let current_episode_racer_pub_date = DateTime::parse_from_rfc2822(&self.release_dates[self.get_num_to_publish()].date).expect("date is valid");
-                let anchor_to_now = current_episode_racer_pub_date.signed_duration_since(self.anchor_date).num_seconds() as f64;
-                let current_episode_original_pub_date = self.get_episode_original_pub_date(self.get_num_to_publish());
-                let first_to_cur = current_episode_original_pub_date.signed_duration_since(self.first_pubdate).num_seconds() as f64;
-                first_to_cur / anchor_to_now
-            },
-        };
-        let adjust_ratio = (current_rate / new_rate) as f64;
-        let now = chrono::Utc::now();
-        let anchor_to_now = now.signed_duration_since(self.anchor_date).num_seconds() as f64;
-        let new_anchor_to_now = anchor_to_now * adjust_ratio;
-        let anchor_adjustment_seconds = anchor_to_now - new_anchor_to_now;
-        let adjust_duration = Duration::seconds(anchor_adjustment_seconds as i64);
-        self.anchor_date = match self.anchor_date.checked_add_signed(adjust_duration) {
-            Some(val) => val,
-            None => return Err(String::from("Anchor date adjustment overflow")),
+        if new_rate <= 0.0 {
+            return Err(format!("Rate must be > 0, got {}", new_rate));
+        }
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error getting stored items to set rate: {}", e)),
         };
+        let num_published = self.get_num_to_publish();
+
+        // Scale the time since the anchor date so we keep our place in the original timeline.
+        // Measured against the pause date if paused, since that's where the feed is stopped.
+        if let RacerType::Ratio(current_rate) = self.rate {
+            let adjust_ratio = current_rate / new_rate;
+            let anchor_to_now = self.get_reference_date().signed_duration_since(self.anchor_date).num_seconds() as f64;
+            let new_anchor_to_now = anchor_to_now * adjust_ratio;
+            let anchor_adjustment_seconds = anchor_to_now - new_anchor_to_now;
+            let adjust_duration = Duration::seconds(anchor_adjustment_seconds as i64);
+            self.anchor_date = match self.anchor_date.checked_add_signed(adjust_duration) {
+                Some(val) => val,
+                None => return Err(String::from("Anchor date adjustment overflow")),
+            };
+        }
 
-        // Update the rate then adjust the feed
+        // Update the rate, make sure we didn't publish or unpublish anything, then adjust the feed
         self.rate = RacerType::Ratio(new_rate);
+        self.keep_num_published(&items, num_published);
         match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
             Ok(_) => Ok(()),
             Err(e) => Err(format!("Error updating feed after setting rate: {}", e)),
         }
     }
     pub async fn set_rate_days(&mut self, new_days_span: u32) -> Result<(), String> {
-        // Adjust the anchor date to keep the same episode count published
-        let adjustment_days: i64 = match self.rate {
-            RacerType::Days(days) => {
-                let days_span_diff = days as i64 - new_days_span as i64;
-                self.get_num_to_publish() as i64 * days_span_diff as i64
-            },
-            RacerType::Ratio(_) => {
-                let days_needed = self.get_num_to_publish() as i64 * new_days_span as i64;
-                let now = chrono::Utc::now();
-                let anchor_to_now_days = now.signed_duration_since(self.anchor_date).num_days();
-                (anchor_to_now_days - days_needed) + 1
-            }
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error getting stored items to set rate: {}", e)),
         };
+        let num_published = self.get_num_to_publish();
 
-        let adjustment_duration = Duration::days(adjustment_days);
-        self.anchor_date = match self.anchor_date.checked_add_signed(adjustment_duration) {
-            Some(val) => val,
-            None => return Err(String::from("Anchor date adjustment overflow")),
-        };
+        // Keep the next episode's publish date where it is
+        if let RacerType::Days(days) = self.rate {
+            let days_span_diff = days as i64 - new_days_span as i64;
+            let adjustment_duration = Duration::days(num_published as i64 * days_span_diff);
+            self.anchor_date = match self.anchor_date.checked_add_signed(adjustment_duration) {
+                Some(val) => val,
+                None => return Err(String::from("Anchor date adjustment overflow")),
+            };
+        }
 
-        // Adjust the racer type to days, then update
+        // Adjust the racer type to days, make sure we didn't publish or unpublish anything, then update
         self.rate = RacerType::Days(new_days_span);
+        self.keep_num_published(&items, num_published);
         match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
             Ok(_) => Ok(()),
             Err(e) => Err(format!("Error updating feed after setting rate: {}", e)),
         }
     }
+    pub async fn set_rate_delay(&mut self, days: u32) -> Result<(), String> {
+        if days == 0 {
+            return Err(String::from("Delay must be > 0 days"));
+        }
+        // A mirror is never racing to catch up, so there's no published count to keep
+        self.rate = RacerType::Delay(days);
+        self.caught_up = None;
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after setting delay: {}", e)),
+        }
+    }
+    pub async fn set_publish_schedule(&mut self, timezone: &str, publish_time: Option<NaiveTime>) -> Result<(), String> {
+        if let Err(e) = timezone.parse::<Tz>() {
+            return Err(format!("Unknown timezone {}: {}", timezone, e));
+        }
+        self.timezone = timezone.to_owned();
+        self.publish_time = publish_time;
+        self.forget_history();
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after setting publish schedule: {}", e)),
+        }
+    }
+    pub async fn set_schedule_constraints(&mut self, constraints: ScheduleConstraints) -> Result<(), String> {
+        self.constraints = constraints;
+        self.forget_history();
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after setting schedule constraints: {}", e)),
+        }
+    }
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_exclude_rules
+    //
+    //  NOTES:  Swaps in new exclude rules. See reschedule_keeping_latest.
+    //  ARGS:   exclusions - The new rules
+    //  RETURN: An error if the rules are invalid or the feed couldn't be updated
+    //
+    pub async fn set_exclude_rules(&mut self, exclusions: ExcludeRules) -> Result<(), String> {
+        exclusions.validate()?;
+        self.reschedule_keeping_latest(|racer| {
+            racer.exclusions = exclusions;
+            racer.exclude_matcher = OnceLock::new();
+        }).await
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_season_config
+    //
+    //  NOTES:  Starts (or stops, with None) racing season by season. Keeps the published count.
+    //  ARGS:   seasons - The new season config
+    //  RETURN: An error if the config is invalid or the feed couldn't be updated
+    //
+    pub async fn set_season_config(&mut self, seasons: Option<SeasonConfig>) -> Result<(), String> {
+        if let Some(config) = &seasons {
+            config.validate()?;
+        }
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error reading stored items: {}", e)),
+        };
+        let num_published = self.get_num_to_publish();
+        self.seasons = seasons;
+        self.forget_history();
+        self.keep_num_published(&items, num_published);
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing seasons: {}", e)),
+        }
+    }
+
+    // Changes which order episodes go out in. Keeps the published count.
+    pub async fn set_episode_order(&mut self, order: EpisodeOrder) -> Result<(), String> {
+        let playlist = self.playlist.clone();
+        self.set_playback_order(order, playlist).await
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_playback_order
+    //
+    //  NOTES:
+    //      Like set_episode_order, but with new playlist settings too. Shuffled and curated
+    //      orders are worked out from scratch here, and only extended from then on.
+    //  ARGS:
+    //      order - The new order
+    //      playlist - Seed, curated GUIDs and new episode placement
+    //  RETURN: An error if the curated list doesn't match the feed or the feed couldn't be updated
+    //
+    pub async fn set_playback_order(&mut self, order: EpisodeOrder, mut playlist: Playlist) -> Result<(), String> {
+        if order.uses_playlist() {
+            let items = match self.get_stored_rss() {
+                Ok(val) => order::order_items(val.items().to_owned(), EpisodeOrder::PubDate, &playlist),
+                Err(e) => return Err(format!("Error reading stored items: {}", e)),
+            };
+            playlist.resolve(order, &items)?;
+        }
+        let num_published = self.get_num_to_publish();
+        self.order = order;
+        self.playlist = playlist;
+        self.forget_history();
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error reading stored items: {}", e)),
+        };
+        self.keep_num_published(&items, num_published);
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing episode order: {}", e)),
+        }
+    }
+
+    pub async fn set_episode_type_policy(&mut self, policy: EpisodeTypePolicy) -> Result<(), String> {
+        self.reschedule_keeping_latest(|racer| racer.episode_type_policy = policy).await
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_sources
+    //
+    //  NOTES:
+    //      Changes which feeds this racer is built from. New feeds have to download before we
+    //      accept them. Dropped feeds take their archives (and any items only they had) with them.
+    //      The latest published episode stays the latest, like reschedule_keeping_latest.
+    //  ARGS:
+    //      urls - Every source, most important first. The first one keeps naming the podcast.
+    //      local_media_root - Where local directory sources are allowed, see local::check_local_dir
+    //      client - For fetching the sources
+    //  RETURN: An error if a url is bad or the feed couldn't be updated
+    //
+    pub async fn set_sources(&mut self, urls: &[String], local_media_root: Option<&Path>, client: &reqwest::Client)
+        -> Result<(), String> {
+        let mut new_urls: Vec<String> = Vec::new();
+        for url in urls.iter().filter(|url| !url.trim().is_empty()) {
+            let url = local::check_local_url(source::normalize_url(url), local_media_root)?;
+            if !new_urls.contains(&url) {
+                new_urls.push(url);
+            }
+        }
+        if new_urls.is_empty() {
+            return Err(String::from("A feed needs at least one source"));
+        }
+        // New sources are downloaded once, here. What we get seeds their archives.
+        let mut fetched: Vec<(&String, rss::Channel)> = Vec::new();
+        for url in &new_urls {
+            if self.sources.iter().all(|source| &source.url != url) {
+                match download_rss_channel(client, url).await.map_err(|e| e.to_string()) {
+                    Ok(val) => fetched.push((url, val)),
+                    Err(e) => return Err(format!("Error downloading rss feed {}: {}", url, e)),
+                }
+            }
+        }
+
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error reading stored items: {}", e)),
+        };
+        let last_published = match self.get_num_to_publish() {
+            0 => None,
+            num => self.scheduled_items(&items).get(num - 1).cloned(),
+        };
+
+        let old_sources = std::mem::take(&mut self.sources);
+        for url in &new_urls {
+            match old_sources.iter().find(|source| &source.url == url) {
+                Some(source) => self.sources.push(source.clone()),
+                None => {
+                    let mut source = FeedSource::new(url);
+                    if let Some((_, channel)) = fetched.iter().find(|(fetched_url, _)| *fetched_url == url) {
+                        write_channel(channel, &self.racer_path.join(&source.archive_file))?;
+                        source.record_fetch::<String>(Ok(channel.items().len()));
+                    }
+                    self.sources.push(source);
+                },
+            }
+        }
+        for source in old_sources.iter().filter(|source| !new_urls.contains(&source.url)) {
+            match std::fs::remove_file(self.racer_path.join(&source.archive_file)) {
+                Ok(_) => (),
+                Err(e) => println!("Error removing the archive for {}: {}", source.url, e),
+            }
+        }
+        self.source_url = new_urls[0].clone();
+        self.forget_history();
+
+        // Rebuild the merged archive from the new set of sources. The sources we already had
+        // are refreshed on the next update.
+        self.merge_source_archives()?;
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error reading stored items: {}", e)),
+        };
+        let num_published = match last_published {
+            Some(last) => {
+                let scheduled = self.scheduled_items(&items);
+                match scheduled.iter().position(|item| source::same_item(item, &last)) {
+                    Some(idx) => idx + 1,
+                    // The latest episode went with a dropped source
+                    None => std::cmp::min(self.get_num_to_publish(), scheduled.len()),
+                }
+            },
+            None => 0,
+        };
+        self.keep_num_published(&items, num_published);
+
+        match self.update(&RssFile::FromStorage, client).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing sources: {}", e)),
+        }
+    }
+
+    // Rewrites the stored original rss from every source's archive, without going to the network
+    fn merge_source_archives(&self) -> Result<(), String> {
+        let channels: Vec<rss::Channel> = self.sources.iter()
+            .filter_map(|source| read_channel(&self.racer_path.join(&source.archive_file)))
+            .collect();
+        match source::merge_channels(&channels) {
+            Some(merged) => write_channel(&merged, &self.racer_path.join(ORIGINAL_RSS_FILE)),
+            None => Err(String::from("None of the sources have an archive")),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::reschedule_keeping_latest
+    //
+    //  NOTES:
+    //      Makes a change to which items get scheduled. The latest published episode stays the
+    //      latest published episode (or the one before it, if it's no longer scheduled), so nothing
+    //      new drops and nothing already heard disappears.
+    //  ARGS:   change - Makes the change to the racer
+    //  RETURN: An error if the feed couldn't be updated
+    //
+    async fn reschedule_keeping_latest<F: FnOnce(&mut FeedRacer)>(&mut self, change: F) -> Result<(), String> {
+        let items = match self.get_stored_items() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error reading stored items: {}", e)),
+        };
+
+        // Index (in the full item list) of the latest published episode
+        let num_published = self.get_num_to_publish();
+        let last_published = match num_published {
+            0 => None,
+            num => self.scheduled_indices(&items).get(num - 1).copied(),
+        };
+
+        change(self);
+        self.forget_history();
+        let num_published = match last_published {
+            Some(last_idx) => self.scheduled_indices(&items).iter().filter(|&&idx| idx <= last_idx).count(),
+            None => 0,
+        };
+        self.keep_num_published(&items, num_published);
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after rescheduling: {}", e)),
+        }
+    }
+
     pub async fn rewind_by_days(&mut self, days: usize) {
         let adjust_duration = Duration::days(days as i64);
         self.anchor_date = match self.anchor_date.checked_add_signed(adjust_duration) {
@@ -245,67 +769,90 @@ impl FeedRacer {
         };
     }
 
-    pub async fn rewind_by_episodes(&mut self, requested_ep_offset: usize) {
-        let current_ep_idx = self.get_next_episode_num()-1;
-        let ep_idx = std::cmp::max(current_ep_idx - requested_ep_offset, 0);
-        let target_ep = &self.release_dates[ep_idx];
-        let target_date = match DateTime::parse_from_rfc2822(&target_ep.date) {
-            Ok(val) => val,
-            Err(e) => {
-                println!("Error fetching target date for episode {:?}: {}", target_ep, e);
-                return
-            }
-        };
-        let adjust_duration = chrono::Utc::now().signed_duration_since(target_date);
-        // Add 1 min to put the time firmly after the publish date
-        let adjust_duration = match adjust_duration.checked_sub(&Duration::minutes(1)) {
-            Some(v) => v,
-            None => {
-                println!("Error adding 1m to duration, attempted to move back by {} episodes", requested_ep_offset);
-                return
-            }
-        };
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::rewind_by_episodes
+    //
+    //  NOTES:
+    //      Unpublishes the latest `requested_ep_offset` episodes. Stops at zero published if asked to
+    //      go back further than that.
+    //  ARGS:   requested_ep_offset - How many episodes to go back
+    //  RETURN: Where the feed ended up
+    //
+    pub async fn rewind_by_episodes(&mut self, requested_ep_offset: usize) -> NavigationResult {
+        let target = self.get_num_to_publish().saturating_sub(requested_ep_offset);
+        self.slide_to_num_published(requested_ep_offset, target).await
+    }
 
-        self.anchor_date = match self.anchor_date.checked_add_signed(adjust_duration) {
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::fastforward_by_episodes
+    //
+    //  NOTES:
+    //      Publishes the next `requested_ep_offset` episodes. Stops once everything is published.
+    //  ARGS:   requested_ep_offset - How many episodes to go forward
+    //  RETURN: Where the feed ended up
+    //
+    pub async fn fastforward_by_episodes(&mut self, requested_ep_offset: usize) -> NavigationResult {
+        let target = std::cmp::min(self.get_num_to_publish().saturating_add(requested_ep_offset), self.get_num_episodes());
+        self.slide_to_num_published(requested_ep_offset, target).await
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::jump_to_episode
+    //
+    //  NOTES:
+    //      Makes episode `ep_num` the latest published episode, wherever the feed is now.
+    //  ARGS:   ep_num - The episode to land on (1 indexed)
+    //  RETURN: Where the feed ended up, or an error if there's no such episode
+    //
+    pub async fn jump_to_episode(&mut self, ep_num: usize) -> Result<NavigationResult, String> {
+        if ep_num == 0 || ep_num > self.get_num_episodes() {
+            return Err(format!("Episode must be between 1 and {}", self.get_num_episodes()));
+        }
+        let requested = ep_num.abs_diff(self.get_num_to_publish());
+        Ok(self.slide_to_num_published(requested, ep_num).await)
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::jump_to_date
+    //
+    //  NOTES:
+    //      Puts the feed where it would have been at the end of `date` (in the feed's timezone) on
+    //      the current schedule. Everything that would have been out by then is published, and
+    //      nothing after it.
+    //  ARGS:   date - The day to jump to
+    //  RETURN: Where the feed ended up
+    //
+    pub async fn jump_to_date(&mut self, date: NaiveDate) -> Result<NavigationResult, String> {
+        let next_day = match date.succ_opt() {
             Some(val) => val,
-            None => return,
-        };
-        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
-            Ok(_) => (),
-            Err(e) => println!("Error updating feed after rewinding by episodes: {}", e),
+            None => return Err(format!("Can't jump to {}", date)),
         };
+        let end_of_day = schedule::localize(&self.get_timezone(), next_day.and_time(NaiveTime::MIN));
+        let target = self.get_num_published_at(end_of_day.with_timezone(&chrono::Utc));
+        let requested = target.abs_diff(self.get_num_to_publish());
+        Ok(self.slide_to_num_published(requested, target).await)
     }
 
-    pub async fn fastforward_by_episodes(&mut self, requested_ep_offset: usize) {
-        let current_ep_idx = self.get_next_episode_num()-1;
-        let ep_idx = std::cmp::min(current_ep_idx + requested_ep_offset, self.release_dates.len()-1);
-        let target_ep = &self.release_dates[ep_idx];
-        let target_date = match DateTime::parse_from_rfc2822(&target_ep.date) {
+    // Re-anchors so `target` episodes are published, and reports how that went
+    async fn slide_to_num_published(&mut self, requested: usize, target: usize) -> NavigationResult {
+        let previously_published = self.get_num_to_publish();
+        let items = match self.get_stored_items() {
             Ok(val) => val,
             Err(e) => {
-                println!("Error fetching target date for episode {:?}: {}", target_ep, e);
-                return
-            }
-        };
-        let adjust_duration = target_date.signed_duration_since(chrono::Utc::now());
-        // Add 1 min to put the time firmly after the publish date
-        let adjust_duration = match adjust_duration.checked_add(&Duration::minutes(1)) {
-            Some(v) => v,
-            None => {
-                println!("Error adding 1m to duration, attempted to move forward by {} episodes", requested_ep_offset);
-                return
+                println!("Error reading stored items to move to episode {}: {}", target, e);
+                return NavigationResult::new(requested, previously_published, previously_published);
             }
         };
 
-        self.anchor_date = match self.anchor_date.checked_sub_signed(adjust_duration) {
-            Some(val) => val,
-            None => return,
-        };
+        if target != previously_published {
+            self.anchor_to_num_published(&items, target);
+        }
 
         match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
             Ok(_) => (),
-            Err(e) => println!("Error updating feed after fast-forwarding by episodes: {}", e),
+            Err(e) => println!("Error updating feed after moving to episode {}: {}", target, e),
         };
+        NavigationResult::new(requested, previously_published, self.get_num_to_publish())
     }
 }
 
@@ -320,12 +867,14 @@ impl FeedRacer {
     //  ARGS:
     //      rss - The rss::Channel to base our PodRacer feed off of
     //      params - The input parameters for this feed
-    //  RETURN: A new, initialized FeedRacer object.
+    //  RETURN: A new, initialized FeedRacer object, or an error if the order can't be set up.
     //
-    fn new(rss: &rss::Channel, params: &RacerCreationParams) -> FeedRacer {
-        let mut items = rss.items().to_owned();
-        // Reverse the items so the oldest entry is first
-        items.reverse();
+    fn new(rss: &rss::Channel, params: &RacerCreationParams) -> Result<FeedRacer, String> {
+        let mut playlist = params.playlist.clone();
+        if params.order.uses_playlist() {
+            playlist.resolve(params.order, &order::order_items(rss.items().to_owned(), EpisodeOrder::PubDate, &playlist))?;
+        }
+        let items = order::order_items(rss.items().to_owned(), params.order, &playlist);
         // Get anchor date
         let start_idx = match items.len() >= params.start_ep && params.start_ep > 0 {
             true => params.start_ep - 1,
@@ -339,8 +888,21 @@ impl FeedRacer {
                 0
             }
         };
-        let start_date = items[start_idx].pub_date().expect("First item has date");
-        let first_pubdate = DateTime::parse_from_rfc2822(start_date).expect("Can parse first item's date");
+        // Starting from a season overrides start_ep
+        let start_idx = match (&params.seasons, params.start_season) {
+            (Some(seasons), Some(start_season)) => {
+                match seasons.seasons_of(&items).iter().position(|season| *season == Some(start_season)) {
+                    Some(idx) => idx,
+                    None => {
+                        println!("Season {} not found, using start_ep {}.", start_season, start_idx + 1);
+                        start_idx
+                    }
+                }
+            },
+            _ => start_idx,
+        };
+        // Release slots follow the original dates in order, whatever order the items are in
+        let first_pubdate = original_dates(&items)[start_idx];
         let anchor_date = chrono::Utc::now();
         let uuid = uuid::Uuid::new_v4().to_string();
 
@@ -377,10 +939,34 @@ impl FeedRacer {
             podcast_title: rss.title().to_owned(),
             old_rate: None,
             pause_date: None,
+            timezone: params.timezone.to_owned(),
+            publish_time: params.publish_time,
+            constraints: ScheduleConstraints::default(),
+            scheduled_pauses: Vec::new(),
+            manual_pause: false,
+            history: Vec::new(),
+            exclusions: ExcludeRules::default(),
+            exclude_matcher: OnceLock::new(),
+            episode_type_policy: EpisodeTypePolicy::default(),
+            seasons: params.seasons.clone(),
+            order: params.order,
+            playlist,
+            catch_up_policy: params.catch_up_policy,
+            caught_up: None,
+            sources: std::iter::once(&params.url)
+                .chain(params.extra_urls.iter())
+                .map(|url| FeedSource::new(url))
+                .collect(),
+            footer: FeedFooter::default(),
+            decoration: ChannelDecoration::default(),
+            badge_artwork: None,
+            namespace_guids: false,
+            proxy_enclosures: false,
+            archive_dir: None,
         };
         racer_data.render_release_dates(&items);
 
-        racer_data
+        Ok(racer_data)
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -392,50 +978,41 @@ impl FeedRacer {
     //  RETURN: Result - I/O successful or not
     //
     pub async fn update(&mut self, preferred_mode: &RssFile, client: &reqwest::Client) -> std::io::Result<bool> {
+        let tz = self.get_timezone();
         // Get original rss feed
         let (mut rss, new_episodes) = self.get_original_rss(preferred_mode, client).await?;
 
+        // Shuffled and curated orders need a place for any new episodes before we render
+        if self.order.uses_playlist() {
+            self.place_new_episodes(rss.items());
+        }
+
         // Re-render in case of rate change
         // Probably won't need this in the future
-        let mut items = rss.items().to_owned();
-        // Sorts ascending order
-        items.sort_by(|a, b| rss_item_cmp(a, b));
+        let items = order::order_items(rss.items().to_owned(), self.order, &self.playlist);
         self.render_release_dates(&items);
-
-        // Tack on a `- PodRacer` to the title
-        rss.set_title(String::from(rss.title()) + " - PodRacer");
-        match &mut rss.image {
-            Some(image) => image.set_title(String::from(image.title()) + " - PodRacer"),
-            None => (),
-        };
+        if self.check_caught_up() {
+            self.render_release_dates(&items);
+        }
+        // Excluded items never get published, and neither does anything held back after catching up
+        let mut items = self.scheduled_items(&items);
+        items.truncate(self.release_dates.len());
 
         // Drain the items we aren't publishing yet
         let mut items_to_publish: Vec<rss::Item> =
             items.drain(..self.get_num_to_publish()).collect();
 
-        // Append the next item's publish date to the podcast description
-        let mut description_addition = if items.len() > 0 {
-            // Check if feed is paused
-            if self.pause_date.is_some() {
-                format!("Feed paused")
-            }
-            else {
-                let next_item = self.release_dates[self.get_num_to_publish()].clone();
-                let s = match DateTime::parse_from_rfc2822(&next_item.date) {
-                    Ok(val) => val.with_timezone(&Local).format("%a, %d %b %Y at %I:%M%P"),
-                    Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
-                };
-                format!("Next episode publishes {}", s)
-            }
-        } else {
-            format!("PodRacer feed has caught up")
+        // Rename the podcast and say when the next episode is coming
+        let status = match self.get_feed_status() {
+            Ok(val) => val,
+            Err(e) => return Err(std::io::Error::other(e)),
         };
-        description_addition += " -- PodRacer UUID: ";
-        description_addition += &self.get_uuid();
-        rss.set_description(format!("{} -- {}", rss.description(), &description_addition));
+        self.decoration.apply(&mut rss, &status, &self.get_uuid());
+        self.apply_badge_artwork(&mut rss, client).await;
 
         // Append racer publish date to the end of the description
         let feed_uuid = self.get_uuid().to_owned();
+        let footer = self.footer_renderer();
         for (item, info) in items_to_publish.iter_mut().zip(self.release_dates.iter()) {
             //
             // Get all the DateTime's we need
@@ -444,31 +1021,20 @@ impl FeedRacer {
                 Ok(val) => val,
                 Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
             };
-            let item_pub_date = match item.pub_date() {
-                Some(val) => val,
-                None => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "no pub_date on item",
-                    ))
-                }
-            };
-            let item_date = match DateTime::parse_from_rfc2822(item_pub_date) {
-                Ok(val) => val,
-                Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
-            };
-
-            // If we have caught up, use the actual publish date because the racer date
-            // will be in the past, which won't make much sense as a publish date
+            // Items without a usable pubDate were scheduled with a borrowed date (see
+            // original_dates), so they just go out on the racer date
+            let item_date = item.pub_date()
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                .unwrap_or(racer_date);
+
+            // The episode we caught up on can have a racer date before it came out upstream. Use
+            // the actual publish date, since an earlier one won't make much sense.
             let racer_pub_date = if racer_date < item_date {
-                item_date.with_timezone(&Local).to_rfc2822()
+                item_date.with_timezone(&tz).to_rfc2822()
             } else {
-                racer_date.with_timezone(&Local).to_rfc2822()
-            };
-            let original_pub_date = match DateTime::parse_from_rfc2822(item_pub_date) {
-                Ok(val) => val.with_timezone(&Local).format("%d %b %Y"),
-                Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
+                racer_date.with_timezone(&tz).to_rfc2822()
             };
+            let original_pub_date = item_date.with_timezone(&tz).format("%d %b %Y");
             let human_original_pub_date = humantime::format_duration(
                 std::time::Duration::from_millis((racer_date - item_date).num_milliseconds() as u64)
             );
@@ -477,24 +1043,58 @@ impl FeedRacer {
             //
             // Set description + content
             //
+            let (renderer, host) = match &footer {
+                Some(val) => val,
+                None => continue,
+            };
+            let context = footer::FooterContext {
+                host,
+                uuid: &feed_uuid,
+                edit_url: format!("{}/edit_feed/{}", host.trim_end_matches('/'), feed_uuid),
+                subscribe_url: &self.subscribe_url,
+                podcast_title: &self.podcast_title,
+                episode_title: item.title().unwrap_or(""),
+                original_date: original_pub_date.to_string(),
+                time_shift: human_original_pub_date.to_string(),
+            };
+            let footer_html = match renderer.render(&context) {
+                Ok(val) => val,
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                },
+            };
             let description = item.description().unwrap_or("").to_owned();
             let mut new_description = description.replace("\r\n", "\n");
-            let feed_uuid_link = format!("<a href=\"http://podracer.zachn.me/edit_feed/{}\">{}</a>",
-                                    feed_uuid, feed_uuid);
-            new_description.push_str(&format!("\n\n<hr>\n\nOriginally published on {} ({} from PodRacer publish date)\n\nFeed UUID: {}",
-                    original_pub_date, human_original_pub_date, feed_uuid_link));
+            new_description.push_str("\n\n");
+            new_description.push_str(&footer_html);
             item.set_description(new_description);
             // Only do content if it is present
             match item.content() {
                 Some(content) => {
                     let mut new_content = content.replace("\r\n", "\n");
-                    new_content.push_str(&format!("<br><hr><br>Originally published on {} ({} from PodRacer publish date)<br><br>Feed UUID:<br>{}",
-                            original_pub_date, human_original_pub_date, feed_uuid_link));
+                    new_content.push_str("<br>");
+                    new_content.push_str(&footer_html.replace("\n", "<br>"));
                     item.set_content(new_content);
                 },
                 None => (),
             };
         }
+        let archive = self.read_archive();
+        let local_media = self.local_media();
+        for item in items_to_publish.iter_mut() {
+            let id = order::item_id(item);
+            if self.proxy_enclosures || local_media.contains_key(&id) || self.archived_path(&archive, &id).is_some() {
+                let media_url = self.get_media_url(&id);
+                if let Some(enclosure) = item.enclosure.as_mut() {
+                    enclosure.set_url(media_url);
+                }
+            }
+            // Players that also have the original feed would merge our episodes with its episodes
+            if self.namespace_guids {
+                item.set_guid(rss::Guid { value: formats::namespaced_id(&id, &feed_uuid), permalink: false });
+            }
+        }
         // Now that we have the items we want, overwrite the objects items.
         rss.set_items(items_to_publish);
 
@@ -520,6 +1120,11 @@ impl FeedRacer {
         let racer_rss_file = File::create(&racer_rss_path)?;
         match rss.pretty_write_to(racer_rss_file, SPACE_CHAR, INDENT_AMOUNT) {
             Ok(_) => {
+                // The other formats are nice to have, don't fail the update over them
+                match self.write_other_formats(&rss) {
+                    Ok(_) => (),
+                    Err(e) => println!("Error writing Atom/JSON feeds, but still continuing: {}.", e),
+                }
                 // Need to scrub on write since pretty_write doesn't write valid xml
                 // Should be fixed with GH-33
                 //scrub_xml_file(&racer_rss_path);
@@ -530,80 +1135,659 @@ impl FeedRacer {
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////
-    //  NAME:   FeedRacer::render_release_dates
+    //  NAME:   FeedRacer::get_feed_status
     //
-    //  NOTES:  Renders the release dates for the passed in items. Items must be in the correct order.
-    //  ARGS:   items - The items to render. Must be in the correct order.
-    //  RETURN: None
+    //  NOTES:
+    //      Where this racer is at - paused, caught up or waiting on its next episode. Must not panic,
+    //      as it's used in the update thread.
+    //  ARGS:   None
+    //  RETURN: The status, or an error if the next release date can't be read
     //
-    fn render_release_dates(&mut self, items: &Vec<rss::Item>) {
-        // Need to protect against divide by 0 when paused. Just keep rendering
-        // the projected release date as if we weren't paused, the actual publishing
-        // is run elsewhere.
-        match self.rate {
-            RacerType::Ratio(rate) => {
-                let protected_rate = if rate == 0.0 {
-                    if let RacerType::Ratio(old_rate) = self.old_rate.clone().unwrap_or(RacerType::Ratio(1.0)) {
-                        old_rate
-                    }
-                    else {
-                        1.0
-                    }
-                }
-                else {
-                    rate
+    pub fn get_feed_status(&self) -> Result<FeedStatus, String> {
+        let num_published = self.get_num_to_publish();
+        let next_episode = match self.release_dates.get(num_published) {
+            Some(val) => val,
+            None => {
+                return match (&self.caught_up, self.catch_up_policy) {
+                    (Some(_), CatchUpPolicy::Stop) => Ok(FeedStatus::Stopped),
+                    _ => Ok(FeedStatus::CaughtUp),
                 };
-                self.render_release_dates_rate_based(items, protected_rate);
             },
-            RacerType::Days(days) => self.render_release_dates_days_based(items, days),
+        };
+        if self.pause_date.is_some() {
+            return Ok(FeedStatus::Paused);
+        }
+        match DateTime::parse_from_rfc2822(&next_episode.date) {
+            Ok(val) => Ok(FeedStatus::NextEpisode(val.with_timezone(&self.get_timezone()))),
+            Err(e) => Err(format!("Error parsing release date {}: {}", next_episode.date, e)),
         }
     }
-    fn render_release_dates_rate_based(&mut self, items: &Vec<rss::Item>, protected_rate: f64) {
-        self.release_dates = Vec::new();
 
-        let mut item_counter = 1;
-        for item in items {
-            // Get diff from first published date
-            let pub_date = item.pub_date().unwrap();
-            let mut time_diff = DateTime::parse_from_rfc2822(pub_date)
-                .unwrap()
-                .signed_duration_since(self.first_pubdate)
-                .num_milliseconds();
-            // Scale that diff
-            time_diff = ((time_diff as f64) / protected_rate) as i64;
-            // Add back to anchor date to get new publish date + convert to string
-            let racer_date = self
-                .anchor_date
-                .checked_add_signed(Duration::milliseconds(time_diff))
-                .unwrap()
-                .to_rfc2822();
-            // Add to vector of dates
-            self.release_dates.push(RacerEpisode {
-                ep_num: item_counter,
-                title: item.title().unwrap_or("[no title]").to_owned(),
-                date: racer_date,
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::apply_badge_artwork
+    //
+    //  NOTES:
+    //      Points the channel's artwork at racer.png if this feed has a badge. racer.png is only
+    //      rebuilt when the artwork or badge text changes. If it can't be made, the channel keeps
+    //      the original artwork.
+    //  ARGS:
+    //      rss - The channel being published, after the title and artwork have been decorated
+    //      client - For downloading the original artwork
+    //  RETURN: None
+    //
+    async fn apply_badge_artwork(&mut self, rss: &mut rss::Channel, client: &reqwest::Client) {
+        let text = match artwork::badge_text(&self.decoration.badge, &self.rate) {
+            Some(val) => val,
+            None => {
+                self.badge_artwork = None;
+                return;
+            },
+        };
+        let source_url = match artwork::channel_artwork_url(rss) {
+            Some(val) => val,
+            None => {
+                println!("{} has no artwork to put a badge on", self.podcast_title);
+                return;
+            },
+        };
+        let badge = artwork::BadgeArtwork { source_url, text };
+        let artwork_path = self.racer_path.join(RACER_ARTWORK_FILE);
+        if self.badge_artwork.as_ref() != Some(&badge) || !artwork_path.exists() {
+            match artwork::write_badge_artwork(client, &badge, &artwork_path).await {
+                Ok(_) => self.badge_artwork = Some(badge),
+                Err(e) => {
+                    println!("Error making badge artwork for {}: {}. Using the original artwork.", self.podcast_title, e);
+                    return;
+                },
+            }
+        }
+        decoration::set_channel_artwork(rss, &self.get_artwork_url());
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::get_media_source
+    //
+    //  NOTES:
+    //      Finds the audio for an episode of this feed. Episodes in the podarch archive are always
+    //      served from it, as are episodes from a local directory source. Otherwise, when
+    //      enclosures are proxied, an archived copy in the media dir wins over the upstream
+    //      enclosure, and only episodes in the original feed are served.
+    //  ARGS:   id - The episode's upstream GUID
+    //  RETURN: Where to get the audio, or None if there's nothing to serve
+    //
+    pub fn get_media_source(&self, id: &str) -> Option<MediaSource> {
+        let archive = self.read_archive();
+        if let Some(path) = self.archived_path(&archive, id) {
+            let mime_type = archive.as_ref()
+                .and_then(|archive| archive.find(id))
+                .map(|episode| episode.mime_type.clone())
+                .unwrap_or_default();
+            return Some(MediaSource::Local { path, mime_type });
+        }
+        if let Some(source) = self.local_media().remove(id) {
+            return Some(source);
+        }
+        if !self.proxy_enclosures {
+            return None;
+        }
+        let original = read_channel(&self.racer_path.join(ORIGINAL_RSS_FILE))?;
+        let enclosure = original.items().iter()
+            .find(|item| order::item_id(item) == id)
+            .and_then(|item| item.enclosure())?;
+        // Files on this machine only come from local_media, never from an upstream enclosure
+        if local::local_path(enclosure.url()).is_some() {
+            return None;
+        }
+        let local_path = self.racer_path.join(MEDIA_DIR).join(formats::percent_encode(id));
+        if local_path.is_file() {
+            return Some(MediaSource::Local {
+                path: local_path,
+                mime_type: enclosure.mime_type().to_owned(),
             });
-            item_counter += 1;
         }
+        Some(MediaSource::Upstream { url: enclosure.url().to_owned() })
     }
 
-    fn render_release_dates_days_based(&mut self, items: &Vec<rss::Item>, days: u32) {
-        self.release_dates = Vec::new();
+    // The podarch manifest, if this feed has an archive. Errors are printed so a missing archive
+    // drive doesn't stop updates.
+    fn read_archive(&self) -> Option<ArchiveManifest> {
+        let dir = self.archive_dir.as_ref()?;
+        match ArchiveManifest::read(dir) {
+            Ok(val) => Some(val),
+            Err(e) => {
+                println!("Error reading the archive for {}: {}", self.podcast_title, e);
+                None
+            },
+        }
+    }
+
+    // The audio for episodes from local directory sources, by episode id. Only what the
+    // directory scan wrote into each local source's own archive counts, so an enclosure from
+    // another feed pointing at a file on this machine (file:///etc/passwd) is never served.
+    fn local_media(&self) -> HashMap<String, MediaSource> {
+        let mut media = HashMap::new();
+        for source in &self.sources {
+            let dir = match local::local_path(&source.url) {
+                Some(val) => val,
+                None => continue,
+            };
+            let scanned = match read_channel(&self.racer_path.join(&source.archive_file)) {
+                Some(val) => val,
+                None => continue,
+            };
+            for item in scanned.items() {
+                let enclosure = match item.enclosure() {
+                    Some(val) => val,
+                    None => continue,
+                };
+                match local::local_path(enclosure.url()) {
+                    Some(path) if local::is_in_dir(&dir, &path) => {
+                        media.entry(order::item_id(item)).or_insert(MediaSource::Local {
+                            path,
+                            mime_type: enclosure.mime_type().to_owned(),
+                        });
+                    },
+                    _ => (),
+                }
+            }
+        }
+        media
+    }
+
+    // Where episode `id` is in the archive, if it's been downloaded
+    fn archived_path(&self, archive: &Option<ArchiveManifest>, id: &str) -> Option<PathBuf> {
+        let episode = archive.as_ref()?.find(id)?;
+        archive::episode_path(self.archive_dir.as_ref()?, episode)
+    }
+
+    // Opens the audio for an episode of this feed. See get_media_source and media::open_media.
+    pub async fn open_media(&self, id: &str, range: Option<&str>, client: &reqwest::Client)
+        -> Result<Option<Media>, String> {
+        match self.get_media_source(id) {
+            Some(source) => media::open_media(&source, range, client).await.map(Some),
+            None => Ok(None),
+        }
+    }
 
-        let mut item_counter = 1;
-        for item in items {
-            let racer_date = self
-                .anchor_date
-                .checked_add_signed(Duration::days((item_counter-1) * days as i64))
-                .unwrap()
-                .to_rfc2822();
-            // Add to vector of dates
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::footer_renderer
+    //
+    //  NOTES:
+    //      Picks this feed's footer template and the host to link back to. The host comes from
+    //      the server's footer settings, falling back to the one the feed was created on. A bad
+    //      server template falls back to the default one so episodes still say where they're from.
+    //  ARGS:   None
+    //  RETURN: The compiled template and host, or None if this feed has no footer
+    //
+    fn footer_renderer(&self) -> Option<(footer::FooterRenderer, String)> {
+        let settings = self.racer_path.parent().and_then(footer::read_footer_settings);
+        let host = match &settings {
+            Some(settings) if !settings.host.is_empty() => settings.host.clone(),
+            _ => self.subscribe_url.split("/podcasts/").next().unwrap_or("").to_owned(),
+        };
+        let template = match &self.footer {
+            FeedFooter::Disabled => return None,
+            FeedFooter::Custom(template) => template.clone(),
+            FeedFooter::Server => settings
+                .and_then(|settings| settings.template)
+                .unwrap_or_else(|| DEFAULT_FOOTER_TEMPLATE.to_owned()),
+        };
+        match footer::FooterRenderer::new(&template) {
+            Ok(renderer) => Some((renderer, host)),
+            Err(e) => {
+                println!("{}. Using the default footer for {}.", e, self.podcast_title);
+                let renderer = footer::FooterRenderer::new(DEFAULT_FOOTER_TEMPLATE).expect("Default footer compiles");
+                Some((renderer, host))
+            },
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::write_other_formats
+    //
+    //  NOTES:
+    //      Writes the published episodes out as racer.atom and racer.json, next to racer.rss.
+    //  ARGS:   rss - The finished racer channel
+    //  RETURN: An error if either file couldn't be written
+    //
+    fn write_other_formats(&self, rss: &rss::Channel) -> Result<(), String> {
+        let atom = formats::atom_feed(rss, &self.get_atom_url(), &self.get_uuid_string());
+        let atom_file = match File::create(self.racer_path.join(RACER_ATOM_FILE)) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Unable to create {}: {}", RACER_ATOM_FILE, e)),
+        };
+        if let Err(e) = atom.write_to(atom_file) {
+            return Err(format!("Unable to write {}: {}", RACER_ATOM_FILE, e));
+        }
+
+        let json = formats::json_feed(rss, &self.get_json_feed_url());
+        let json_file = match File::create(self.racer_path.join(RACER_JSON_FILE)) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Unable to create {}: {}", RACER_JSON_FILE, e)),
+        };
+        match serde_json::to_writer_pretty(json_file, &json) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Unable to write {}: {}", RACER_JSON_FILE, e)),
+        }
+    }
+
+    // A file served next to racer.rss
+    fn sibling_url(&self, file: &str) -> String {
+        match self.subscribe_url.strip_suffix(RACER_RSS_FILE) {
+            Some(base) => format!("{}{}", base, file),
+            None => format!("{}/{}", self.subscribe_url.trim_end_matches('/'), file),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::render_release_dates
+    //
+    //  NOTES:
+    //      Renders the release dates for the passed in items. Items must be in the correct order.
+    //      Excluded items (and trailers, if we're skipping them) are dropped first, and everything
+    //      after them moves up to fill the gap. Each remaining item gets a release slot, except
+    //      bonus episodes riding along with their neighbour.
+    //  ARGS:   items - The items to render. Must be in the correct order.
+    //  RETURN: None
+    //
+    fn render_release_dates(&mut self, items: &Vec<rss::Item>) {
+        let scheduled = self.scheduled_items(items);
+        let infos: Vec<EpisodeInfo> = scheduled.iter().map(EpisodeInfo::from_item).collect();
+        let slot_idxs = self.slot_indices(&infos);
+        let num_slots = slot_idxs.last().map(|idx| idx + 1).unwrap_or(0);
+
+        // Pausing doesn't touch the rate - we keep rendering the projected release dates as if we
+        // weren't paused, and only count the ones released before the pause date.
+        let slots = match (&self.seasons, &self.rate) {
+            // Mirrors follow upstream's dates, so seasons don't come into it
+            (_, RacerType::Delay(days)) => delay_based_slots(&scheduled, &slot_idxs, num_slots, *days),
+            (Some(seasons), _) => {
+                // Each slot belongs to the season of the first item in it
+                let item_seasons = seasons.seasons_of(&scheduled);
+                let mut slot_seasons = Vec::with_capacity(num_slots);
+                for (slot_idx, season) in slot_idxs.iter().zip(item_seasons) {
+                    if *slot_idx == slot_seasons.len() {
+                        slot_seasons.push(season);
+                    }
+                }
+                self.season_based_slots(seasons, &original_dates(items), &slot_seasons)
+            },
+            (None, RacerType::Ratio(rate)) => self.rate_based_slots(&original_dates(items), num_slots, *rate),
+            (None, RacerType::Days(days)) => self.days_based_slots(num_slots, *days),
+        };
+
+        let mut dates: Vec<DateTime<Tz>> = slot_idxs.iter()
+            .map(|slot_idx| self.localize_release_date(slots[*slot_idx]))
+            .collect();
+        // Episodes that came out after we caught up follow the catch up policy instead of the rate
+        if let Some(caught_up) = &self.caught_up {
+            for idx in caught_up.num_published..dates.len() {
+                let original = match scheduled[idx].pub_date().map(DateTime::parse_from_rfc2822) {
+                    Some(Ok(val)) => val.with_timezone(&chrono::Utc),
+                    _ => continue,
+                };
+                let date = match self.catch_up_policy {
+                    CatchUpPolicy::Live => original.with_timezone(&self.get_timezone()),
+                    CatchUpPolicy::Delay(days) => self.localize_release_date(original + Duration::days(days as i64)),
+                    CatchUpPolicy::Stop => {
+                        dates.truncate(idx);
+                        break;
+                    },
+                };
+                // Never before the episode ahead of it
+                dates[idx] = match idx {
+                    0 => date,
+                    _ => std::cmp::max(date, dates[idx - 1]),
+                };
+            }
+        }
+
+        self.release_dates = Vec::new();
+        for (idx, ((item, info), date)) in scheduled.iter().zip(infos).zip(dates).enumerate() {
             self.release_dates.push(RacerEpisode {
-                ep_num: item_counter,
+                ep_num: idx as i64 + 1,
                 title: item.title().unwrap_or("[no title]").to_owned(),
-                date: racer_date,
+                date: date.to_rfc2822(),
+                info,
             });
-            item_counter += 1;
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::check_caught_up
+    //
+    //  NOTES:
+    //      Notices the racer catching up (everything published) or falling behind again (rewound
+    //      past where it caught up). Call after rendering, and render again if anything changed.
+    //  ARGS:   None
+    //  RETURN: True if the caught up state changed
+    //
+    fn check_caught_up(&mut self) -> bool {
+        // A mirror is always caught up, and new episodes always keep the delay
+        if let RacerType::Delay(_) = self.rate {
+            return self.caught_up.take().is_some();
+        }
+        let num_published = self.get_num_to_publish();
+        match &self.caught_up {
+            None => {
+                if self.release_dates.is_empty() || num_published < self.release_dates.len() {
+                    return false;
+                }
+                println!("{} has caught up with {} episodes published. New episodes go {}.",
+                         self.podcast_title, num_published, self.catch_up_policy);
+                self.caught_up = Some(CaughtUp {
+                    date: chrono::Utc::now(),
+                    num_published,
+                });
+                true
+            },
+            Some(caught_up) => {
+                if num_published >= caught_up.num_published {
+                    return false;
+                }
+                println!("{} is no longer caught up ({} episodes published)", self.podcast_title, num_published);
+                self.caught_up = None;
+                true
+            },
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_footer
+    //
+    //  NOTES:
+    //      Changes the footer on this feed's episodes. Custom templates are checked before
+    //      they're saved.
+    //  ARGS:   footer - The new footer
+    //  RETURN: An error if the template is bad or the feed couldn't be updated
+    //
+    pub async fn set_footer(&mut self, footer: FeedFooter) -> Result<(), String> {
+        if let FeedFooter::Custom(template) = &footer {
+            validate_footer_template(template)?;
+        }
+        self.footer = footer;
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing the footer: {}", e)),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_decoration
+    //
+    //  NOTES:
+    //      Changes the racer feed's title, status line and artwork. Blank artwork goes back to the
+    //      podcast's own.
+    //  ARGS:   decoration - The new title style, status line setting and artwork
+    //  RETURN: An error if the title or artwork is bad or the feed couldn't be updated
+    //
+    pub async fn set_decoration(&mut self, mut decoration: ChannelDecoration) -> Result<(), String> {
+        decoration.artwork = decoration.artwork
+            .map(|artwork| artwork.trim().to_owned())
+            .filter(|artwork| !artwork.is_empty());
+        decoration.validate()?;
+        self.decoration = decoration;
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing the title and artwork: {}", e)),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_namespace_guids
+    //
+    //  NOTES:
+    //      Switches published GUIDs between the upstream ones and ones under this racer's uuid.
+    //      Namespaced GUIDs only depend on the upstream GUID, so they stay the same through
+    //      updates, rewinds and rate changes. Players will see every episode as new when this
+    //      changes.
+    //  ARGS:   namespace_guids - Whether to namespace GUIDs
+    //  RETURN: An error if the feed couldn't be updated
+    //
+    pub async fn set_namespace_guids(&mut self, namespace_guids: bool) -> Result<(), String> {
+        self.namespace_guids = namespace_guids;
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing GUIDs: {}", e)),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_proxy_enclosures
+    //
+    //  NOTES:
+    //      Switches enclosures between the upstream links and this server's media route.
+    //  ARGS:   proxy_enclosures - Whether to serve episodes through this server
+    //  RETURN: An error if the feed couldn't be updated
+    //
+    pub async fn set_proxy_enclosures(&mut self, proxy_enclosures: bool) -> Result<(), String> {
+        self.proxy_enclosures = proxy_enclosures;
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing enclosures: {}", e)),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_archive_dir
+    //
+    //  NOTES:
+    //      Points this feed at a podarch directory. Episodes in its manifest are served from there
+    //      instead of the upstream host.
+    //  ARGS:
+    //      archive_dir - The podarch directory, or None to stop using one
+    //      local_media_root - Where archives are allowed, see local::check_local_dir
+    //  RETURN: An error if the directory isn't allowed, has no readable manifest or the feed
+    //          couldn't be updated
+    //
+    pub async fn set_archive_dir(&mut self, archive_dir: Option<&str>, local_media_root: Option<&Path>)
+        -> Result<(), String> {
+        self.archive_dir = match archive_dir.map(str::trim).filter(|dir| !dir.is_empty()) {
+            Some(dir) => {
+                let dir = match local::check_local_dir(Path::new(dir), local_media_root) {
+                    Ok(val) => val,
+                    Err(e) => return Err(format!("Can't use archive {}: {}", dir, e)),
+                };
+                ArchiveManifest::read(&dir)?;
+                Some(dir)
+            },
+            None => None,
+        };
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing the archive: {}", e)),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::set_catch_up_policy
+    //
+    //  NOTES:
+    //      Changes what happens after catching up. If we already have, the switch happens at the
+    //      latest published episode - nothing already out disappears, and only later episodes
+    //      follow the new policy.
+    //  ARGS:   policy - The new policy
+    //  RETURN: An error if the feed couldn't be updated
+    //
+    pub async fn set_catch_up_policy(&mut self, policy: CatchUpPolicy) -> Result<(), String> {
+        let num_published = self.get_num_to_publish();
+        if let Some(caught_up) = &mut self.caught_up {
+            caught_up.num_published = num_published;
+        }
+        self.catch_up_policy = policy;
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after changing the catch up policy: {}", e)),
+        }
+    }
+    // Slot i goes out when the i-th oldest item of the full feed did, squished/stretched
+    fn rate_based_slots(&self, dates: &[DateTime<chrono::FixedOffset>], num_slots: usize, rate: f64)
+        -> Vec<DateTime<chrono::Utc>> {
+        dates.iter()
+            .take(num_slots)
+            .map(|date| {
+                // Get diff from first published date
+                let mut time_diff = date.signed_duration_since(self.first_pubdate).num_milliseconds();
+                // Scale that diff
+                time_diff = ((time_diff as f64) / rate) as i64;
+                // Add back to anchor date to get new publish date
+                self.anchor_date
+                    .checked_add_signed(Duration::milliseconds(time_diff))
+                    .unwrap()
+            })
+            .collect()
+    }
+    // Slot i goes out i * days after the anchor
+    fn days_based_slots(&self, num_slots: usize, days: u32) -> Vec<DateTime<chrono::Utc>> {
+        (0..num_slots)
+            .map(|idx| {
+                self.anchor_date
+                    .checked_add_signed(Duration::days(idx as i64 * days as i64))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::season_based_slots
+    //
+    //  NOTES:
+    //      Like rate/days based slots, but each season races at its own rate. Crossing into a new
+    //      season takes one step at the old season's rate, plus the configured gap. The slot of
+    //      the first episode on or after first_pubdate lands on the anchor date, like it does for
+    //      rate based feeds.
+    //  ARGS:
+    //      seasons - The season config
+    //      dates - The original dates of the full list of items, oldest first
+    //      slot_seasons - The season of each slot
+    //  RETURN: The release date of each slot
+    //
+    fn season_based_slots(&self, seasons: &SeasonConfig, dates: &[DateTime<chrono::FixedOffset>],
+                          slot_seasons: &[Option<u32>]) -> Vec<DateTime<chrono::Utc>> {
+        let original_date = |idx: usize| -> DateTime<chrono::FixedOffset> { dates[idx] };
+        // How long after slot idx-1 slot idx comes, at `rate`
+        let step = |rate: &RacerType, idx: usize| -> Duration {
+            match rate {
+                RacerType::Days(days) | RacerType::Delay(days) => Duration::days(*days as i64),
+                RacerType::Ratio(rate) => {
+                    let diff = original_date(idx).signed_duration_since(original_date(idx - 1)).num_milliseconds();
+                    Duration::milliseconds(((diff as f64) / rate) as i64)
+                },
+            }
+        };
+
+        let mut offsets: Vec<Duration> = Vec::with_capacity(slot_seasons.len());
+        for (idx, season) in slot_seasons.iter().enumerate() {
+            if idx == 0 {
+                offsets.push(Duration::zero());
+                continue;
+            }
+            let prev_season = slot_seasons[idx - 1];
+            let mut offset = offsets[idx - 1] + step(&seasons.rate_for(prev_season, &self.rate), idx);
+            if *season != prev_season {
+                offset += Duration::days(seasons.gap_days as i64);
+            }
+            offsets.push(offset);
+        }
+
+        let reference = (0..slot_seasons.len())
+            .find(|idx| original_date(*idx) >= self.first_pubdate)
+            .unwrap_or(0);
+        let reference_offset = offsets.get(reference).copied().unwrap_or_else(Duration::zero);
+        offsets.into_iter()
+            .map(|offset| self.anchor_date.checked_add_signed(offset - reference_offset).unwrap())
+            .collect()
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::slot_indices
+    //
+    //  NOTES:
+    //      Works out which release slot each scheduled item goes out in. Normally that's one slot
+    //      each, but bonus episodes can share the slot of the episode before them (or after them,
+    //      if the feed opens with a bonus).
+    //  ARGS:   infos - Episode info for the scheduled items, in order
+    //  RETURN: The slot index for each item. Never decreases.
+    //
+    fn slot_indices(&self, infos: &[EpisodeInfo]) -> Vec<usize> {
+        let mut slot_idxs = Vec::with_capacity(infos.len());
+        let mut num_slots: usize = 0;
+        for info in infos {
+            if self.episode_type_policy.bonus_with_neighbour && info.episode_type == EpisodeType::Bonus {
+                slot_idxs.push(num_slots.saturating_sub(1));
+            }
+            else {
+                slot_idxs.push(num_slots);
+                num_slots += 1;
+            }
+        }
+        slot_idxs
+    }
+
+    // Indices of the items that aren't excluded
+    fn scheduled_indices(&self, items: &[rss::Item]) -> Vec<usize> {
+        let skip_trailers = self.episode_type_policy.skip_trailers;
+        if self.exclusions.is_empty() && !skip_trailers {
+            return (0..items.len()).collect();
+        }
+        // Rendering runs this over and over while re-anchoring, so only compile the rules once
+        let matcher = self.exclude_matcher.get_or_init(|| self.exclusions.matcher());
+        items.iter()
+            .enumerate()
+            .filter(|(_, item)| !matcher.is_excluded(item))
+            .filter(|(_, item)| !(skip_trailers && EpisodeInfo::from_item(item).episode_type == EpisodeType::Trailer))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+    // The items that aren't excluded, in order
+    fn scheduled_items(&self, items: &[rss::Item]) -> Vec<rss::Item> {
+        self.scheduled_indices(items).into_iter().map(|idx| items[idx].clone()).collect()
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::localize_release_date
+    //
+    //  NOTES:
+    //      Moves a rendered release date into this feed's timezone. If the feed has a preferred
+    //      publish time, the date is pushed forward to the next time the local clock reads that
+    //      time, so episodes always drop at the same time of day. Anything landing in a window
+    //      excluded by the schedule constraints is then pushed to the next allowed time.
+    //  ARGS:   date - The raw release date
+    //  RETURN: The release date in the feed's timezone
+    //
+    fn localize_release_date(&self, date: DateTime<chrono::Utc>) -> DateTime<Tz> {
+        let local = self.snap_to_publish_time(date);
+        if self.constraints.is_empty() {
+            return local;
+        }
+        self.constraints.next_allowed(local, self.publish_time)
+    }
+    fn snap_to_publish_time(&self, date: DateTime<chrono::Utc>) -> DateTime<Tz> {
+        let tz = self.get_timezone();
+        let local = date.with_timezone(&tz);
+        let publish_time = match self.publish_time {
+            Some(val) => val,
+            None => return local,
+        };
+
+        let mut day = local.date_naive();
+        loop {
+            // Skip over times that don't exist on this day (DST gaps)
+            if let Some(candidate) = tz.from_local_datetime(&day.and_time(publish_time)).earliest() {
+                if candidate >= local {
+                    return candidate;
+                }
+            }
+            day = match day.succ_opt() {
+                Some(val) => val,
+                None => return local,
+            };
         }
     }
 
@@ -625,11 +1809,9 @@ impl FeedRacer {
     //      Gets the original rss one way or another (downloaded or from storage).
     //      We try to avoid downloading if possible. If we have the file on disk and the feed
     //      doesn't integrate new episodes, there's no need to download so we can just serve back
-    //      what we have on disk. If either of those things is not true, we need to fetch to update
-    //      the rss feed, but we only overwrite the stored file if it has more feed items.
-    // TODO -> handle feeds that have a constant number of entries and push the oldest entry out.
-    // TODO -> Wrap this return in an option for the case where there is no stored file and the
-    //         network request fails.
+    //      what we have on disk. Otherwise we fetch every source, fold what we got into each
+    //      source's archive, and merge the archives into the stored original rss. A source that
+    //      fails to download falls back to its archive, so one bad host doesn't take the feed down.
     //  ARGS:
     //      preferred_mode - the requested mode. We don't always honor it, but it lets us know if the asker
     //      wants to go to the network or not.
@@ -639,25 +1821,7 @@ impl FeedRacer {
     async fn get_original_rss(&mut self, preferred_mode: &RssFile, client: &reqwest::Client) -> std::io::Result<(rss::Channel, bool)> {
         let mut stored_rss_path = self.racer_path.clone();
         stored_rss_path.push(ORIGINAL_RSS_FILE);
-        let stored_rss_file = match File::open(&stored_rss_path) {
-            Ok(val) => Some(val),
-            Err(e) => {
-                println!("Error opening original rss file ({}): {}", stored_rss_path.display(), e);
-                None
-            }
-        };
-        let stored_rss = if let Some(f) = stored_rss_file {
-            let buf_reader = BufReader::new(f);
-            match rss::Channel::read_from(buf_reader) {
-                Ok(val) => Some(val),
-                Err(e) => {
-                    println!("Error reading rss channel from disk: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
+        let stored_rss = read_channel(&stored_rss_path);
 
         let (stored_rss, functional_mode) = match stored_rss {
             Some(val) => {
@@ -672,40 +1836,75 @@ impl FeedRacer {
 
         match functional_mode {
             RssFile::Download => {
-                match download_rss_channel(client, &self.source_url).await {
-                    Ok(network_file) => {
-                        // Compare to stored file - update if we need to
-                        let num_to_update = match &stored_rss {
-                            Some(rss) => {
-                                (network_file.items().len() as i64 - rss.items().len() as i64).abs()
+                // Fetch every source at once. Errors are flattened to strings so they can be held
+                // while the other downloads finish.
+                let fetches = futures::future::join_all(self.sources.iter()
+                    .map(|source| async move {
+                        download_rss_channel(client, &source.url).await.map_err(|e| e.to_string())
+                    }))
+                    .await;
+
+                let racer_path = self.racer_path.clone();
+                let mut channels = Vec::new();
+                for (source, fetched) in self.sources.iter_mut().zip(fetches) {
+                    let archive_path = racer_path.join(&source.archive_file);
+                    let archive = read_channel(&archive_path);
+                    match fetched {
+                        Ok(mut network_file) => {
+                            // Keep anything that has dropped off the end of the upstream feed
+                            let archived = archive.map(|channel| channel.items().to_owned()).unwrap_or_default();
+                            let num_archived = archived.len();
+                            let items = source::merge_items(vec![network_file.items().to_owned(), archived]);
+                            let num_items = items.len();
+                            network_file.set_items(items);
+                            if num_items != num_archived {
+                                if let Err(e) = write_channel(&network_file, &archive_path) {
+                                    println!("Error writing archive for {}: {}. Continuing without writing.", source.url, e);
+                                }
                             }
-                            None => network_file.items().len() as i64,
-                        };
-                        if num_to_update > 0 {
-                            // Overwrite our stored original RSS file
-                            // TODO - If a feed pushes out the oldest entries, overwriting won't cut it.
-                            //        We'll need to save old items.
-                            match File::create(stored_rss_path) {
-                                Ok(stored_rss_file) => {
-                                    match network_file.pretty_write_to(stored_rss_file, SPACE_CHAR, INDENT_AMOUNT) {
-                                        Ok(_) => (),
-                                        Err(e) => println!("Error writing network_file to disk: {}. Continuing without writing.", e),
-                                    };
-                                },
-                                Err(e) => println!("Error during File::create(stored_rss_path): {}. Continuing without writing.", e),
-                            };
-                        }
-                        return Ok((network_file, num_to_update > 0));
+                            source.record_fetch::<String>(Ok(num_items));
+                            channels.push(network_file);
+                        },
+                        Err(e) => {
+                            println!("Could not get network file {}: {}", source.url, e);
+                            println!("Resuming with its archive");
+                            source.record_fetch(Err(e));
+                            if let Some(archive) = archive {
+                                channels.push(archive);
+                            }
+                        },
                     }
-                    Err(e) => {
-                        println!("Could not get network file: {}", e);
-                        println!("Resuming with stored rss file");
+                }
+
+                // The channel info comes from the first source we have anything for
+                let merged = match source::merge_channels(&channels) {
+                    Some(val) => val,
+                    None => {
                         // Panics if there was no stored rss and the network failed
                         if let Some(stored_rss) = stored_rss {
                             return Ok((stored_rss, false));
                         }
-                    }
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            "Error getting original rss",
+                        ));
+                    },
                 };
+
+                let changed = match &stored_rss {
+                    Some(rss) => {
+                        rss.items().len() != merged.items().len()
+                            || rss.items().iter().zip(merged.items()).any(|(a, b)| !source::same_item(a, b))
+                    },
+                    None => true,
+                };
+                if changed {
+                    // Overwrite our stored original RSS file
+                    if let Err(e) = write_channel(&merged, &stored_rss_path) {
+                        println!("Error writing merged rss to disk: {}. Continuing without writing.", e);
+                    }
+                }
+                return Ok((merged, changed));
             }
             RssFile::FromStorage => {
                 if let Some(stored_rss) = stored_rss {
@@ -721,51 +1920,159 @@ impl FeedRacer {
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////
-    //  NAME:   FeedRacer::get_num_to_publish
+    //  NAME:   FeedRacer::get_num_to_publish
+    //
+    //  NOTES:
+    //      Counts how many items are ready to publish according to this racer's rules.
+    //      This function must not panic, as it's used in the update thread
+    //  ARGS:   None
+    //  RETURN: The number of items that should be published.
+    //
+    pub fn get_num_to_publish(&self) -> usize {
+        // Get today's date (or pause date if paused)
+        self.get_num_published_at(self.get_reference_date())
+    }
+
+    // Counts how many release dates are before `time`
+    fn get_num_published_at(&self, time: DateTime<chrono::Utc>) -> usize {
+        let mut ret = 0;
+        for release_date in &self.release_dates {
+            let date = match chrono::DateTime::parse_from_rfc2822(&release_date.date) {
+                Ok(val) => val,
+                Err(e) => {
+                    println!("Error parsing release date {}: {}", release_date.date, e);
+                    continue;
+                }
+            };
+            if date.signed_duration_since(time) < chrono::Duration::zero() {
+                ret += 1;
+            }
+        }
+        ret
+    }
+
+    // The moment the feed is frozen at - the pause date if paused, now otherwise
+    fn get_reference_date(&self) -> DateTime<chrono::Utc> {
+        match self.pause_date {
+            None => chrono::Utc::now(),
+            Some(date) => date,
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::keep_num_published
+    //
+    //  NOTES:
+    //      Call after changing the rate or anything else that moves release dates around. If the
+    //      number of published episodes changed, re-anchors so it's back to `num_published`.
+    //  ARGS:
+    //      items - The items to render, in order
+    //      num_published - How many episodes should be out as of the reference date
+    //  RETURN: None
+    //
+    fn keep_num_published(&mut self, items: &Vec<rss::Item>, num_published: usize) {
+        self.render_release_dates(items);
+        // A mirror's dates are pinned to upstream's, there's no anchor to move
+        if let RacerType::Delay(_) = self.rate {
+            return;
+        }
+        if self.get_num_to_publish() != num_published {
+            self.anchor_to_num_published(items, num_published);
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::anchor_to_num_published
     //
     //  NOTES:
-    //      Counts how many items are ready to publish according to this racer's rules.
-    //      This function must not panic, as it's used in the update thread
-    //  ARGS:   None
-    //  RETURN: The number of items that should be published.
+    //      Moves the anchor date so exactly `num` episodes are out as of the reference date, with
+    //      the newest one going out a minute before. The published count only ever drops as the
+    //      anchor moves later, so we can search for the right anchor regardless of rate type,
+    //      publish time or schedule constraints.
+    //  ARGS:
+    //      items - The items to render, in order
+    //      num - How many episodes should be published. Clamped to the number of episodes.
+    //  RETURN: The number of episodes actually published
     //
-    pub fn get_num_to_publish(&self) -> usize {
-        let mut ret = 0;
-
-        // Get today's date (or pause date if paused)
-        let now = match self.pause_date {
-            None => chrono::Utc::now(),
-            Some(date) => date,
+    fn anchor_to_num_published(&mut self, items: &Vec<rss::Item>, num: usize) -> usize {
+        if let RacerType::Delay(days) = self.rate {
+            println!("{} mirrors upstream {} days late and can't be moved", self.podcast_title, days);
+            self.render_release_dates(items);
+            return self.get_num_to_publish();
+        }
+        let reference = self.get_reference_date();
+        // Search for the last anchor that still has `target` episodes out
+        let target = std::cmp::max(std::cmp::min(num, self.scheduled_indices(items).len()), 1);
+        let original_anchor = self.anchor_date;
+        let count_at = |racer: &mut FeedRacer, anchor: DateTime<chrono::Utc>| -> usize {
+            racer.anchor_date = anchor;
+            racer.render_release_dates(items);
+            racer.get_num_published_at(reference)
         };
 
-        // Count how many are before todays dates
-        for release_date in &self.release_dates {
-            let date = chrono::DateTime::parse_from_rfc2822(&release_date.date).unwrap();
-            if date.signed_duration_since(now) < chrono::Duration::zero() {
-                ret += 1;
+        // Find an anchor on either side of the boundary
+        let mut early = original_anchor;
+        let mut late = original_anchor;
+        let mut step = Duration::hours(1);
+        let mut found = false;
+        for _ in 0..40 {
+            if count_at(self, early) < target {
+                late = early;
+                early = match early.checked_sub_signed(step) { Some(val) => val, None => break };
+            }
+            else if count_at(self, late) >= target {
+                early = late;
+                late = match late.checked_add_signed(step) { Some(val) => val, None => break };
             }
+            else {
+                found = true;
+                break;
+            }
+            step = step * 2;
+        }
+        if !found {
+            println!("Error: could not find an anchor date with {} episodes published", num);
+            self.anchor_date = original_anchor;
+            self.render_release_dates(items);
+            return self.get_num_to_publish();
         }
 
-        ret
+        // Narrow it down to the second
+        while late.signed_duration_since(early) > Duration::seconds(1) {
+            let mid = early + late.signed_duration_since(early) / 2;
+            if count_at(self, mid) >= target {
+                early = mid;
+            }
+            else {
+                late = mid;
+            }
+        }
+
+        // Back off a minute so the newest episode is firmly published, or push the first one a
+        // minute out if we want nothing published.
+        let anchor = if num == 0 { late + Duration::minutes(1) } else { early - Duration::minutes(1) };
+        count_at(self, anchor)
     }
 
     pub fn get_num_episodes(&self) -> usize {
         self.release_dates.len()
     }
 
+    // The next release date as of the reference date, so a paused feed still reports the episode
+    // it stopped in front of. Now if there's nothing left to publish.
     pub fn get_next_episode_pub_date(&self) -> DateTime<chrono::Utc> {
-        let now = chrono::Utc::now();
+        let reference = self.get_reference_date();
         for release_date in &self.release_dates {
             let date = chrono::DateTime::parse_from_rfc2822(&release_date.date).unwrap();
-            if date.signed_duration_since(now) > chrono::Duration::zero() {
+            if date.signed_duration_since(reference) > chrono::Duration::zero() {
                 return date.into();
             }
         }
-        now
+        chrono::Utc::now()
     }
 
     pub fn get_next_episode_num(&self) -> usize {
-        let now = chrono::Utc::now();
+        let now = self.get_reference_date();
         let mut count: usize = 0;
         for release_date in &self.release_dates {
             let date = chrono::DateTime::parse_from_rfc2822(&release_date.date).unwrap();
@@ -779,35 +2086,23 @@ impl FeedRacer {
 
     pub fn get_episode_pub_date(&self, num: usize) -> DateTime<chrono::Utc> {
         let now = chrono::Utc::now();
-        let date = chrono::DateTime::parse_from_rfc2822(&self.release_dates[num].date).unwrap();
+        // Past the last episode there's nothing left to publish
+        let release_date = match self.release_dates.get(num) {
+            Some(val) => val,
+            None => return now,
+        };
+        let date = chrono::DateTime::parse_from_rfc2822(&release_date.date).unwrap();
         if date.signed_duration_since(now) > chrono::Duration::zero() {
             return date.into();
         }
         now
     }
 
-    // Publish next episode by moving the Anchor Date back. Takes the episode number to set the publish date to (1 indexed)
+    // Publish next episode by moving the Anchor Date back. Takes the episode number to set the publish date to (0 indexed)
     pub async fn publish_episode_num(&mut self, num: usize) {
-        // Get the date for the next episode to publish
-        let now = chrono::Utc::now();
-        let ep_publish_date = self.get_episode_pub_date(num);
-
-        // Move the anchor_date back by the difference
-        let time_to_publish_next = ep_publish_date.signed_duration_since(now);
-                                   // .checked_add(&Duration::seconds(30)).expect("Can add a few seconds");
-        self.anchor_date = match self.anchor_date.checked_sub_signed(time_to_publish_next) {
-            Some(time) => time,
-            None => {
-                println!("ERROR: Time addition overflow");
-                self.anchor_date
-            }
-        };
-
-        // Update now
-        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
-            Ok(_) => (),
-            Err(e) => println!("Error updating after fast-forward: {}", e),
-        }
+        // Re-anchor rather than shifting by the time left, publish times and schedule constraints
+        // don't move release dates one for one with the anchor
+        self.slide_to_num_published(1, num + 1).await;
     }
 
     // Publish next episode by moving the Anchor Date back
@@ -831,17 +2126,20 @@ impl FeedRacer {
     // }
 
     pub async fn pause_feed(&mut self) {
+        self.pause_at(chrono::Utc::now(), true).await;
+    }
+
+    pub async fn unpause_feed(&mut self) -> Option<chrono::Duration> {
+        self.unpause_at(chrono::Utc::now()).await
+    }
+
+    // Stops the clock at `date`. `manual` is false for pauses the schedule put in place.
+    async fn pause_at(&mut self, date: DateTime<chrono::Utc>, manual: bool) {
         match self.pause_date {
             None => {
-                // Save old rate
-                self.old_rate = Some(self.rate.clone());
-                // Set rate to 0
-                self.rate = match self.rate {
-                    RacerType::Ratio(_) => RacerType::Ratio(0.0),
-                    RacerType::Days(_) => RacerType::Days(1000),
-                };
-                // Save current date
-                self.pause_date = Some(chrono::Utc::now());
+                // Save the pause date. The rate stays as-is, we just stop the clock.
+                self.pause_date = Some(date);
+                self.manual_pause = manual;
 
                 // Update to write to file
                 match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
@@ -853,18 +2151,18 @@ impl FeedRacer {
         }
     }
 
-    pub async fn unpause_feed(&mut self) -> Option<chrono::Duration> {
+    // Starts the clock again as of `date`, shifting the anchor by the time spent paused
+    async fn unpause_at(&mut self, date: DateTime<chrono::Utc>) -> Option<chrono::Duration> {
         match self.pause_date {
             Some(pause_date) => {
-                // Restore rate
-                self.rate = self.old_rate.clone().expect("Old rate saved");
+                // Unpausing ends any scheduled pause we're in the middle of
+                self.scheduled_pauses.retain(|pause| !pause.active);
+                self.manual_pause = false;
 
-                // Set old rate to None
-                self.old_rate = None;
+                let num_published = self.get_num_to_publish();
 
                 // Adjust our anchor date to resume
-                let now = chrono::Utc::now();
-                let time_paused = now.signed_duration_since(pause_date);
+                let time_paused = std::cmp::max(date.signed_duration_since(pause_date), chrono::Duration::zero());
                 self.anchor_date = match self.anchor_date.checked_add_signed(time_paused) {
                     Some(time) => time,
                     None => {
@@ -873,6 +2171,14 @@ impl FeedRacer {
                     }
                 };
 
+                // Publish times + schedule constraints don't shift with the anchor, so make sure
+                // we come back with what we paused with. That's counted as of when we resumed,
+                // which may be earlier than now, so hold the clock there while we check.
+                self.pause_date = Some(date);
+                match self.get_stored_items() {
+                    Ok(items) => self.keep_num_published(&items, num_published),
+                    Err(e) => println!("Error getting stored items after unpausing: {}", e),
+                }
                 self.pause_date = None;
 
                 // Update to write to file
@@ -890,68 +2196,362 @@ impl FeedRacer {
         }
     }
 
-    fn get_episode_original_pub_date(&self, episode_num: usize) -> DateTime<chrono::Utc> {
-        // Get stored rss file
-        let mut stored_rss_path = self.racer_path.clone();
-        stored_rss_path.push(ORIGINAL_RSS_FILE);
-        let stored_rss_file = match File::open(&stored_rss_path) {
-            Ok(val) => Some(val),
-            Err(e) => {
-                println!("Error opening original rss file ({}): {}", stored_rss_path.display(), e);
-                None
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::add_scheduled_pause
+    //
+    //  NOTES:
+    //      Schedules a pause. The feed pauses at the start of `start` and resumes at the start of
+    //      `end`, both local dates in the feed's timezone.
+    //  ARGS:
+    //      start - The day to pause the feed
+    //      end - The day to resume the feed
+    //  RETURN: Result - error string if the dates don't make sense
+    //
+    pub async fn add_scheduled_pause(&mut self, start: NaiveDate, end: NaiveDate) -> Result<(), String> {
+        let tz = self.get_timezone();
+        let midnight = NaiveTime::from_hms_opt(0, 0, 0).expect("Midnight is valid");
+        let start = schedule::localize(&tz, start.and_time(midnight)).with_timezone(&chrono::Utc);
+        let end = schedule::localize(&tz, end.and_time(midnight)).with_timezone(&chrono::Utc);
+        if end <= start {
+            return Err(String::from("Scheduled pause must end after it starts"));
+        }
+        if end <= chrono::Utc::now() {
+            return Err(String::from("Scheduled pause is already over"));
+        }
+        self.scheduled_pauses.push(ScheduledPause { start, end, active: false });
+        self.scheduled_pauses.sort_by_key(|pause| pause.start);
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Error updating feed after scheduling pause: {}", e)),
+        }
+    }
+
+    // Removes a scheduled pause. Resumes the feed if that pause is currently in effect.
+    pub async fn cancel_scheduled_pause(&mut self, idx: usize) -> Result<ScheduledPause, String> {
+        if idx >= self.scheduled_pauses.len() {
+            return Err(format!("No scheduled pause at index {}", idx));
+        }
+        let pause = self.scheduled_pauses.remove(idx);
+        if pause.active && self.pause_date.is_some() {
+            // unpause_feed writes the file for us
+            self.unpause_feed().await;
+            return Ok(pause);
+        }
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(pause),
+            Err(e) => Err(format!("Error updating feed after cancelling pause: {}", e)),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::apply_scheduled_pauses
+    //
+    //  NOTES:
+    //      Pauses or resumes this feed according to its scheduled pauses. The feed is paused as
+    //      of the window's start and resumed as of its end, not whenever the update thread got
+    //      to it, so the anchor date moves by exactly the length of the window. If the feed was
+    //      paused by hand, the manual pause is left alone - windows opening or closing don't
+    //      touch it. Windows that opened and closed between runs are dropped without pausing.
+    //      This function must not panic, as it's used in the update thread.
+    //  ARGS:   None
+    //  RETURN: true if the feed was paused or resumed
+    //
+    pub async fn apply_scheduled_pauses(&mut self) -> bool {
+        let now = chrono::Utc::now();
+        let mut changed = false;
+        let mut resumed_at = None;
+        let checkpoint = self.checkpoint("Scheduled pause");
+
+        // Lift pauses that have run their course
+        let ended = self.scheduled_pauses.iter()
+            .find(|pause| pause.active && pause.end <= now)
+            .map(|pause| pause.end);
+        if let Some(end) = ended {
+            if self.pause_date.is_some() && !self.manual_pause {
+                self.unpause_at(end).await;
+                resumed_at = Some(end);
+                changed = true;
             }
-        };
-        let stored_rss = if let Some(f) = stored_rss_file {
-            let buf_reader = BufReader::new(f);
-            match rss::Channel::read_from(buf_reader) {
-                Ok(val) => Some(val),
-                Err(e) => {
-                    println!("Error reading rss channel from disk: {}", e);
-                    None
-                }
+            self.scheduled_pauses.retain(|pause| !pause.active);
+        }
+        // Drop windows we never got to (e.g. the server was down the whole time)
+        let num_pauses = self.scheduled_pauses.len();
+        self.scheduled_pauses.retain(|pause| pause.end > now);
+        let expired = num_pauses != self.scheduled_pauses.len();
+
+        // Apply pauses that have started, but never from before we last resumed
+        if self.pause_date.is_none() {
+            let started = self.scheduled_pauses.iter().position(|pause| pause.start <= now);
+            if let Some(idx) = started {
+                let start = match resumed_at {
+                    Some(resumed) => std::cmp::max(self.scheduled_pauses[idx].start, resumed),
+                    None => self.scheduled_pauses[idx].start,
+                };
+                self.scheduled_pauses[idx].active = true;
+                self.pause_at(start, false).await;
+                changed = true;
             }
-        } else {
-            None
+        }
+
+        if changed {
+            self.record_change(checkpoint);
+        }
+        else if expired {
+            match self.write_to_file() {
+                Ok(_) => (),
+                Err(e) => println!("Error writing the racer.file after dropping expired pauses: {}.", e),
+            }
+        }
+        changed
+    }
+
+    // Snapshot of where the feed is now. Hand it back to record_change after the edit.
+    pub fn checkpoint(&self, action: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: chrono::Utc::now(),
+            action: action.to_owned(),
+            anchor_date: self.anchor_date,
+            rate: self.rate.clone(),
+            pause_date: self.pause_date,
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::record_change
+    //
+    //  NOTES:
+    //      Adds a checkpoint to the history if the feed has moved since it was taken. Edits that
+    //      failed or didn't change anything aren't worth undoing, so they're left out.
+    //  ARGS:   before - The checkpoint taken before the edit
+    //  RETURN: True if the edit was recorded
+    //
+    pub fn record_change(&mut self, before: HistoryEntry) -> bool {
+        if before.same_state(&self.checkpoint("")) {
+            return false;
+        }
+        self.history.push(before);
+        if self.history.len() > MAX_HISTORY {
+            let num_to_drop = self.history.len() - MAX_HISTORY;
+            self.history.drain(..num_to_drop);
+        }
+        match self.write_to_file() {
+            Ok(_) => true,
+            Err(e) => {
+                println!("Error writing the racer.file after recording history: {}.", e);
+                false
+            }
+        }
+    }
+
+    // The history only holds the anchor, rate and pause state. Anchors taken before a change to
+    // which episodes go out or when would put the feed somewhere else now, so they can't be undone.
+    fn forget_history(&mut self) {
+        self.history.clear();
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::undo_last_change
+    //
+    //  NOTES:
+    //      Puts the anchor date, rate and pause state back the way they were before the last
+    //      recorded edit. Undoing isn't recorded, so repeated undos walk back through the history.
+    //  ARGS:   None
+    //  RETURN: The entry that was undone, or an error if there's nothing to undo
+    //
+    pub async fn undo_last_change(&mut self) -> Result<HistoryEntry, String> {
+        let entry = match self.history.pop() {
+            Some(val) => val,
+            None => return Err(String::from("Nothing to undo")),
         };
 
-        // Probably won't need this in the future
-        let mut items = stored_rss.expect("Stored rss exists").items().to_owned();
-        // Sorts ascending order
-        items.sort_by(|a, b| rss_item_cmp(a, b));
+        self.anchor_date = entry.anchor_date;
+        self.rate = entry.rate.clone();
+        self.pause_date = entry.pause_date;
+        // A scheduled pause that's been lifted by hand shouldn't put itself back
+        if self.pause_date.is_none() {
+            self.scheduled_pauses.retain(|pause| !pause.active);
+        }
+        self.manual_pause = self.pause_date.is_some() && !self.scheduled_pauses.iter().any(|pause| pause.active);
+
+        match self.update(&RssFile::FromStorage, &reqwest::Client::new()).await {
+            Ok(_) => Ok(entry),
+            Err(e) => Err(format!("Error updating feed after undoing {}: {}", entry.action, e)),
+        }
+    }
+
+    // Gets the stored original rss file
+    fn get_stored_rss(&self) -> std::io::Result<rss::Channel> {
+        let mut stored_rss_path = self.racer_path.clone();
+        stored_rss_path.push(ORIGINAL_RSS_FILE);
+        let stored_rss_file = File::open(&stored_rss_path)?;
+        match rss::Channel::read_from(BufReader::new(stored_rss_file)) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, e)),
+        }
+    }
 
-        DateTime::parse_from_rfc2822(items[episode_num].pub_date().expect("pub date stringable"))
-            .expect("can parse DateTime")
-            .with_timezone(&chrono::Utc)
+    // Gets the items from the stored original rss file, in publishing order
+    fn get_stored_items(&self) -> std::io::Result<Vec<rss::Item>> {
+        let stored_rss = self.get_stored_rss()?;
+        Ok(order::order_items(stored_rss.items().to_owned(), self.order, &self.playlist))
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::place_new_episodes
+    //
+    //  NOTES:
+    //      Adds episodes the playlist hasn't seen to it. Interleaved episodes only go after the
+    //      latest published episode, so nothing lands in the part of the feed already heard.
+    //  ARGS:   items - Every item, in the order the feed lists them
+    //  RETURN: None
+    //
+    fn place_new_episodes(&mut self, items: &[rss::Item]) {
+        let by_date = order::order_items(items.to_owned(), EpisodeOrder::PubDate, &self.playlist);
+        let first_open = match self.get_num_to_publish() {
+            0 => 0,
+            num => {
+                let arranged = self.playlist.arrange(by_date.clone());
+                match self.scheduled_items(&arranged).get(num - 1) {
+                    Some(latest) => {
+                        let latest_id = order::item_id(latest);
+                        match self.playlist.ids.iter().position(|id| *id == latest_id) {
+                            Some(idx) => idx + 1,
+                            None => self.playlist.ids.len(),
+                        }
+                    },
+                    None => self.playlist.ids.len(),
+                }
+            },
+        };
+        if self.playlist.add_new(&by_date, first_open) {
+            println!("Placed new episodes {} in the {} order", self.playlist.new_episodes, self.order);
+        }
     }
 
+    ////////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   FeedRacer::update_to_current_schema_version
+    //
+    //  NOTES:
+    //      Migrates racer files written by older versions.
+    //      1.1 -> 1.2: Pausing used to zero the rate (or set Days(1000)) and stash the real rate
+    //                  in old_rate. Put the real rate back.
+    //      1.2 -> 1.3: Racers had a single source_url. Make it the only source, and start its
+    //                  archive off with everything we've stored so far.
+    //  ARGS:   None
+    //  RETURN: None
+    //
     pub fn update_to_current_schema_version(&mut self) {
+        if let Some(old_rate) = self.old_rate.take() {
+            if self.pause_date.is_some() {
+                self.rate = old_rate;
+            }
+        }
+        if self.sources.is_empty() {
+            // Read-only loads don't write the racer back, so this has to come out the same every
+            // time. A random archive name would leave a new copy behind on each load.
+            let source = FeedSource {
+                archive_file: String::from(MIGRATED_SOURCE_ARCHIVE_FILE),
+                ..FeedSource::new(&self.source_url)
+            };
+            let stored_rss_path = self.racer_path.join(ORIGINAL_RSS_FILE);
+            if stored_rss_path.exists() && !self.racer_path.join(&source.archive_file).exists() {
+                match std::fs::copy(&stored_rss_path, self.racer_path.join(&source.archive_file)) {
+                    Ok(_) => (),
+                    Err(e) => println!("Error seeding the archive for {}: {}", self.source_url, e),
+                }
+            }
+            self.sources.push(source);
+        }
+        self.schema_version = SCHEMA_VERSION.to_owned();
+    }
+}
+
+// Reads an rss file, or None (with a note in the log) if it's missing or unreadable
+fn read_channel(path: &Path) -> Option<rss::Channel> {
+    let file = match File::open(path) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("Error opening rss file ({}): {}", path.display(), e);
+            return None;
+        }
+    };
+    match rss::Channel::read_from(BufReader::new(file)) {
+        Ok(val) => Some(val),
+        Err(e) => {
+            println!("Error reading rss channel from disk ({}): {}", path.display(), e);
+            None
+        }
+    }
+}
 
+fn write_channel(channel: &rss::Channel, path: &Path) -> Result<(), String> {
+    let file = match File::create(path) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Unable to create file: {}", e)),
+    };
+    match channel.pretty_write_to(file, SPACE_CHAR, INDENT_AMOUNT) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Unable to write rss file: {}", e)),
     }
 }
 
+// The original publish dates of the items, oldest first. Slot i goes with the i-th date. Items
+// with a missing or broken pubDate take the date of the item before them (or, at the start, the
+// first date there is), like order::order_items does.
+fn original_dates(items: &[rss::Item]) -> Vec<DateTime<chrono::FixedOffset>> {
+    let parsed: Vec<Option<DateTime<chrono::FixedOffset>>> = items.iter()
+        .map(|item| item.pub_date().and_then(|date| DateTime::parse_from_rfc2822(date).ok()))
+        .collect();
+    let mut current = parsed.iter().flatten().next().copied()
+        .unwrap_or_else(|| chrono::Utc::now().into());
+    let mut dates: Vec<DateTime<chrono::FixedOffset>> = parsed.into_iter()
+        .map(|date| {
+            if let Some(date) = date {
+                current = date;
+            }
+            current
+        })
+        .collect();
+    dates.sort();
+    dates
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
-//  NAME:   rss_item_cmp
+//  NAME:   delay_based_slots
 //
 //  NOTES:
-//      A sort function for rss items. Sorts by date. Lots of string stuff for each item, might
-//      need to come up with a better solution to this if it ends up being a bottleneck.
-//  ARGS:   a/b - The rss::Items to sort
-//  RETURN: An ordering
+//      Slot i goes out `days` after the first item in it did upstream. Used for mirrors, where
+//      the anchor doesn't matter. Items that came out of order upstream wait for the one ahead
+//      of them, so release dates never go backwards.
+//  ARGS:
+//      scheduled - The scheduled items, in order
+//      slot_idxs - The slot each scheduled item goes in
+//      num_slots - How many slots there are
+//      days - How far behind upstream to publish
+//  RETURN: The release date of each slot
 //
-fn rss_item_cmp(a: &rss::Item, b: &rss::Item) -> std::cmp::Ordering {
-    let a_sec = DateTime::parse_from_rfc2822(a.pub_date().unwrap())
-        .unwrap()
-        .timestamp();
-    let b_sec = DateTime::parse_from_rfc2822(b.pub_date().unwrap())
-        .unwrap()
-        .timestamp();
-    if a_sec < b_sec {
-        std::cmp::Ordering::Less
-    } else if b_sec < a_sec {
-        std::cmp::Ordering::Greater
-    } else {
-        std::cmp::Ordering::Equal
+fn delay_based_slots(scheduled: &[rss::Item], slot_idxs: &[usize], num_slots: usize, days: u32)
+    -> Vec<DateTime<chrono::Utc>> {
+    let mut slots: Vec<DateTime<chrono::Utc>> = Vec::with_capacity(num_slots);
+    for (item, slot_idx) in scheduled.iter().zip(slot_idxs) {
+        if *slot_idx < slots.len() {
+            continue;
+        }
+        let original = match item.pub_date().map(DateTime::parse_from_rfc2822) {
+            Some(Ok(val)) => Some(val.with_timezone(&chrono::Utc) + Duration::days(days as i64)),
+            _ => None,
+        };
+        let slot = match (original, slots.last()) {
+            (Some(date), Some(prev)) => std::cmp::max(date, *prev),
+            (Some(date), None) => date,
+            (None, Some(prev)) => *prev,
+            (None, None) => chrono::Utc::now() + Duration::days(days as i64),
+        };
+        slots.push(slot);
     }
+    slots
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -966,7 +2566,8 @@ fn rss_item_cmp(a: &rss::Item, b: &rss::Item) -> std::cmp::Ordering {
 fn get_racer_at_path(path: &str) -> std::io::Result<FeedRacer> {
     let racer_file_path: PathBuf = [path, RACER_FILE].iter().collect();
     let racer_file = File::open(racer_file_path)?;
-    let racer: FeedRacer = serde_json::from_reader(&racer_file)?;
+    let mut racer: FeedRacer = serde_json::from_reader(&racer_file)?;
+    racer.update_to_current_schema_version();
     Ok(racer)
 }
 
@@ -979,13 +2580,16 @@ fn get_racer_at_path(path: &str) -> std::io::Result<FeedRacer> {
 //  ARGS:
 //      path - the directory of the racer of interest
 //      preferred_mode - Whether we prefer to download a fresh copy or not.
-//  RETURN: A result. Typically only fails on I/O or network stuff.
+//  RETURN: A result with if there were new episodes + if the racer just caught up. Typically only
+//          fails on I/O or network stuff.
 //
-async fn update_racer_at_path(path: &str, preferred_mode: &RssFile, client: &reqwest::Client) -> std::io::Result<bool> {
+async fn update_racer_at_path(path: &str, preferred_mode: &RssFile, client: &reqwest::Client) -> std::io::Result<(bool, bool)> {
     // Load in racer file
     let mut racer = get_racer_at_path(path)?;
 
-    racer.update(preferred_mode, client).await
+    let was_caught_up = racer.caught_up.is_some();
+    let new_eps = racer.update(preferred_mode, client).await?;
+    Ok((new_eps, !was_caught_up && racer.caught_up.is_some()))
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -993,15 +2597,17 @@ async fn update_racer_at_path(path: &str, preferred_mode: &RssFile, client: &req
 //
 //  NOTES:
 //      Gets all the dirs in base_dir. Each of these dirs has info for one
-//      feed.
+//      feed. Files (like the server's footer settings) are skipped.
 //      This function must not panic, as it's used in the update thread.
 //  ARGS:   None
-//  RETURN: All the items in the podracer dir
+//  RETURN: All the dirs in the podracer dir
 //
-pub fn get_all_podcast_dirs(base_dir: &str) -> Result<std::fs::ReadDir, String> {
+pub fn get_all_podcast_dirs(base_dir: &str) -> Result<Vec<std::io::Result<std::fs::DirEntry>>, String> {
     let dir = String::from(base_dir);
     match Path::read_dir(Path::new(&dir)) {
-        Ok(val) => Ok(val),
+        Ok(val) => Ok(val
+            .filter(|entry| entry.as_ref().map(|entry| entry.path().is_dir()).unwrap_or(true))
+            .collect()),
         Err(e) => return Err(format!("Cannot access dir: {:?}.\nError: {}", dir, e)),
     }
 }
@@ -1019,6 +2625,7 @@ pub async fn update_all<'a>(base_dir: &str, client: &'a reqwest::Client) -> Resu
     let start = std::time::SystemTime::now();
     let mut counter = 0;
     let mut num_with_new_eps = 0;
+    let mut num_caught_up = 0;
     let podcast_dirs = match get_all_podcast_dirs(base_dir) {
         Ok(val) => val,
         Err(str) => return Err(format!("Error in update_all: {}", str)),
@@ -1030,42 +2637,36 @@ pub async fn update_all<'a>(base_dir: &str, client: &'a reqwest::Client) -> Resu
         .map(|podcast_dir| {
             let client = &client;
             async move {
-                let mut has_new_eps = false;
                 let path = match podcast_dir {
                     Ok(val) => val.path(),
                     Err(e) => {
                         println!("Error iterating over path from read_dir: {}", e);
-                        return has_new_eps;
+                        return (false, false);
                     },
                 };
                 let path_str = match path.to_str() {
                     Some(val) => val,
                     None => {
                         println!("Tried to open empty path");
-                        return has_new_eps;
+                        return (false, false);
                     },
                 };
 
                 match update_racer_at_path(path_str, &RssFile::Download, client).await {
-                    Ok(new_eps) => {
-                        if new_eps {
-                            has_new_eps = true;
-                        }
-                    }
+                    Ok(val) => val,
                     Err(e) => {
                         println!("Could not update path {}. Error was: {}", path_str, e);
-                        return has_new_eps;
+                        (false, false)
                     }
-                };
-
-                has_new_eps
+                }
             }
         })
         .buffer_unordered(parallel_gets);
 
-    let new_eps_vec = results.collect::<Vec<bool>>().await;
-    for new_ep in new_eps_vec {
+    let new_eps_vec = results.collect::<Vec<(bool, bool)>>().await;
+    for (new_ep, caught_up) in new_eps_vec {
         if new_ep { num_with_new_eps += 1; }
+        if caught_up { num_caught_up += 1; }
         counter += 1;
     }
 
@@ -1081,9 +2682,38 @@ pub async fn update_all<'a>(base_dir: &str, client: &'a reqwest::Client) -> Resu
         num_updated: counter,
         time: duration,
         num_with_new_eps,
+        num_caught_up,
     })
 }
 
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   apply_all_scheduled_pauses
+//
+//  NOTES:
+//      Pauses + resumes every racer on this server according to its scheduled pauses.
+//      This function must not panic, as it's used in the update thread.
+//  ARGS:   base_dir - The podracer dir
+//  RETURN: The number of racers that were paused or resumed, or an error string
+//
+pub async fn apply_all_scheduled_pauses(base_dir: &str) -> Result<u64, String> {
+    let racers = match get_all_racers(base_dir) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Error in apply_all_scheduled_pauses: {}", e)),
+    };
+
+    let mut num_changed = 0;
+    for mut racer in racers {
+        if racer.scheduled_pauses.is_empty() {
+            continue;
+        }
+        if racer.apply_scheduled_pauses().await {
+            println!("Applied scheduled pause for {} ({})", racer.get_podcast_title(), racer.get_uuid());
+            num_changed += 1;
+        }
+    }
+    Ok(num_changed)
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //  NAME:   create_feed
 //
@@ -1092,28 +2722,38 @@ pub async fn update_all<'a>(base_dir: &str, client: &'a reqwest::Client) -> Resu
 //  RETURN: A FeedRacer or error String
 //
 pub async fn create_feed(params: &mut RacerCreationParams, client: &reqwest::Client) -> Result<FeedRacer, String> {
-    if None == params.url.find("http") {
-        params.url = String::from("https://") + &params.url;
+    let local_media_root = params.local_media_root.as_ref().map(PathBuf::from);
+    params.url = local::check_local_url(source::normalize_url(&params.url), local_media_root.as_deref())?;
+    let mut extra_urls: Vec<String> = Vec::new();
+    for url in params.extra_urls.iter().filter(|url| !url.trim().is_empty()) {
+        let url = local::check_local_url(source::normalize_url(url), local_media_root.as_deref())?;
+        if url != params.url && !extra_urls.contains(&url) {
+            extra_urls.push(url);
+        }
     }
-    let rss = match download_rss_channel(client, &params.url).await {
-        Ok(val) => val,
-        Err(e) => return Err(format!("Error downloading rss feed: {}", e)),
-    };
+    params.extra_urls = extra_urls;
+
+    let mut channels = Vec::new();
+    for url in std::iter::once(&params.url).chain(params.extra_urls.iter()) {
+        match download_rss_channel(client, url).await {
+            Ok(val) => channels.push(val),
+            Err(e) => return Err(format!("Error downloading rss feed {}: {}", url, e)),
+        };
+    }
+    // The first feed names the podcast, everything else just adds items
+    let rss = source::merge_channels(&channels).expect("Downloaded at least one feed");
 
     // Make racer
-    let racer = FeedRacer::new(&rss, &params);
+    let mut racer = FeedRacer::new(&rss, &params)?;
     // Make directory
     std::fs::create_dir_all(&racer.racer_path).expect("Creating racer dir");
-    let racer_path = racer.racer_path.to_str().expect("Can transform dirname to str");
-    // Write out original rss feed to file in dir
-    let original_rss_file = match File::create(String::from(racer_path) + "/" + ORIGINAL_RSS_FILE) {
-        Ok(val) => val,
-        Err(e) => return Err(format!("Unable to create file: {}", e)),
-    };
-    match rss.pretty_write_to(original_rss_file, SPACE_CHAR, 2) {
-        Ok(_) => (),
-        Err(e) => return Err(format!("unable to write original rss file: {}", e)),
-    };
+    let racer_path = racer.racer_path.to_str().expect("Can transform dirname to str").to_owned();
+    // Write out original rss feed to file in dir, along with each source's archive
+    write_channel(&rss, &racer.racer_path.join(ORIGINAL_RSS_FILE))?;
+    for (source, channel) in racer.sources.iter_mut().zip(&channels) {
+        write_channel(channel, &Path::new(&racer_path).join(&source.archive_file))?;
+        source.record_fetch::<String>(Ok(channel.items().len()));
+    }
     match racer.write_to_file() {
         Ok(_) => (),
         Err(e) => return Err(format!("failed with error: {}", e)),
@@ -1135,11 +2775,16 @@ pub async fn create_feed(params: &mut RacerCreationParams, client: &reqwest::Cli
 //  NAME:   download_rss_channel
 //
 //  NOTES:
-//      Handles the network stuff for getting an rss file from the network.
+//      Handles the network stuff for getting a feed from the network. Atom and JSON Feeds are
+//      turned into an rss channel, see ingest.rs. file:// urls are read from a local directory
+//      instead, see local.rs.
 //  ARGS:   url - the url of the file to get
 //  RETURN: A channel or error information
 //
 pub async fn download_rss_channel(client: &reqwest::Client, url: &str) -> Result<rss::Channel, Box<dyn std::error::Error>> {
+    if let Some(dir) = local::local_path(url) {
+        return local::read_local_channel(&dir).map_err(|e| e.into());
+    }
     let content = match client.get(url).send().await {
         Ok(val) => match val.bytes().await {
             Ok(val) => val,
@@ -1148,21 +2793,11 @@ pub async fn download_rss_channel(client: &reqwest::Client, url: &str) -> Result
         Err(e) => return Err(Box::new(e)),
     };
 
-    //
-    // Scrub the file
-    //
-    let buf_content = BufReader::new(&content[..]);
-    let tmp_file_name = "/tmp/scrubbed.rss".to_owned();
-    let scrubbed_file = File::create(&tmp_file_name).expect("Failed to create tmp scrub file");
-    scrub_xml_content_to_file(buf_content, &scrubbed_file);
-    let scrubbed_file = File::open(&tmp_file_name).expect("Failed to open tmp scrub file");
-    let scrubbed_buff = std::io::BufReader::new(&scrubbed_file);
-
-    match rss::Channel::read_from(scrubbed_buff) {
+    match ingest::parse_feed(&content) {
         Ok(channel) => Ok(channel),
         Err(e) => {
             println!("Failure when downloading rss channel");
-            Err(Box::new(e))
+            Err(e.into())
         }
     }
 }
@@ -1170,16 +2805,33 @@ pub async fn download_rss_channel(client: &reqwest::Client, url: &str) -> Result
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //  NAME:   get_by_dir_name
 //
-//  NOTES:  Check if the specified directory hosts a FeedRacer + return it.
+//  NOTES:
+//      Check if the specified directory hosts a FeedRacer + return it. The name comes straight
+//      from request urls, so anything but a plain directory name under base_dir is turned away,
+//      and a directory without a readable racer file is treated as not being a racer.
 //  ARGS:   target_dir: the name of the directory to check
 //  RETURN: A FeedRacer or None
 //
 pub fn get_by_dir_name(base_dir: &str, target_dir: &str) -> Option<FeedRacer> {
+    let mut components = Path::new(target_dir).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(_)), None) => (),
+        _ => {
+            println!("{:?} is not a racer dir name", target_dir);
+            return None;
+        }
+    }
     let mut dir = PathBuf::from(base_dir);
     dir.push(target_dir);
     if dir.is_dir() {
-        let racer = get_racer_at_path(dir.as_path().to_str().unwrap()).unwrap();
-        return Some(racer);
+        let dir_str = dir.to_str()?;
+        return match get_racer_at_path(dir_str) {
+            Ok(racer) => Some(racer),
+            Err(e) => {
+                println!("Error loading racer from {:?}: {}", dir, e);
+                None
+            }
+        };
     }
     println!("{:?} is not a racer dir", dir);
     None
@@ -1239,16 +2891,16 @@ pub fn get_all_racers(base_dir: &str) -> Result<Vec<FeedRacer>, String> {
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
-//  NAME:   scrub_xml_content_to_file
+//  NAME:   scrub_xml_content
 //
 //  NOTES:
 //      Some rss feeds don't properly escape things. Properly escape known issues.
 //      This is not really scalable, but if I'm the only one using it then it should be more or
-//      less fine.
-//  ARGS:   file_name - The file to scrub and replace
-//  RETURN: None
+//      less fine. Done in memory so concurrent downloads don't trample each other's scrub file.
+//  ARGS:   in_buf - The content to scrub
+//  RETURN: The scrubbed content
 //
-pub fn scrub_xml_content_to_file<B: BufRead>(in_buf: B, file: &File) {
+pub fn scrub_xml_content<B: BufRead>(in_buf: B) -> Vec<u8> {
     // Known bad strings
     let subs: std::collections::HashMap<String, String> =
         [("& ".to_owned(), "&amp; ".to_owned())]
@@ -1256,22 +2908,37 @@ pub fn scrub_xml_content_to_file<B: BufRead>(in_buf: B, file: &File) {
     //subs.insert("&source".to_owned(), "&amp;source".to_owned());
     //subs.insert("&stitched".to_owned(), "&amp;stitched".to_owned());
 
-    let mut out_buf = std::io::BufWriter::new(file);
+    let mut out_buf: Vec<u8> = Vec::new();
     in_buf
         .lines()
         .map(|line_res| {
-            line_res.and_then(|mut line| {
+            line_res.map(|mut line| {
                 for (key, val) in &subs {
                     if line.contains(key) {
                         line = line.replace(key, val);
                     }
                 }
                 line.push_str("\n");
-                out_buf.write_all(line.as_bytes())
+                out_buf.extend_from_slice(line.as_bytes());
             })
         })
         .collect::<Result<(), _>>()
         .expect("IO failed");
+    out_buf
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   scrub_xml_content_to_file
+//
+//  NOTES:  Scrubs content with scrub_xml_content and writes it out to a file
+//  ARGS:
+//      in_buf - The content to scrub
+//      file - Where to write the scrubbed content
+//  RETURN: None
+//
+pub fn scrub_xml_content_to_file<B: BufRead>(in_buf: B, file: &File) {
+    let mut out_buf = std::io::BufWriter::new(file);
+    out_buf.write_all(&scrub_xml_content(in_buf)).expect("IO failed");
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -1306,27 +2973,13 @@ trait RssExt {
     fn correct_known_rss_issues(&mut self, url: &str);
 }
 impl RssExt for rss::Channel {
-    // Attempt to fix things that I know are wrong in the feeds that I use.
+    // Attempt to fix things that I know are wrong in the feeds that I use. iTunes and podcast
+    // namespace metadata passes through, minus the elements in metadata.rs that are known bad.
     fn correct_known_rss_issues(&mut self, url: &str) {
-        //// Check  for itunes:owner element itunes:email
-        //match &mut self.itunes_ext {
-        //    Some(itunes) => {
-        //        match &mut itunes.owner {
-        //            Some(owner) => {
-        //                if owner.name.is_some() {
-        //                    if owner.email.is_none() {
-        //                        owner.set_email("example@example.com".to_owned());
-        //                    }
-        //                }
-        //            },
-        //            None => (),
-        //        }
-        //    },
-        //    None => (),
-        //}
-
-        // Remove iTunes stuff
-        self.set_itunes_ext(None);
+        if let Some(itunes) = &mut self.itunes_ext {
+            metadata::fix_channel_itunes(itunes);
+        }
+        metadata::fix_channel_podcast(&mut self.extensions);
 
         // Correct self links
         let ext = &mut self.extensions;
@@ -1348,17 +3001,12 @@ impl RssExt for rss::Channel {
             None => (),
         };
 
-        // Remove <media:rights status="userCreated" />
         for item in &mut self.items {
-            // Remove iTunes stuff
-            item.set_itunes_ext(None);
-            let ext = &mut item.extensions;
-            match ext.get_mut("media") {
-                Some(media) => {
-                    media.remove("rights");
-                },
-                None => (),
+            if let Some(itunes) = &mut item.itunes_ext {
+                metadata::fix_item_itunes(itunes);
             }
+            metadata::fix_item_podcast(&mut item.extensions);
+            metadata::fix_item_media(&mut item.extensions);
         }
     }
 }
@@ -1386,10 +3034,51 @@ impl fmt::Display for FeedRacer {
         writeln!(f, "schema_version: {}", self.schema_version)?;
         writeln!(f, "racer_path: {}", self.racer_path.display())?;
         writeln!(f, "source_url: {}", self.source_url)?;
+        for source in &self.sources {
+            writeln!(f, "source: {}", source)?;
+        }
         writeln!(f, "subscribe_url: {}", self.subscribe_url)?;
         writeln!(f, "anchor_date: {}", self.anchor_date)?;
         writeln!(f, "first_pubdate: {}", self.first_pubdate)?;
         writeln!(f, "rate: {}", self.rate)?;
+        writeln!(f, "timezone: {}", self.timezone)?;
+        if let Some(publish_time) = self.publish_time {
+            writeln!(f, "publish_time: {}", publish_time)?;
+        }
+        if let Some(quiet_hours) = &self.constraints.quiet_hours {
+            writeln!(f, "quiet_hours: {}", quiet_hours)?;
+        }
+        writeln!(f, "skip_weekends: {}", self.constraints.skip_weekends)?;
+        for blackout in &self.constraints.blackouts {
+            writeln!(f, "blackout: {}", blackout)?;
+        }
+        for pause in &self.scheduled_pauses {
+            writeln!(f, "scheduled_pause: {}", pause)?;
+        }
+        if !self.exclusions.is_empty() {
+            writeln!(f, "exclusions: {}", self.exclusions)?;
+        }
+        writeln!(f, "episode_type_policy: {}", self.episode_type_policy)?;
+        writeln!(f, "order: {}", self.order)?;
+        writeln!(f, "catch_up_policy: {}", self.catch_up_policy)?;
+        writeln!(f, "footer: {}", self.footer)?;
+        writeln!(f, "title: {}", self.decoration.title)?;
+        writeln!(f, "badge: {}", self.decoration.badge)?;
+        writeln!(f, "namespace_guids: {}", self.namespace_guids)?;
+        writeln!(f, "proxy_enclosures: {}", self.proxy_enclosures)?;
+        writeln!(f, "archive_dir: {:?}", self.archive_dir)?;
+        if let Some(caught_up) = &self.caught_up {
+            writeln!(f, "caught_up: {}", caught_up)?;
+        }
+        if self.order.uses_playlist() {
+            writeln!(f, "playlist: {}", self.playlist)?;
+        }
+        if let Some(seasons) = &self.seasons {
+            writeln!(f, "seasons: {}", seasons)?;
+        }
+        for entry in &self.history {
+            writeln!(f, "history: {}", entry)?;
+        }
         writeln!(f, "release_dates {{")?;
         for entry in self.release_dates.as_slice() {
             writeln!(f, "\t{},", entry)?;
@@ -1407,6 +3096,7 @@ impl fmt::Display for RacerType {
         let to_write = match self {
             RacerType::Ratio(rate) => format!("Ratio({})", rate),
             RacerType::Days(days) => format!("Days({})", days),
+            RacerType::Delay(days) => format!("Delay({})", days),
         };
         write!(f, "{}", to_write)
     }
@@ -0,0 +1,363 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   local.rs
+//
+//  © Zach Nielsen 2020
+//  Feeds made from a directory of audio files, for shows that have no rss
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::{DateTime, Duration};
+use id3::TagLike;
+
+use crate::archive::ArchiveManifest;
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// Sources that start with this are directories on this machine
+pub const LOCAL_SCHEME: &str = "file://";
+// Directory episodes have no release dates, so they're given one a day from here
+const FIRST_PUB_DATE: &str = "Sat, 01 Jan 2000 00:00:00 +0000";
+
+// What we could read from an audio file's tags
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AudioTags {
+    pub title: Option<String>,
+    pub album: Option<String>,
+    pub artist: Option<String>,
+    pub comment: Option<String>,
+    pub track: Option<u32>,
+    pub disc: Option<u32>,
+}
+
+// An audio file found in the directory
+struct LocalEpisode {
+    path: PathBuf,
+    // Relative to the directory, with '/' between components
+    name: String,
+    mime_type: String,
+    size: u64,
+    tags: AudioTags,
+}
+
+// The directory in a local source url, if it is one
+pub fn local_path(url: &str) -> Option<PathBuf> {
+    let path = Path::new(url.trim().strip_prefix(LOCAL_SCHEME)?);
+    match path.is_absolute() {
+        true => Some(path.to_owned()),
+        false => None,
+    }
+}
+
+pub fn local_url(path: &Path) -> String {
+    format!("{}{}", LOCAL_SCHEME, path.display())
+}
+
+// Audio types we'll put in a feed, by extension
+fn audio_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "mp3" => Some("audio/mpeg"),
+        "m4a" | "m4b" => Some("audio/mp4"),
+        "aac" => Some("audio/aac"),
+        "ogg" | "oga" | "opus" => Some("audio/ogg"),
+        "flac" => Some("audio/flac"),
+        "wav" => Some("audio/wav"),
+        _ => None,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   read_local_channel
+//
+//  NOTES:
+//      Builds a channel out of the audio files in a directory and its sub-directories. Episodes
+//      are in track order (disc, then track) if every file has a track number, and filename
+//      order otherwise. They're a day apart, oldest first, so racers schedule them like any other
+//      feed. If podarch downloaded the files, episodes keep the GUIDs from its manifest.
+//  ARGS:   dir - The directory to read
+//  RETURN: The channel, or an error if the directory can't be read or has no audio in it
+//
+pub fn read_local_channel(dir: &Path) -> Result<rss::Channel, String> {
+    let dir = match std::fs::canonicalize(dir) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Can't read {:?}: {}", dir, e)),
+    };
+    if !dir.is_dir() {
+        return Err(format!("{:?} is not a directory", dir));
+    }
+    let mut episodes = Vec::new();
+    find_episodes(&dir, &dir, &mut episodes)?;
+    if episodes.is_empty() {
+        return Err(format!("No audio files in {:?}", dir));
+    }
+    match episodes.iter().all(|episode| episode.tags.track.is_some()) {
+        true => episodes.sort_by(|a, b| {
+            (a.tags.disc.unwrap_or(1), a.tags.track, &a.name).cmp(&(b.tags.disc.unwrap_or(1), b.tags.track, &b.name))
+        }),
+        false => episodes.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+
+    // Everything on one album by one artist names the podcast. Otherwise it's the directory.
+    let common = |tag: fn(&AudioTags) -> Option<&String>| {
+        let first = tag(&episodes[0].tags)?;
+        match episodes.iter().all(|episode| tag(&episode.tags) == Some(first)) {
+            true => Some(first.to_owned()),
+            false => None,
+        }
+    };
+    let dir_name = dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let title = common(|tags| tags.album.as_ref()).unwrap_or(dir_name);
+    let artist = common(|tags| tags.artist.as_ref());
+
+    let manifest = ArchiveManifest::read(&dir).ok();
+    let first_date = DateTime::parse_from_rfc2822(FIRST_PUB_DATE).expect("First pub date parses");
+    let items: Vec<rss::Item> = episodes.iter().enumerate().map(|(idx, episode)| {
+        let guid = manifest.as_ref()
+            .and_then(|manifest| manifest.episodes.iter().find(|archived| archived.file == episode.name))
+            .map(|archived| archived.guid.clone())
+            .unwrap_or_else(|| episode.name.clone());
+        let title = episode.tags.title.clone().unwrap_or_else(|| {
+            Path::new(&episode.name).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+        });
+        rss::Item {
+            title: Some(title),
+            description: episode.tags.comment.clone(),
+            author: episode.tags.artist.clone(),
+            guid: Some(rss::Guid { value: guid, permalink: false }),
+            enclosure: Some(rss::Enclosure {
+                url: local_url(&episode.path),
+                length: episode.size.to_string(),
+                mime_type: episode.mime_type.clone(),
+            }),
+            pub_date: Some((first_date + Duration::days(idx as i64)).to_rfc2822()),
+            ..Default::default()
+        }
+    }).rev().collect();
+
+    Ok(rss::Channel {
+        title,
+        link: local_url(&dir),
+        description: match artist {
+            Some(artist) => format!("By {}", artist),
+            None => String::from("Audio files on the PodRacer server"),
+        },
+        items,
+        ..Default::default()
+    })
+}
+
+// Adds the audio files under `path` to `episodes`. Hidden files and symlinks are skipped, so a
+// link back up the tree can't send us around in circles (or out of the directory).
+fn find_episodes(dir: &Path, path: &Path, episodes: &mut Vec<LocalEpisode>) -> Result<(), String> {
+    let entries = match std::fs::read_dir(path) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Can't read {:?}: {}", path, e)),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let file_type = match entry.file_type() {
+            Ok(val) => val,
+            Err(_) => continue,
+        };
+        if file_type.is_dir() {
+            find_episodes(dir, &path, episodes)?;
+            continue;
+        }
+        if !file_type.is_file() {
+            continue;
+        }
+        let mime_type = match audio_mime_type(&path) {
+            Some(val) => val,
+            None => continue,
+        };
+        let name = match path.strip_prefix(dir) {
+            Ok(val) => val.components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => continue,
+        };
+        episodes.push(LocalEpisode {
+            size: entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            tags: read_tags(&path),
+            mime_type: mime_type.to_owned(),
+            name,
+            path,
+        });
+    }
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   check_local_dir
+//
+//  NOTES:
+//      Makes sure a directory typed into a form is one the server is willing to read from. Only
+//      directories inside the server's local media root are allowed, and none at all if it
+//      doesn't have one.
+//  ARGS:
+//      dir - The directory to check
+//      root - The server's local media root, if it has one
+//  RETURN: The directory with links and ".." resolved, or why it can't be used
+//
+pub fn check_local_dir(dir: &Path, root: Option<&Path>) -> Result<PathBuf, String> {
+    let root = match root {
+        Some(val) => val,
+        None => return Err(String::from("This server doesn't allow local directories. Set local_media_root to allow them.")),
+    };
+    let root = match std::fs::canonicalize(root) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Can't use local media root {:?}: {}", root, e)),
+    };
+    let dir = match std::fs::canonicalize(dir) {
+        Ok(val) => val,
+        Err(e) => return Err(format!("Can't use {:?}: {}", dir, e)),
+    };
+    if !dir.starts_with(&root) {
+        return Err(format!("{:?} is outside of the local media root {:?}", dir, root));
+    }
+    if !dir.is_dir() {
+        return Err(format!("{:?} is not a directory", dir));
+    }
+    Ok(dir)
+}
+
+// Checks a local source url with check_local_dir and points it at the resolved directory. Other
+// urls are passed back as they are.
+pub(crate) fn check_local_url(url: String, root: Option<&Path>) -> Result<String, String> {
+    match local_path(&url) {
+        Some(dir) => Ok(local_url(&check_local_dir(&dir, root)?)),
+        None => Ok(url),
+    }
+}
+
+// True if `path` is a file inside the local source `dir`, so it's safe to serve
+pub(crate) fn is_in_dir(dir: &Path, path: &Path) -> bool {
+    match (std::fs::canonicalize(dir), std::fs::canonicalize(path)) {
+        (Ok(dir), Ok(path)) => path.starts_with(dir) && path.is_file(),
+        _ => false,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   read_tags
+//
+//  NOTES:
+//      Reads ID3 tags from mp3s and Vorbis comments from Ogg (Vorbis and Opus) and FLAC files.
+//      Anything else, or a file with broken tags, has no tags.
+//  ARGS:   path - The audio file
+//  RETURN: The tags
+//
+pub fn read_tags(path: &Path) -> AudioTags {
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "mp3" => match id3::Tag::read_from_path(path) {
+            Ok(tag) => AudioTags {
+                title: non_empty(tag.title()),
+                album: non_empty(tag.album()),
+                artist: non_empty(tag.artist()),
+                comment: tag.comments().next().and_then(|comment| non_empty(Some(&comment.text))),
+                track: tag.track(),
+                disc: tag.disc(),
+            },
+            Err(_) => AudioTags::default(),
+        },
+        "flac" => read_flac_comments(path).map(|comments| vorbis_tags(&comments)).unwrap_or_default(),
+        "ogg" | "oga" | "opus" => read_ogg_comments(path).map(|comments| vorbis_tags(&comments)).unwrap_or_default(),
+        _ => AudioTags::default(),
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|value| !value.is_empty()).map(str::to_owned)
+}
+
+// Turns "KEY=value" comments into tags. Keys are case insensitive, and track numbers can be
+// written "3/12".
+fn vorbis_tags(comments: &[String]) -> AudioTags {
+    let get = |key: &str| {
+        comments.iter()
+            .filter_map(|comment| comment.split_once('='))
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .and_then(|(_, value)| non_empty(Some(value)))
+    };
+    let number = |key: &str| get(key).and_then(|value| value.split('/').next()?.trim().parse::<u32>().ok());
+    AudioTags {
+        title: get("TITLE"),
+        album: get("ALBUM"),
+        artist: get("ARTIST"),
+        comment: get("DESCRIPTION").or_else(|| get("COMMENT")),
+        track: number("TRACKNUMBER"),
+        disc: number("DISCNUMBER"),
+    }
+}
+
+// Reads a Vorbis comment block: a vendor string, then a count of "KEY=value" strings. Every
+// length is a little endian u32.
+fn parse_vorbis_comments(data: &[u8]) -> Option<Vec<String>> {
+    let mut pos = 0;
+    let read_u32 = |pos: &mut usize| -> Option<u32> {
+        let bytes = data.get(*pos..*pos + 4)?;
+        *pos += 4;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let vendor_len = read_u32(&mut pos)? as usize;
+    pos += vendor_len;
+    let count = read_u32(&mut pos)?;
+    let mut comments = Vec::new();
+    for _ in 0..count {
+        let len = read_u32(&mut pos)? as usize;
+        let comment = data.get(pos..pos + len)?;
+        pos += len;
+        comments.push(String::from_utf8_lossy(comment).into_owned());
+    }
+    Some(comments)
+}
+
+// FLAC files start with "fLaC" and a list of metadata blocks. Each has a header byte (the top bit
+// marks the last block, the rest is the type) and a 24 bit big endian length. Type 4 holds the
+// comments.
+fn read_flac_comments(path: &Path) -> Option<Vec<String>> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut marker = [0u8; 4];
+    file.read_exact(&mut marker).ok()?;
+    if &marker != b"fLaC" {
+        return None;
+    }
+    loop {
+        let mut header = [0u8; 4];
+        file.read_exact(&mut header).ok()?;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let mut block = vec![0u8; len];
+        file.read_exact(&mut block).ok()?;
+        if header[0] & 0x7f == 4 {
+            return parse_vorbis_comments(&block);
+        }
+        if header[0] & 0x80 != 0 {
+            return None;
+        }
+    }
+}
+
+// The comments are the second packet of an Ogg stream, after "\x03vorbis" or "OpusTags"
+fn read_ogg_comments(path: &Path) -> Option<Vec<String>> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut reader = ogg::PacketReader::new(std::io::BufReader::new(file));
+    reader.read_packet().ok()??;
+    let packet = reader.read_packet().ok()??;
+    let data = packet.data.strip_prefix(b"\x03vorbis")
+        .or_else(|| packet.data.strip_prefix(b"OpusTags"))?;
+    parse_vorbis_comments(data)
+}
@@ -0,0 +1,190 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   media.rs
+//
+//  © Zach Nielsen 2020
+//  Serving episode audio through PodRacer, for upstream hosts that have died or geoblock
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use std::path::PathBuf;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// Racer dir sub-directory where archived copies of episodes live, named by their encoded GUID
+pub const MEDIA_DIR: &str = "media";
+
+// Where an episode's audio comes from
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaSource {
+    // An archived copy in the racer's media dir
+    Local { path: PathBuf, mime_type: String },
+    // The enclosure url from the original feed
+    Upstream { url: String },
+}
+
+// What a Range header asks for, once the size is known
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ByteRange {
+    // No range, or one we don't handle (multiple ranges, bad syntax). The whole file is sent.
+    Full,
+    // First and last byte, inclusive
+    Partial(u64, u64),
+    // Starts past the end of the file
+    Unsatisfiable,
+}
+
+// The body of a media response
+pub enum MediaBody {
+    File(tokio::io::Take<tokio::fs::File>),
+    Upstream(reqwest::Response),
+    Empty,
+}
+
+// Everything needed to answer a media request
+pub struct Media {
+    pub status: u16,
+    pub mime_type: String,
+    pub content_length: Option<u64>,
+    pub content_range: Option<String>,
+    // Whether the client can ask for ranges next time
+    pub accept_ranges: bool,
+    pub body: MediaBody,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   parse_range
+//
+//  NOTES:
+//      Reads a Range header (RFC 7233) for a file of `size` bytes. Only single byte ranges are
+//      supported: "bytes=10-19", "bytes=10-" and "bytes=-10". Anything else is ignored, which
+//      the spec allows, and the whole file is sent.
+//  ARGS:
+//      header - The Range header
+//      size - Size of the file in bytes
+//  RETURN: The range to send
+//
+pub fn parse_range(header: &str, size: u64) -> ByteRange {
+    let spec = match header.trim().strip_prefix("bytes=") {
+        Some(val) if !val.contains(',') => val.trim(),
+        _ => return ByteRange::Full,
+    };
+    let (start, end) = match spec.split_once('-') {
+        Some(val) => val,
+        None => return ByteRange::Full,
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        // The last `end` bytes
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if size == 0 => ByteRange::Unsatisfiable,
+            Ok(len) => ByteRange::Partial(size - std::cmp::min(len, size), size - 1),
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let start = match start.parse::<u64>() {
+        Ok(val) => val,
+        Err(_) => return ByteRange::Full,
+    };
+    let end = match end {
+        "" => None,
+        end => match end.parse::<u64>() {
+            Ok(val) if val >= start => Some(val),
+            _ => return ByteRange::Full,
+        },
+    };
+    if start >= size {
+        return ByteRange::Unsatisfiable;
+    }
+    let last = size - 1;
+    ByteRange::Partial(start, end.map(|end| std::cmp::min(end, last)).unwrap_or(last))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   open_media
+//
+//  NOTES:
+//      Opens the audio for a media request. Local copies are sliced to the requested range here.
+//      Upstream requests pass the Range header along, and whatever the upstream host answers
+//      with (a range or the whole file) is passed back.
+//  ARGS:
+//      source - Where the audio is
+//      range - The request's Range header, if any
+//      client - For fetching upstream audio
+//  RETURN: The response to send, or an error if the audio couldn't be opened
+//
+pub(crate) async fn open_media(source: &MediaSource, range: Option<&str>, client: &reqwest::Client)
+    -> Result<Media, String> {
+    match source {
+        MediaSource::Local { path, mime_type } => {
+            let mut file = match tokio::fs::File::open(path).await {
+                Ok(val) => val,
+                Err(e) => return Err(format!("Unable to open {:?}: {}", path, e)),
+            };
+            let size = match file.metadata().await {
+                Ok(val) => val.len(),
+                Err(e) => return Err(format!("Unable to read {:?}: {}", path, e)),
+            };
+            let range = range.map(|range| parse_range(range, size)).unwrap_or(ByteRange::Full);
+            let (status, start, len, content_range) = match range {
+                ByteRange::Full => (200, 0, size, None),
+                ByteRange::Partial(start, end) => {
+                    (206, start, end - start + 1, Some(format!("bytes {}-{}/{}", start, end, size)))
+                },
+                ByteRange::Unsatisfiable => {
+                    return Ok(Media {
+                        status: 416,
+                        mime_type: mime_type.to_owned(),
+                        content_length: Some(0),
+                        content_range: Some(format!("bytes */{}", size)),
+                        accept_ranges: true,
+                        body: MediaBody::Empty,
+                    });
+                },
+            };
+            if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+                return Err(format!("Unable to seek in {:?}: {}", path, e));
+            }
+            Ok(Media {
+                status,
+                mime_type: mime_type.to_owned(),
+                content_length: Some(len),
+                content_range,
+                accept_ranges: true,
+                body: MediaBody::File(file.take(len)),
+            })
+        },
+        MediaSource::Upstream { url } => {
+            let mut request = client.get(url);
+            if let Some(range) = range {
+                request = request.header(reqwest::header::RANGE, range);
+            }
+            let response = match request.send().await {
+                Ok(val) => val,
+                Err(e) => return Err(format!("Error fetching {}: {}", url, e)),
+            };
+            let header = |name: reqwest::header::HeaderName| {
+                response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_owned)
+            };
+            let mime_type = header(reqwest::header::CONTENT_TYPE)
+                .unwrap_or_else(|| String::from("application/octet-stream"));
+            let content_range = header(reqwest::header::CONTENT_RANGE);
+            let status = response.status().as_u16();
+            let accept_ranges = status == 206
+                || header(reqwest::header::ACCEPT_RANGES).map(|value| value == "bytes").unwrap_or(false);
+            Ok(Media {
+                status,
+                mime_type,
+                content_length: response.content_length(),
+                content_range,
+                accept_ranges,
+                body: MediaBody::Upstream(response),
+            })
+        },
+    }
+}
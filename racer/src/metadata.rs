@@ -0,0 +1,179 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   metadata.rs
+//
+//  © Zach Nielsen 2020
+//  Passing iTunes and Podcasting 2.0 metadata through to the racer feed, fixing only the
+//  elements we know are broken
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use rss::extension::itunes::{ITunesCategory, ITunesChannelExtension, ITunesItemExtension};
+use rss::extension::ExtensionMap;
+
+use crate::exclude::parse_duration;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+pub const PODCAST_NAMESPACE_PREFIX: &str = "podcast";
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   fix_channel_itunes
+//
+//  NOTES:
+//      Everything is kept except:
+//      - new-feed-url, which would move subscribers off the racer and onto the original feed
+//      - explicit values Apple doesn't know, which are mapped to true/false or dropped
+//      - type values other than episodic/serial
+//      - empty or non-http image links
+//      - categories without a name
+//      - an owner without an email, which fails Apple's validator
+//  ARGS:   itunes - The channel's iTunes data
+//  RETURN: None
+//
+pub(crate) fn fix_channel_itunes(itunes: &mut ITunesChannelExtension) {
+    itunes.new_feed_url = None;
+    itunes.explicit = itunes.explicit.as_deref().and_then(fix_explicit);
+    itunes.r#type = itunes.r#type.as_deref().and_then(|value| one_of(value, &["episodic", "serial"]));
+    itunes.image = itunes.image.as_deref().and_then(fix_image);
+    itunes.categories = fix_categories(&itunes.categories);
+    let no_email = itunes.owner.as_ref()
+        .map(|owner| owner.email.as_deref().unwrap_or("").trim().is_empty())
+        .unwrap_or(false);
+    if no_email {
+        itunes.owner = None;
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   fix_item_itunes
+//
+//  NOTES:
+//      Everything is kept except values Apple would reject:
+//      - durations that aren't seconds, MM:SS or HH:MM:SS. Fractional seconds are rounded down.
+//      - explicit values other than true/false (and the old yes/clean/no)
+//      - season and episode numbers that aren't positive whole numbers
+//      - episodeType values other than full/trailer/bonus
+//      - empty or non-http image links
+//  ARGS:   itunes - The item's iTunes data
+//  RETURN: None
+//
+pub(crate) fn fix_item_itunes(itunes: &mut ITunesItemExtension) {
+    itunes.duration = itunes.duration.as_deref().and_then(fix_duration);
+    itunes.explicit = itunes.explicit.as_deref().and_then(fix_explicit);
+    itunes.season = itunes.season.as_deref().and_then(fix_positive_number);
+    itunes.episode = itunes.episode.as_deref().and_then(fix_positive_number);
+    itunes.episode_type = itunes.episode_type.as_deref()
+        .and_then(|value| one_of(value, &["full", "trailer", "bonus"]));
+    itunes.image = itunes.image.as_deref().and_then(fix_image);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   fix_channel_podcast
+//
+//  NOTES:
+//      podcast:guid identifies the show itself. Podcast apps and the Podcast Index would treat the
+//      racer as the original show if we kept it, so it's dropped. Everything else in the podcast
+//      namespace (funding, person, locked, value...) passes through.
+//  ARGS:   extensions - The channel's extensions
+//  RETURN: None
+//
+pub(crate) fn fix_channel_podcast(extensions: &mut ExtensionMap) {
+    if let Some(podcast) = extensions.get_mut(PODCAST_NAMESPACE_PREFIX) {
+        podcast.remove("guid");
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   fix_item_podcast
+//
+//  NOTES:
+//      Chapters, transcripts and soundbites are kept. Chapters and transcripts without a url
+//      point nowhere and make some apps error out, so those get dropped.
+//  ARGS:   extensions - The item's extensions
+//  RETURN: None
+//
+pub(crate) fn fix_item_podcast(extensions: &mut ExtensionMap) {
+    if let Some(podcast) = extensions.get_mut(PODCAST_NAMESPACE_PREFIX) {
+        for name in ["chapters", "transcript"] {
+            if let Some(elements) = podcast.get_mut(name) {
+                elements.retain(|element| {
+                    element.attrs.get("url").map(|url| !url.trim().is_empty()).unwrap_or(false)
+                });
+                if elements.is_empty() {
+                    podcast.remove(name);
+                }
+            }
+        }
+    }
+}
+
+// <media:rights status="userCreated" /> isn't valid media rss. Other rights are kept.
+pub(crate) fn fix_item_media(extensions: &mut ExtensionMap) {
+    if let Some(media) = extensions.get_mut("media") {
+        if let Some(rights) = media.get_mut("rights") {
+            rights.retain(|element| element.attrs.get("status").map(String::as_str) != Some("userCreated"));
+            if rights.is_empty() {
+                media.remove("rights");
+            }
+        }
+    }
+}
+
+// Apple takes true/false. Older feeds use yes/explicit and no/clean.
+fn fix_explicit(value: &str) -> Option<String> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "explicit" => Some(String::from("true")),
+        "false" | "no" | "clean" => Some(String::from("false")),
+        _ => None,
+    }
+}
+
+fn fix_duration(value: &str) -> Option<String> {
+    let value = value.trim();
+    if parse_duration(value).is_some() {
+        return Some(value.to_owned());
+    }
+    match value.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Some((secs as u64).to_string()),
+        _ => None,
+    }
+}
+
+fn fix_positive_number(value: &str) -> Option<String> {
+    match value.trim().parse::<u32>() {
+        Ok(num) if num > 0 => Some(num.to_string()),
+        _ => None,
+    }
+}
+
+fn fix_image(value: &str) -> Option<String> {
+    let value = value.trim();
+    match value.starts_with("http://") || value.starts_with("https://") {
+        true => Some(value.to_owned()),
+        false => None,
+    }
+}
+
+// The lowercase value, if it's one of `allowed`
+fn one_of(value: &str, allowed: &[&str]) -> Option<String> {
+    let value = value.trim().to_lowercase();
+    match allowed.contains(&value.as_str()) {
+        true => Some(value),
+        false => None,
+    }
+}
+
+fn fix_categories(categories: &[ITunesCategory]) -> Vec<ITunesCategory> {
+    categories.iter()
+        .filter(|category| !category.text.trim().is_empty())
+        .map(|category| ITunesCategory {
+            text: category.text.clone(),
+            subcategory: category.subcategory.as_ref()
+                .and_then(|sub| fix_categories(std::slice::from_ref(sub.as_ref())).pop())
+                .map(Box::new),
+        })
+        .collect()
+}
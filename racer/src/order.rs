@@ -0,0 +1,338 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   order.rs
+//
+//  © Zach Nielsen 2020
+//  Which order a racer publishes episodes in
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::DateTime;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::episode::EpisodeInfo;
+
+use std::collections::HashMap;
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum EpisodeOrder {
+    // Oldest <pubDate> first
+    #[default]
+    PubDate,
+    // By <itunes:season>, then <itunes:episode>
+    EpisodeNumber,
+    // By the first number in the title
+    TitleNumber,
+    // The reverse of the order items appear in the feed, since feeds list the newest first
+    FeedOrder,
+    // A random order, from the playlist's seed
+    Shuffle,
+    // The playlist's list of GUIDs, then everything else by date
+    Curated,
+}
+
+// Where episodes go when they show up after a shuffled or curated order was set up
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum NewEpisodePlacement {
+    // After everything else
+    #[default]
+    End,
+    // Somewhere random among the episodes that haven't been published yet
+    Interleave,
+}
+
+// The fixed order behind EpisodeOrder::Shuffle and EpisodeOrder::Curated. Once an episode has a
+// place it keeps it, so updates never reshuffle what's already there.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Playlist {
+    #[serde(default)]
+    pub seed: u64,
+    // GUIDs in the order the user wants them, for Curated
+    #[serde(default)]
+    pub curated: Vec<String>,
+    #[serde(default)]
+    pub new_episodes: NewEpisodePlacement,
+    // Item ids in publishing order. Filled in by resolve and add_new.
+    #[serde(default)]
+    pub ids: Vec<String>,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   order_items
+//
+//  NOTES:
+//      Puts items in publishing order. Every strategy starts from the reversed feed order and
+//      does a stable sort, so items that tie (same timestamp, same number) always come out in
+//      the same order - the one listed later in the feed goes first. Items missing the value
+//      we're sorting on (no number, bad date) take the value of the item before them, which
+//      keeps them next to their neighbours. Shuffled and curated orders follow the playlist.
+//  ARGS:
+//      items - The items, in the order the feed lists them
+//      order - The strategy to use
+//      playlist - The fixed order for Shuffle and Curated
+//  RETURN: The items in publishing order
+//
+pub fn order_items(mut items: Vec<rss::Item>, order: EpisodeOrder, playlist: &Playlist) -> Vec<rss::Item> {
+    if order.uses_playlist() {
+        return playlist.arrange(order_items(items, EpisodeOrder::PubDate, playlist));
+    }
+    items.reverse();
+    if order == EpisodeOrder::FeedOrder {
+        return items;
+    }
+
+    // Number-based orders fall back to dates for the unnumbered items, so sort by date first
+    let dates = inherit(items.iter().map(pub_date_key).collect());
+    let mut keyed: Vec<((i64, i64), rss::Item)> = dates.into_iter()
+        .map(|date| (date, 0))
+        .zip(items)
+        .collect();
+    keyed.sort_by_key(|(key, _)| *key);
+    if order == EpisodeOrder::PubDate {
+        return keyed.into_iter().map(|(_, item)| item).collect();
+    }
+
+    let title_number = Regex::new(r"\d+").expect("Title number regex is valid");
+    let numbers = inherit(keyed.iter()
+        .map(|(_, item)| match order {
+            EpisodeOrder::EpisodeNumber => {
+                let info = EpisodeInfo::from_item(item);
+                info.episode.map(|episode| (info.season.unwrap_or(0) as i64, episode as i64))
+            },
+            _ => item.title()
+                .and_then(|title| title_number.find(title))
+                .and_then(|number| number.as_str().parse().ok())
+                .map(|number| (number, 0)),
+        })
+        .collect());
+    for ((key, _), number) in keyed.iter_mut().zip(numbers) {
+        *key = number;
+    }
+    keyed.sort_by_key(|(key, _)| *key);
+    keyed.into_iter().map(|(_, item)| item).collect()
+}
+
+// How the playlist refers to an item: its guid, or failing that its enclosure or title
+pub(crate) fn item_id(item: &rss::Item) -> String {
+    if let Some(guid) = item.guid() {
+        return guid.value().trim().to_owned();
+    }
+    if let Some(enclosure) = item.enclosure() {
+        return enclosure.url().trim().to_owned();
+    }
+    item.title().unwrap_or("").trim().to_owned()
+}
+
+impl Playlist {
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   Playlist::resolve
+    //
+    //  NOTES:
+    //      Works out the full order from scratch. Shuffle shuffles everything with the seed, so
+    //      the same seed always gives the same order. Curated puts the listed GUIDs first and the
+    //      rest after them by date.
+    //  ARGS:
+    //      order - Shuffle or Curated. Anything else clears the playlist.
+    //      items - Every item, oldest first
+    //  RETURN: An error if a curated GUID isn't in the feed
+    //
+    pub fn resolve(&mut self, order: EpisodeOrder, items: &[rss::Item]) -> Result<(), String> {
+        let mut ids: Vec<String> = items.iter().map(item_id).collect();
+        match order {
+            EpisodeOrder::Shuffle => {
+                let mut rng = SplitMix64(self.seed);
+                for idx in (1..ids.len()).rev() {
+                    ids.swap(idx, rng.below(idx + 1));
+                }
+            },
+            EpisodeOrder::Curated => {
+                if self.curated.is_empty() {
+                    return Err(String::from("A curated order needs at least one GUID"));
+                }
+                let missing: Vec<&str> = self.curated.iter()
+                    .filter(|guid| !ids.contains(guid))
+                    .map(|guid| guid.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(format!("GUIDs not in the feed: {}", missing.join(", ")));
+                }
+                ids.retain(|id| !self.curated.contains(id));
+                ids = self.curated.iter().cloned().chain(ids).collect();
+            },
+            _ => ids.clear(),
+        }
+        self.ids = ids;
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   Playlist::add_new
+    //
+    //  NOTES:  Gives a place to every item the playlist doesn't know about yet.
+    //  ARGS:
+    //      items - Every item, oldest first
+    //      first_open - The first place new items may go. Everything before it is published.
+    //  RETURN: True if anything was added
+    //
+    pub fn add_new(&mut self, items: &[rss::Item], first_open: usize) -> bool {
+        let first_open = std::cmp::min(first_open, self.ids.len());
+        let mut added = false;
+        for id in items.iter().map(item_id) {
+            if self.ids.contains(&id) {
+                continue;
+            }
+            match self.new_episodes {
+                NewEpisodePlacement::End => self.ids.push(id),
+                NewEpisodePlacement::Interleave => {
+                    // Seeded off the playlist length so the same update always lands the same way
+                    let mut rng = SplitMix64(self.seed ^ (self.ids.len() as u64).wrapping_mul(0x9E37_79B9));
+                    let idx = first_open + rng.below(self.ids.len() - first_open + 1);
+                    self.ids.insert(idx, id);
+                },
+            }
+            added = true;
+        }
+        added
+    }
+
+    // Puts items in playlist order. Anything the playlist doesn't know goes last, in the order given.
+    pub fn arrange(&self, items: Vec<rss::Item>) -> Vec<rss::Item> {
+        let positions: HashMap<&str, usize> = self.ids.iter()
+            .enumerate()
+            .rev()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+        let mut keyed: Vec<(usize, rss::Item)> = items.into_iter()
+            .map(|item| (positions.get(item_id(&item).as_str()).copied().unwrap_or(usize::MAX), item))
+            .collect();
+        keyed.sort_by_key(|(position, _)| *position);
+        keyed.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+// Small seeded random number generator. Hand rolled so a seed means the same order forever,
+// whatever version of a random crate we'd otherwise be on.
+struct SplitMix64(u64);
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    // Uniform enough in 0..n for shuffling episodes
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn pub_date_key(item: &rss::Item) -> Option<i64> {
+    item.pub_date()
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.timestamp())
+}
+
+// Fills in missing keys from the key before (or, at the start, the first key there is)
+fn inherit<K: Copy + Default>(keys: Vec<Option<K>>) -> Vec<K> {
+    let mut current = keys.iter().flatten().next().copied().unwrap_or_default();
+    keys.into_iter()
+        .map(|key| {
+            if let Some(key) = key {
+                current = key;
+            }
+            current
+        })
+        .collect()
+}
+
+impl std::str::FromStr for EpisodeOrder {
+    type Err = String;
+    fn from_str(s: &str) -> Result<EpisodeOrder, String> {
+        match s.trim() {
+            "pubdate" => Ok(EpisodeOrder::PubDate),
+            "episode" => Ok(EpisodeOrder::EpisodeNumber),
+            "title" => Ok(EpisodeOrder::TitleNumber),
+            "feed" => Ok(EpisodeOrder::FeedOrder),
+            "shuffle" => Ok(EpisodeOrder::Shuffle),
+            "curated" => Ok(EpisodeOrder::Curated),
+            _ => Err(format!("Unknown episode order {}", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for NewEpisodePlacement {
+    type Err = String;
+    fn from_str(s: &str) -> Result<NewEpisodePlacement, String> {
+        match s.trim() {
+            "end" => Ok(NewEpisodePlacement::End),
+            "interleave" => Ok(NewEpisodePlacement::Interleave),
+            _ => Err(format!("Unknown new episode placement {}", s)),
+        }
+    }
+}
+
+impl NewEpisodePlacement {
+    // The name FromStr takes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NewEpisodePlacement::End => "end",
+            NewEpisodePlacement::Interleave => "interleave",
+        }
+    }
+}
+
+impl EpisodeOrder {
+    // The name FromStr takes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EpisodeOrder::PubDate => "pubdate",
+            EpisodeOrder::EpisodeNumber => "episode",
+            EpisodeOrder::TitleNumber => "title",
+            EpisodeOrder::FeedOrder => "feed",
+            EpisodeOrder::Shuffle => "shuffle",
+            EpisodeOrder::Curated => "curated",
+        }
+    }
+
+    // True for orders that follow a Playlist
+    pub fn uses_playlist(&self) -> bool {
+        matches!(self, EpisodeOrder::Shuffle | EpisodeOrder::Curated)
+    }
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for EpisodeOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpisodeOrder::PubDate => write!(f, "publish date"),
+            EpisodeOrder::EpisodeNumber => write!(f, "episode number"),
+            EpisodeOrder::TitleNumber => write!(f, "number in title"),
+            EpisodeOrder::FeedOrder => write!(f, "feed order"),
+            EpisodeOrder::Shuffle => write!(f, "shuffle"),
+            EpisodeOrder::Curated => write!(f, "curated list"),
+        }
+    }
+}
+impl fmt::Display for NewEpisodePlacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NewEpisodePlacement::End => write!(f, "at the end"),
+            NewEpisodePlacement::Interleave => write!(f, "interleaved"),
+        }
+    }
+}
+impl fmt::Display for Playlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {}, {} curated, new episodes {}, {} placed", self.seed, self.curated.len(),
+               self.new_episodes, self.ids.len())
+    }
+}
@@ -0,0 +1,175 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   schedule.rs
+//
+//  © Zach Nielsen 2020
+//  Constraints on when a racer is allowed to publish
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// Give up pushing a date after this many moves, so a schedule that excludes everything can't hang
+// the update thread.
+const MAX_PUSHES: usize = 1000;
+
+// No publishing between `start` and `end` (local time). Wraps midnight if start > end.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+// No publishing from `start` through `end`, inclusive (local dates)
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BlackoutRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+// Pause the feed at `start`, resume it at `end`. `active` is set once the pause has been applied.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ScheduledPause {
+    pub start: DateTime<chrono::Utc>,
+    pub end: DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub active: bool,
+}
+
+// Optional constraints on when episodes can drop. Applies to all racer types.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ScheduleConstraints {
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    #[serde(default)]
+    pub skip_weekends: bool,
+    #[serde(default)]
+    pub blackouts: Vec<BlackoutRange>,
+}
+
+impl QuietHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        }
+        else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl BlackoutRange {
+    fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+impl ScheduleConstraints {
+    pub fn is_empty(&self) -> bool {
+        self.quiet_hours.is_none() && !self.skip_weekends && self.blackouts.is_empty()
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   ScheduleConstraints::next_allowed
+    //
+    //  NOTES:
+    //      Pushes a release date forward until it lands outside every excluded window. Excluded
+    //      days (weekends, blackouts) push to the start of the next day - the feed's publish time
+    //      if it has one, midnight otherwise. Quiet hours push to the end of the quiet window.
+    //      Only ever moves dates later, so episode order is preserved.
+    //  ARGS:
+    //      date - The release date to check
+    //      day_start - Where to land when pushed to a new day
+    //  RETURN: The first allowed time at or after `date`
+    //
+    pub fn next_allowed(&self, date: DateTime<Tz>, day_start: Option<NaiveTime>) -> DateTime<Tz> {
+        let tz = date.timezone();
+        let day_start = day_start.unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).expect("Midnight is valid"));
+        let mut candidate = date;
+
+        for _ in 0..MAX_PUSHES {
+            let day = candidate.date_naive();
+            let time = candidate.time();
+
+            if self.is_excluded_day(day) {
+                let next_day = match day.succ_opt() {
+                    Some(val) => val,
+                    None => return candidate,
+                };
+                candidate = localize(&tz, next_day.and_time(day_start));
+                continue;
+            }
+
+            if let Some(quiet_hours) = &self.quiet_hours {
+                if quiet_hours.contains(time) {
+                    // Wrapped windows that started yesterday end today, otherwise they end tomorrow
+                    let end_day = if quiet_hours.start > quiet_hours.end && time >= quiet_hours.start {
+                        match day.succ_opt() {
+                            Some(val) => val,
+                            None => return candidate,
+                        }
+                    }
+                    else {
+                        day
+                    };
+                    candidate = localize(&tz, end_day.and_time(quiet_hours.end));
+                    continue;
+                }
+            }
+
+            return candidate;
+        }
+
+        println!("Error: could not find an allowed publish time after {}. Schedule constraints may exclude everything.", date);
+        candidate
+    }
+
+    fn is_excluded_day(&self, day: NaiveDate) -> bool {
+        if self.skip_weekends {
+            match day.weekday() {
+                Weekday::Sat | Weekday::Sun => return true,
+                _ => (),
+            }
+        }
+        self.blackouts.iter().any(|blackout| blackout.contains(day))
+    }
+}
+
+// Pin a local time to the timezone, stepping past DST gaps
+pub(crate) fn localize(tz: &Tz, local: NaiveDateTime) -> DateTime<Tz> {
+    match tz.from_local_datetime(&local).earliest() {
+        Some(val) => val,
+        None => localize(tz, local + Duration::hours(1)),
+    }
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+impl fmt::Display for ScheduledPause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.start.to_rfc2822(), self.end.to_rfc2822())?;
+        if self.active {
+            write!(f, " (active)")?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Display for BlackoutRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.start, self.end)
+    }
+}
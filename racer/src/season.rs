@@ -0,0 +1,146 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   season.rs
+//
+//  © Zach Nielsen 2020
+//  Racing a show season by season
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::episode::EpisodeInfo;
+use crate::RacerType;
+
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// Where an episode's season number comes from
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum SeasonSource {
+    // <itunes:season>
+    ITunes,
+    // A regex run against the title. The first capture group is the season number.
+    TitlePattern(String),
+}
+
+// Overrides the racer's rate for one season
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SeasonRate {
+    pub season: u32,
+    pub rate: RacerType,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SeasonConfig {
+    pub source: SeasonSource,
+    // Seasons without an entry here use the racer's rate
+    #[serde(default)]
+    pub rates: Vec<SeasonRate>,
+    // Extra break between the last episode of a season and the first of the next
+    #[serde(default)]
+    pub gap_days: u32,
+}
+
+impl SeasonConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let SeasonSource::TitlePattern(pattern) = &self.source {
+            let regex = match Regex::new(pattern) {
+                Ok(val) => val,
+                Err(e) => return Err(format!("Invalid season pattern {}: {}", pattern, e)),
+            };
+            if regex.captures_len() < 2 {
+                return Err(format!("Season pattern {} needs a capture group for the season number", pattern));
+            }
+        }
+        for season_rate in &self.rates {
+            let valid = match season_rate.rate {
+                RacerType::Ratio(rate) => rate > 0.0,
+                RacerType::Days(days) => days > 0,
+                RacerType::Delay(_) => {
+                    return Err(format!("Season {} can't be a delay, only whole feeds can", season_rate.season));
+                },
+            };
+            if !valid {
+                return Err(format!("Rate for season {} must be > 0", season_rate.season));
+            }
+        }
+        Ok(())
+    }
+
+    // The rate season `season` races at
+    pub fn rate_for(&self, season: Option<u32>, default: &RacerType) -> RacerType {
+        season
+            .and_then(|season| self.rates.iter().find(|season_rate| season_rate.season == season))
+            .map(|season_rate| season_rate.rate.clone())
+            .unwrap_or_else(|| default.clone())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+    //  NAME:   SeasonConfig::seasons_of
+    //
+    //  NOTES:
+    //      Works out the season of each item. Items that don't say belong to the same season as
+    //      the item before them, and anything before the first numbered item belongs to the first
+    //      season we find.
+    //  ARGS:   items - The items, in order
+    //  RETURN: The season of each item, None if no item has a season at all
+    //
+    pub fn seasons_of(&self, items: &[rss::Item]) -> Vec<Option<u32>> {
+        let pattern = match &self.source {
+            SeasonSource::ITunes => None,
+            SeasonSource::TitlePattern(pattern) => match Regex::new(pattern) {
+                Ok(val) => Some(val),
+                Err(e) => {
+                    println!("Error compiling season pattern {}, ignoring it: {}", pattern, e);
+                    None
+                }
+            },
+        };
+
+        let mut seasons: Vec<Option<u32>> = items.iter()
+            .map(|item| match &pattern {
+                None => EpisodeInfo::from_item(item).season,
+                Some(pattern) => item.title()
+                    .and_then(|title| pattern.captures(title))
+                    .and_then(|captures| captures.get(1))
+                    .and_then(|season| season.as_str().trim().parse().ok()),
+            })
+            .collect();
+
+        let first = seasons.iter().flatten().next().copied();
+        let mut current = first;
+        for season in seasons.iter_mut() {
+            match season {
+                Some(val) => current = Some(*val),
+                None => *season = current,
+            }
+        }
+        seasons
+    }
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for SeasonSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeasonSource::ITunes => write!(f, "itunes:season"),
+            SeasonSource::TitlePattern(pattern) => write!(f, "title pattern {}", pattern),
+        }
+    }
+}
+impl fmt::Display for SeasonConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "from {}, {} day gap", self.source, self.gap_days)?;
+        for season_rate in &self.rates {
+            write!(f, ", season {}: {}", season_rate.season, season_rate.rate)?;
+        }
+        Ok(())
+    }
+}
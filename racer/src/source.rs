@@ -0,0 +1,160 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   source.rs
+//
+//  © Zach Nielsen 2020
+//  Upstream feeds a racer is built from, and merging them into one archive
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::local::LOCAL_SCHEME;
+
+use std::collections::HashSet;
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// One upstream feed, plus how the last fetch of it went
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FeedSource {
+    pub url: String,
+    // Every item this source has ever listed is kept in this file in the racer dir, so items
+    // that fall off the end of the upstream feed aren't lost
+    pub archive_file: String,
+    #[serde(default)]
+    pub last_checked: Option<DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub last_success: Option<DateTime<chrono::Utc>>,
+    // Why the last fetch failed. Cleared on the next success.
+    #[serde(default)]
+    pub last_error: Option<String>,
+    // Items in this source's archive
+    #[serde(default)]
+    pub num_items: usize,
+}
+
+impl FeedSource {
+    pub fn new(url: &str) -> FeedSource {
+        FeedSource {
+            url: url.to_owned(),
+            archive_file: format!("source_{}.rss", uuid::Uuid::new_v4()),
+            last_checked: None,
+            last_success: None,
+            last_error: None,
+            num_items: 0,
+        }
+    }
+
+    // Notes down the result of a fetch
+    pub(crate) fn record_fetch<E: fmt::Display>(&mut self, result: Result<usize, E>) {
+        let now = chrono::Utc::now();
+        self.last_checked = Some(now);
+        match result {
+            Ok(num_items) => {
+                self.last_success = Some(now);
+                self.last_error = None;
+                self.num_items = num_items;
+            },
+            Err(e) => self.last_error = Some(e.to_string()),
+        }
+    }
+}
+
+// Adds a scheme to urls that were typed without one. Absolute paths are local directories.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    if url.starts_with(LOCAL_SCHEME) {
+        return url.to_owned();
+    }
+    if url.starts_with('/') {
+        return format!("{}{}", LOCAL_SCHEME, url);
+    }
+    match url.find("http") {
+        Some(_) => url.to_owned(),
+        None => String::from("https://") + url,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//  NAME:   merge_items
+//
+//  NOTES:
+//      Concatenates lists of items, dropping any item we've already seen. Two items are the same
+//      episode if they share a guid, an enclosure url, or a title + publish date - shows that
+//      move hosts don't always keep their guids. Earlier lists win, and the order within each
+//      list is kept.
+//  ARGS:   lists - The lists to merge, most important first
+//  RETURN: The merged items
+//
+pub(crate) fn merge_items(lists: Vec<Vec<rss::Item>>) -> Vec<rss::Item> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut merged = Vec::new();
+    for item in lists.into_iter().flatten() {
+        let keys = item_keys(&item);
+        if keys.iter().any(|key| seen.contains(key)) {
+            continue;
+        }
+        seen.extend(keys);
+        merged.push(item);
+    }
+    merged
+}
+
+// The first channel, with everyone's items merged in. Namespaces are merged too, so items from
+// the other feeds can keep their podcast:/media: tags.
+pub(crate) fn merge_channels(channels: &[rss::Channel]) -> Option<rss::Channel> {
+    let mut merged = channels.first()?.clone();
+    merged.set_items(merge_items(channels.iter().map(|channel| channel.items().to_owned()).collect()));
+    let mut namespaces = merged.namespaces().clone();
+    for channel in channels.iter().skip(1) {
+        for (prefix, url) in channel.namespaces() {
+            namespaces.entry(prefix.clone()).or_insert_with(|| url.clone());
+        }
+    }
+    merged.set_namespaces(namespaces);
+    Some(merged)
+}
+
+// True if both items are the same episode, by the rules in merge_items
+pub(crate) fn same_item(a: &rss::Item, b: &rss::Item) -> bool {
+    let keys = item_keys(a);
+    item_keys(b).iter().any(|key| keys.contains(key))
+}
+
+// Everything that identifies an item. Prefixed so a guid can't collide with a url.
+fn item_keys(item: &rss::Item) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(guid) = item.guid() {
+        keys.push(format!("guid:{}", guid.value().trim()));
+    }
+    if let Some(enclosure) = item.enclosure() {
+        keys.push(format!("enclosure:{}", enclosure.url().trim()));
+    }
+    if let (Some(title), Some(date)) = (item.title(), item.pub_date()) {
+        if let Ok(date) = DateTime::parse_from_rfc2822(date) {
+            keys.push(format!("title:{}@{}", title.trim().to_lowercase(), date.timestamp()));
+        }
+    }
+    keys
+}
+
+//
+// Display implementation
+//
+impl fmt::Display for FeedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} items", self.url, self.num_items)?;
+        if let Some(last_success) = self.last_success {
+            write!(f, ", last fetched {}", last_success.to_rfc2822())?;
+        }
+        if let Some(error) = &self.last_error {
+            write!(f, ", last error: {}", error)?;
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,196 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   archive.rs
+//
+//  © Zach Nielsen 2020
+//  Serving episodes podarch has downloaded
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{ArchiveManifest, ArchivedEpisode, FeedRacer, MediaBody, MediaSource, RacerType};
+
+use tokio::io::AsyncReadExt;
+
+use std::path::{Path, PathBuf};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const AUDIO: &[u8] = b"0123456789abcdefghij";
+
+// An archive of episodes 1 and 2 of a 3 episode fixture feed, the way podarch lays it out
+fn write_archive(dir: &TestDir, rss: &str) -> PathBuf {
+    let archive_dir = PathBuf::from(dir.as_str()).join("archive");
+    std::fs::create_dir_all(&archive_dir).expect("Can make the archive dir");
+    let channel = rss::Channel::read_from(rss.as_bytes()).expect("Fixture parses");
+    let mut manifest = ArchiveManifest::new("http://127.0.0.1/feed.rss");
+    for item in channel.items() {
+        let title = item.title().expect("Fixture items have titles");
+        if title == "Episode 3" {
+            continue;
+        }
+        let file = format!("{}.mp3", title);
+        std::fs::write(archive_dir.join(&file), AUDIO).expect("Can archive an episode");
+        manifest.insert(ArchivedEpisode::new(item, &file, Some(AUDIO.len() as u64)));
+    }
+    manifest.write(&archive_dir).expect("Can write the manifest");
+    archive_dir
+}
+
+fn racer_channel(racer: &FeedRacer) -> rss::Channel {
+    let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+        .expect("Racer rss was written");
+    rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses")
+}
+
+fn enclosure_url<'a>(channel: &'a rss::Channel, guid: &str) -> &'a str {
+    channel.items().iter()
+        .find(|item| item.title() == Some(&guid.replace("episode-", "Episode ")))
+        .and_then(|item| item.enclosure())
+        .map(|enclosure| enclosure.url())
+        .expect("Episode is published with an enclosure")
+}
+
+#[test]
+fn manifests_round_trip() {
+    let dir = TestDir::new();
+    let archive_dir = write_archive(&dir, &fixture_rss(3));
+    let mut manifest = ArchiveManifest::read(&archive_dir).expect("Manifest reads");
+    assert_eq!(manifest.version, racer::ARCHIVE_MANIFEST_VERSION);
+    assert_eq!(manifest.episodes.len(), 2);
+    let episode = manifest.find("episode-1").expect("Episode 1 is archived").clone();
+    assert_eq!(episode.file, "Episode 1.mp3");
+    assert_eq!(episode.mime_type, "audio/mpeg");
+    assert_eq!(episode.original_url, "http://127.0.0.1/episode-1.mp3");
+    assert_eq!(episode.size, Some(20));
+
+    // Downloading an episode again replaces it
+    manifest.insert(ArchivedEpisode { file: String::from("again.mp3"), ..episode });
+    assert_eq!(manifest.episodes.len(), 2);
+    assert_eq!(manifest.find("episode-1").map(|episode| episode.file.as_str()), Some("again.mp3"));
+
+    // Manifests from a newer podarch aren't guessed at
+    manifest.version = racer::ARCHIVE_MANIFEST_VERSION + 1;
+    manifest.write(&archive_dir).expect("Can write the manifest");
+    assert!(ArchiveManifest::read(&archive_dir).is_err());
+}
+
+#[test]
+fn archived_episodes_are_served_locally() {
+    let dir = TestDir::new();
+    let rss = fixture_rss(3);
+    let archive_dir = write_archive(&dir, &rss);
+    let root = PathBuf::from(dir.as_str());
+    let url = serve(rss, "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        racer.jump_to_episode(3).await.expect("Episode exists");
+        racer.set_archive_dir(Some(archive_dir.to_str().unwrap()), Some(&root)).await.expect("Can use the archive");
+        assert!(!racer.get_proxy_enclosures());
+
+        let reloaded = reload(&dir, &racer);
+        let canonical = std::fs::canonicalize(&archive_dir).unwrap();
+        assert_eq!(reloaded.get_archive_dir(), Some(canonical.as_path()));
+
+        // Archived episodes point at the media route, even without proxying. The rest are untouched.
+        let channel = racer_channel(&racer);
+        assert_eq!(enclosure_url(&channel, "episode-1"), racer.get_media_url("episode-1"));
+        assert_eq!(enclosure_url(&channel, "episode-2"), racer.get_media_url("episode-2"));
+        assert_eq!(enclosure_url(&channel, "episode-3"), "http://127.0.0.1/episode-3.mp3");
+        assert_eq!(racer.get_media_source("episode-3"), None);
+        assert_eq!(racer.get_media_source("episode-1"), Some(MediaSource::Local {
+            path: canonical.join("Episode 1.mp3"),
+            mime_type: String::from("audio/mpeg"),
+        }));
+
+        let client = reqwest::Client::new();
+        let media = racer.open_media("episode-2", Some("bytes=10-14"), &client).await
+            .expect("Opens").expect("Is served");
+        assert_eq!(media.status, 206);
+        assert_eq!(media.mime_type, "audio/mpeg");
+        assert_eq!(media.content_range.as_deref(), Some("bytes 10-14/20"));
+        let mut body = Vec::new();
+        match media.body {
+            MediaBody::File(mut file) => { file.read_to_end(&mut body).await.expect("Can read the file"); },
+            _ => panic!("Archived episodes are read from disk"),
+        }
+        assert_eq!(body, b"abcde");
+
+        // Dropping the archive puts the upstream links back
+        racer.set_archive_dir(None, Some(&root)).await.expect("Can drop the archive");
+        assert_eq!(racer.get_archive_dir(), None);
+        assert_eq!(racer.get_media_source("episode-1"), None);
+        let channel = racer_channel(&racer);
+        assert_eq!(enclosure_url(&channel, "episode-1"), "http://127.0.0.1/episode-1.mp3");
+    });
+}
+
+#[test]
+fn bad_archives_are_rejected() {
+    let dir = TestDir::new();
+    let empty_dir = PathBuf::from(dir.as_str()).join("empty");
+    std::fs::create_dir_all(&empty_dir).unwrap();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        let root = Path::new(dir.as_str());
+        assert!(racer.set_archive_dir(Some("/not/a/real/dir"), Some(root)).await.is_err());
+        assert!(racer.set_archive_dir(Some(empty_dir.to_str().unwrap()), Some(root)).await.is_err());
+        assert_eq!(racer.get_archive_dir(), None);
+    });
+}
+
+#[test]
+fn archives_must_be_in_the_media_root() {
+    let dir = TestDir::new();
+    let rss = fixture_rss(3);
+    let archive_dir = write_archive(&dir, &rss);
+    let media_root = PathBuf::from(dir.as_str()).join("media");
+    std::fs::create_dir_all(&media_root).unwrap();
+    let url = serve(rss, "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        let archive = archive_dir.to_str().unwrap();
+        // Without a root, no server path is allowed. With one, only paths under it are.
+        assert!(racer.set_archive_dir(Some(archive), None).await.is_err());
+        assert!(racer.set_archive_dir(Some(archive), Some(&media_root)).await.is_err());
+        let escape = media_root.join("..").join("archive");
+        assert!(racer.set_archive_dir(Some(escape.to_str().unwrap()), Some(&media_root)).await.is_err());
+        assert_eq!(racer.get_archive_dir(), None);
+    });
+}
+
+#[test]
+fn files_outside_the_archive_are_ignored() {
+    let dir = TestDir::new();
+    let rss = fixture_rss(3);
+    let archive_dir = write_archive(&dir, &rss);
+    // A file next to the archive, which the manifest tries to reach
+    std::fs::write(PathBuf::from(dir.as_str()).join("secret.mp3"), AUDIO).unwrap();
+    let mut manifest = ArchiveManifest::read(&archive_dir).unwrap();
+    for episode in &mut manifest.episodes {
+        if episode.guid == "episode-1" {
+            episode.file = String::from("../secret.mp3");
+        }
+    }
+    manifest.write(&archive_dir).unwrap();
+    let root = PathBuf::from(dir.as_str());
+    let url = serve(rss, "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        racer.jump_to_episode(3).await.expect("Episode exists");
+        racer.set_archive_dir(Some(archive_dir.to_str().unwrap()), Some(&root)).await.expect("Can use the archive");
+
+        assert_eq!(racer.get_media_source("episode-1"), None);
+        assert!(racer.get_media_source("episode-2").is_some());
+        let channel = racer_channel(&racer);
+        assert_eq!(enclosure_url(&channel, "episode-1"), "http://127.0.0.1/episode-1.mp3");
+    });
+}
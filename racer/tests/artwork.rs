@@ -0,0 +1,113 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   artwork.rs
+//
+//  © Zach Nielsen 2020
+//  Badged artwork in racer.png
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{ArtworkBadge, ChannelDecoration, FeedRacer, RacerType};
+
+use std::io::Cursor;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// A plain red square
+fn red_png(size: u32) -> Vec<u8> {
+    let artwork = image::RgbaImage::from_pixel(size, size, image::Rgba([200, 0, 0, 255]));
+    let mut png = Vec::new();
+    artwork.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).expect("Can write png");
+    png
+}
+
+fn fixture_with_artwork(artwork_url: &str) -> String {
+    with_item_extras(fixture_rss(3), &[]).replace(
+        "<description>A podcast for testing</description>",
+        &format!("<description>A podcast for testing</description><itunes:image href=\"{}\"/>", artwork_url),
+    )
+}
+
+fn racer_channel(racer: &FeedRacer) -> rss::Channel {
+    let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+        .expect("Racer rss was written");
+    rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses")
+}
+
+fn read_artwork(racer: &FeedRacer) -> image::RgbaImage {
+    image::open(racer.get_racer_path().join(racer::RACER_ARTWORK_FILE)).expect("Artwork was written").to_rgba8()
+}
+
+#[test]
+fn badge_is_drawn_and_linked() {
+    let dir = TestDir::new();
+    let artwork_url = serve_bytes(red_png(400), "image/png");
+    let url = serve(fixture_with_artwork(&artwork_url), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(2.0), 1).await;
+        // No badge by default
+        assert!(!racer.get_racer_path().join(racer::RACER_ARTWORK_FILE).exists());
+        assert_eq!(racer_channel(&racer).itunes_ext().and_then(|itunes| itunes.image()), Some(artwork_url.as_str()));
+
+        racer.set_decoration(ChannelDecoration { badge: ArtworkBadge::Rate, ..Default::default() }).await
+            .expect("Can add a badge");
+        let channel = racer_channel(&racer);
+        assert_eq!(racer.get_artwork_url(), "http://localhost:41968/podcasts/".to_owned()
+            + racer.get_racer_name().to_str().unwrap() + "/racer.png");
+        assert_eq!(channel.itunes_ext().and_then(|itunes| itunes.image()), Some(racer.get_artwork_url().as_str()));
+        assert_eq!(channel.image().map(|image| image.url()), Some(racer.get_artwork_url().as_str()));
+
+        // Same size, untouched above the band, darkened with white text in it
+        let artwork = read_artwork(&racer);
+        assert_eq!(artwork.dimensions(), (400, 400));
+        assert_eq!(artwork.get_pixel(200, 100), &image::Rgba([200, 0, 0, 255]));
+        let band: Vec<_> = (320..400).flat_map(|y| (0..400).map(move |x| (x, y))).map(|(x, y)| *artwork.get_pixel(x, y)).collect();
+        assert!(band.contains(&image::Rgba([80, 0, 0, 255])));
+        assert!(band.contains(&image::Rgba([255, 255, 255, 255])));
+
+        // Turning the badge off goes back to the original artwork
+        racer.set_decoration(ChannelDecoration::default()).await.expect("Can remove the badge");
+        assert_eq!(racer_channel(&racer).itunes_ext().and_then(|itunes| itunes.image()), Some(artwork_url.as_str()));
+    });
+}
+
+#[test]
+fn badge_follows_rate_changes() {
+    let dir = TestDir::new();
+    let artwork_url = serve_bytes(red_png(300), "image/png");
+    let url = serve(fixture_with_artwork(&artwork_url), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(2.0), 1).await;
+        racer.set_decoration(ChannelDecoration { badge: ArtworkBadge::Rate, ..Default::default() }).await
+            .expect("Can add a badge");
+        let two_times = read_artwork(&racer);
+        racer.set_rate_ratio(1.5).await.expect("Can change the rate");
+        assert_ne!(read_artwork(&racer), two_times);
+        assert!(format!("{}", reload(&dir, &racer)).contains("badge: rate badge"));
+    });
+}
+
+#[test]
+fn bad_artwork_keeps_the_original() {
+    let dir = TestDir::new();
+    let artwork_url = serve(String::from("not an image"), "image/png");
+    let url = serve(fixture_with_artwork(&artwork_url), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        racer.set_decoration(ChannelDecoration { badge: ArtworkBadge::PodRacer, ..Default::default() }).await
+            .expect("A broken image doesn't fail the update");
+        assert_eq!(racer_channel(&racer).itunes_ext().and_then(|itunes| itunes.image()), Some(artwork_url.as_str()));
+        assert!(!racer.get_racer_path().join(racer::RACER_ARTWORK_FILE).exists());
+
+        let empty_text = ChannelDecoration { badge: ArtworkBadge::Text(String::from(" ")), ..Default::default() };
+        assert!(racer.set_decoration(empty_text).await.is_err());
+    });
+}
@@ -0,0 +1,116 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   catch_up.rs
+//
+//  © Zach Nielsen 2020
+//  What happens once a racer catches up to the live feed
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{CatchUpPolicy, FeedRacer, RacerType, RssFile};
+
+use std::sync::{Arc, Mutex};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// Adds a brand new episode to the top of a fixture feed
+fn with_new_episode(rss: &str, num: usize, pub_date: chrono::DateTime<chrono::Utc>) -> String {
+    rss.replacen(
+        "<item>",
+        &format!("<item><title>Episode {num}</title><guid>episode-{num}</guid>\
+                  <pubDate>{date}</pubDate></item>\n<item>",
+                 num = num, date = pub_date.to_rfc2822()),
+        1,
+    )
+}
+
+// A racer with everything published, and a handle to change what upstream serves
+async fn caught_up_racer(dir: &TestDir, policy: CatchUpPolicy) -> (FeedRacer, Arc<Mutex<String>>) {
+    let body = Arc::new(Mutex::new(fixture_rss(10)));
+    let url = serve_shared(body.clone(), "application/rss+xml");
+    let mut params = creation_params(dir, &url, RacerType::Ratio(1.0), 1);
+    params.catch_up_policy = policy;
+    let mut racer = create_racer_with_params(dir, params).await;
+    racer.jump_to_episode(10).await.expect("Episode exists");
+    assert_eq!(racer.get_caught_up().map(|caught_up| caught_up.num_published), Some(10));
+    (racer, body)
+}
+
+async fn new_episode_comes_out(racer: &mut FeedRacer, body: &Arc<Mutex<String>>, pub_date: chrono::DateTime<chrono::Utc>) {
+    let rss = with_new_episode(&fixture_rss(10), 11, pub_date);
+    *body.lock().unwrap() = rss;
+    racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+}
+
+#[test]
+fn caught_up_state_follows_the_feed() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(5), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        assert!(racer.get_caught_up().is_none());
+
+        racer.jump_to_episode(5).await.expect("Episode exists");
+        assert!(racer.get_caught_up().is_some());
+        assert!(reload(&dir, &racer).get_caught_up().is_some());
+
+        racer.rewind_by_episodes(2).await;
+        assert_eq!(racer.get_num_to_publish(), 3);
+        assert!(racer.get_caught_up().is_none());
+    });
+}
+
+#[test]
+fn live_publishes_with_upstream() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let (mut racer, body) = caught_up_racer(&dir, CatchUpPolicy::Live).await;
+        new_episode_comes_out(&mut racer, &body, chrono::Utc::now() - chrono::Duration::hours(1)).await;
+        assert_eq!(racer.get_num_episodes(), 11);
+        assert_eq!(racer.get_num_to_publish(), 11);
+        assert_eq!(racer.get_current_ep_title().unwrap(), "Episode 11");
+    });
+}
+
+#[test]
+fn delay_stays_behind_live() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let (mut racer, body) = caught_up_racer(&dir, CatchUpPolicy::Delay(2)).await;
+        let pub_date = chrono::Utc::now() - chrono::Duration::hours(1);
+        new_episode_comes_out(&mut racer, &body, pub_date).await;
+        assert_eq!(racer.get_num_episodes(), 11);
+        assert_eq!(racer.get_num_to_publish(), 10);
+        let expected = pub_date + chrono::Duration::days(2);
+        let next = racer.get_next_episode_pub_date();
+        assert!((next - expected).num_seconds().abs() <= 1, "{} vs {}", next, expected);
+    });
+}
+
+#[test]
+fn stop_holds_new_episodes_back() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let (mut racer, body) = caught_up_racer(&dir, CatchUpPolicy::Stop).await;
+        new_episode_comes_out(&mut racer, &body, chrono::Utc::now() - chrono::Duration::hours(1)).await;
+        assert_eq!(racer.get_num_episodes(), 10);
+        assert_eq!(racer.get_num_to_publish(), 10);
+        let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+            .expect("Racer rss was written");
+        assert!(!racer_rss.contains("Episode 11"));
+        assert!(racer_rss.contains("stopped publishing new episodes"));
+
+        // Going live lets it out
+        racer.set_catch_up_policy(CatchUpPolicy::Live).await.expect("Can change policy");
+        assert_eq!(racer.get_num_to_publish(), 11);
+    });
+}
@@ -0,0 +1,222 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   common/mod.rs
+//
+//  © Zach Nielsen 2020
+//  Shared helpers for the racer integration tests
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+#![allow(dead_code)]
+
+use racer::{FeedRacer, RacerCreationParams, RacerType};
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// A scratch podracer dir that cleans up after itself
+pub struct TestDir {
+    pub path: PathBuf,
+}
+impl TestDir {
+    pub fn new() -> TestDir {
+        let path = std::env::temp_dir().join(format!("podracer-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).expect("Creating test dir");
+        TestDir { path }
+    }
+    pub fn as_str(&self) -> &str {
+        self.path.to_str().expect("Test dir is a str")
+    }
+}
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.path).unwrap_or(());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   serve
+//
+//  NOTES:
+//      Serves `body` to every request on a local port, forever. Good enough to stand in for
+//      a podcast host.
+//  ARGS:
+//      body - What to respond with
+//      content_type - The Content-Type header to send
+//  RETURN: The url to fetch from
+//
+pub fn serve(body: String, content_type: &'static str) -> String {
+    serve_shared(Arc::new(Mutex::new(body)), content_type)
+}
+
+// Like serve, but the body can be swapped out later to stand in for an upstream feed changing
+pub fn serve_shared(body: Arc<Mutex<String>>, content_type: &'static str) -> String {
+    serve_with(move |_| full_response(body.lock().expect("Body lock").clone().into_bytes(), content_type))
+}
+
+// Like serve, for bodies that aren't text (artwork, audio)
+pub fn serve_bytes(body: Vec<u8>, content_type: &'static str) -> String {
+    serve_with(move |_| full_response(body.clone(), content_type))
+}
+
+// Like serve_bytes, but answers "Range: bytes=<start>-<end>" requests with just those bytes, like a
+// media host would
+pub fn serve_with_ranges(body: Vec<u8>, content_type: &'static str) -> String {
+    serve_with(move |request| {
+        let range = request.lines()
+            .find_map(|line| line.to_lowercase().strip_prefix("range: bytes=").map(str::to_owned))
+            .and_then(|range| {
+                let (start, end) = range.trim().split_once('-')?;
+                let start: usize = start.parse().ok()?;
+                let end: usize = end.parse().unwrap_or(body.len() - 1);
+                Some((start, std::cmp::min(end, body.len() - 1)))
+            });
+        match range {
+            Some((start, end)) => {
+                let header = format!(
+                    "HTTP/1.1 206 Partial Content\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+                     Content-Range: bytes {}-{}/{}\r\nAccept-Ranges: bytes\r\nConnection: close\r\n\r\n",
+                    content_type, end - start + 1, start, end, body.len()
+                );
+                [header.as_bytes(), &body[start..=end]].concat()
+            },
+            None => full_response(body.clone(), content_type),
+        }
+    })
+}
+
+pub fn full_response(body: Vec<u8>, content_type: &str) -> Vec<u8> {
+    let header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        content_type,
+        body.len()
+    );
+    [header.into_bytes(), body].concat()
+}
+
+// Answers every request with whatever `respond` makes of the request headers
+pub fn serve_with<F>(respond: F) -> String
+where
+    F: Fn(&str) -> Vec<u8> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Can bind a local port");
+    let port = listener.local_addr().expect("Listener has an address").port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(val) => val,
+                Err(_) => continue,
+            };
+            // Read until the end of the headers, there's never a request body
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let response = respond(&String::from_utf8_lossy(&request));
+            stream.write_all(&response).unwrap_or(());
+        }
+    });
+    format!("http://127.0.0.1:{}/feed.rss", port)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   fixture_rss
+//
+//  NOTES:
+//      Builds an rss feed with `num_items` weekly episodes, newest first like most real feeds.
+//      The newest episode came out 30 days ago.
+//  ARGS:   num_items - How many episodes to put in the feed
+//  RETURN: The feed as a string
+//
+pub fn fixture_rss(num_items: usize) -> String {
+    let newest = chrono::Utc::now() - chrono::Duration::days(30);
+    let mut items = String::new();
+    for idx in (0..num_items).rev() {
+        let pub_date = newest - chrono::Duration::weeks((num_items - 1 - idx) as i64);
+        items.push_str(&format!(
+            "<item><title>Episode {num}</title><guid>episode-{num}</guid>\
+             <description>Episode {num} description</description>\
+             <enclosure url=\"http://127.0.0.1/episode-{num}.mp3\" length=\"1000\" type=\"audio/mpeg\"/>\
+             <pubDate>{date}</pubDate></item>\n",
+            num = idx + 1,
+            date = pub_date.to_rfc2822(),
+        ));
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\"><channel><title>Test Podcast</title>\
+         <link>http://127.0.0.1/</link><description>A podcast for testing</description>\n{}\
+         </channel></rss>\n",
+        items
+    )
+}
+
+// Adds extra xml (itunes tags, etc) to the given episodes of a fixture feed
+pub fn with_item_extras(rss: String, extras: &[(usize, &str)]) -> String {
+    let mut rss = rss.replace(
+        "<rss version=\"2.0\">",
+        "<rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">",
+    );
+    for (num, extra) in extras {
+        let guid = format!("<guid>episode-{}</guid>", num);
+        rss = rss.replace(&guid, &format!("{}{}", guid, extra));
+    }
+    rss
+}
+
+// Creation params for the feed at `url`, living in `dir`
+pub fn creation_params(dir: &TestDir, url: &str, rate: RacerType, start_ep: usize) -> RacerCreationParams {
+    RacerCreationParams {
+        static_file_dir: String::new(),
+        podracer_dir: dir.as_str().to_owned(),
+        host: String::from("http://localhost:41968"),
+        url: url.to_owned(),
+        extra_urls: Vec::new(),
+        start_ep,
+        port: 41968,
+        rate,
+        timezone: racer::DEFAULT_TIMEZONE.to_owned(),
+        publish_time: None,
+        seasons: None,
+        start_season: None,
+        order: racer::EpisodeOrder::default(),
+        playlist: racer::Playlist::default(),
+        catch_up_policy: racer::CatchUpPolicy::default(),
+        local_media_root: None,
+    }
+}
+
+// Creates a racer in `dir` for the feed at `url`, then loads it back from disk.
+pub async fn create_racer(dir: &TestDir, url: &str, rate: RacerType, start_ep: usize) -> FeedRacer {
+    create_racer_with_params(dir, creation_params(dir, url, rate, start_ep)).await
+}
+pub async fn create_racer_with_params(dir: &TestDir, mut params: RacerCreationParams) -> FeedRacer {
+    let racer = racer::create_feed(&mut params, &reqwest::Client::new())
+        .await
+        .expect("Can create racer");
+    reload(dir, &racer)
+}
+
+// Loads a fresh copy of the racer from disk
+pub fn reload(dir: &TestDir, racer: &FeedRacer) -> FeedRacer {
+    let dir_name = racer.get_racer_name().to_str().expect("Racer name is a str").to_owned();
+    racer::get_by_dir_name(dir.as_str(), &dir_name).expect("Racer is on disk")
+}
+
+pub fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Can build a runtime")
+}
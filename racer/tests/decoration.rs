@@ -0,0 +1,108 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   decoration.rs
+//
+//  © Zach Nielsen 2020
+//  The racer feed's title, status line and artwork
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{ChannelDecoration, FeedRacer, FeedStatus, RacerType, TitleStyle};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+fn racer_channel(racer: &FeedRacer) -> rss::Channel {
+    let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+        .expect("Racer rss was written");
+    rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses")
+}
+
+fn fixture_with_image() -> String {
+    fixture_rss(3).replace(
+        "<description>A podcast for testing</description>",
+        "<description>A podcast for testing</description>\
+         <image><url>http://127.0.0.1/cover.png</url><title>Test Podcast</title><link>http://127.0.0.1/</link></image>",
+    )
+}
+
+#[test]
+fn default_decoration_is_unchanged() {
+    let dir = TestDir::new();
+    let url = serve(fixture_with_image(), "application/rss+xml");
+    runtime().block_on(async {
+        let racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        assert_eq!(racer.get_decoration(), &ChannelDecoration::default());
+        let channel = racer_channel(&racer);
+        assert_eq!(channel.title(), "Test Podcast - PodRacer");
+        assert_eq!(channel.image().expect("Image kept").title(), "Test Podcast - PodRacer");
+        assert!(matches!(racer.get_feed_status(), Ok(FeedStatus::NextEpisode(_))));
+        assert!(channel.description().starts_with("A podcast for testing -- Next episode publishes "));
+        assert!(channel.description().ends_with(&format!("-- PodRacer UUID: {}", racer.get_uuid())));
+    });
+}
+
+#[test]
+fn custom_title_and_artwork() {
+    let dir = TestDir::new();
+    let url = serve(fixture_with_image(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        racer.set_decoration(ChannelDecoration {
+            title: TitleStyle::Custom(String::from(" My Rewatch ")),
+            next_episode_line: false,
+            artwork: Some(String::from(" https://example.com/art.jpg ")),
+            ..Default::default()
+        }).await.expect("Can set a custom title");
+
+        let channel = racer_channel(&racer);
+        assert_eq!(channel.title(), "My Rewatch");
+        assert_eq!(channel.description(), "A podcast for testing");
+        let image = channel.image().expect("Image kept");
+        assert_eq!(image.url(), "https://example.com/art.jpg");
+        assert_eq!(image.title(), "My Rewatch");
+        assert_eq!(channel.itunes_ext().and_then(|itunes| itunes.image()), Some("https://example.com/art.jpg"));
+        assert_eq!(reload(&dir, &racer).get_decoration(), racer.get_decoration());
+
+        racer.set_decoration(ChannelDecoration {
+            title: TitleStyle::Original,
+            next_episode_line: true,
+            artwork: Some(String::from("  ")),
+            ..Default::default()
+        }).await.expect("Can go back to the original title");
+        let channel = racer_channel(&racer);
+        assert_eq!(channel.title(), "Test Podcast");
+        assert_eq!(channel.image().expect("Image kept").url(), "http://127.0.0.1/cover.png");
+        assert_eq!(racer.get_decoration().artwork, None);
+    });
+}
+
+#[test]
+fn bad_decoration_is_refused() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        let empty_title = ChannelDecoration { title: TitleStyle::Custom(String::from(" ")), ..Default::default() };
+        assert!(racer.set_decoration(empty_title).await.is_err());
+        let bad_artwork = ChannelDecoration { artwork: Some(String::from("file:///etc/passwd")), ..Default::default() };
+        assert!(racer.set_decoration(bad_artwork).await.is_err());
+        assert_eq!(racer.get_decoration(), &ChannelDecoration::default());
+
+        // Artwork without an image to replace still gets one
+        racer.set_decoration(ChannelDecoration {
+            artwork: Some(String::from("https://example.com/art.jpg")),
+            ..Default::default()
+        }).await.expect("Can set artwork");
+        let channel = racer_channel(&racer);
+        assert_eq!(channel.image().expect("Image added").url(), "https://example.com/art.jpg");
+        assert_eq!(channel.image().expect("Image added").title(), "Test Podcast - PodRacer");
+    });
+}
@@ -0,0 +1,97 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   delay.rs
+//
+//  © Zach Nielsen 2020
+//  Mirroring a feed a fixed number of days late
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{RacerType, RssFile};
+
+use std::sync::{Arc, Mutex};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// The fixture's newest episode came out 30 days ago, and the one before it 37 days ago
+const DELAY_DAYS: u32 = 35;
+
+fn newest_fixture_date() -> chrono::DateTime<chrono::Utc> {
+    let rss = rss::Channel::read_from(fixture_rss(10).as_bytes()).expect("Fixture parses");
+    let newest = rss.items()[0].pub_date().expect("Fixture has dates");
+    chrono::DateTime::parse_from_rfc2822(newest).unwrap().with_timezone(&chrono::Utc)
+}
+
+fn assert_close(actual: chrono::DateTime<chrono::Utc>, expected: chrono::DateTime<chrono::Utc>) {
+    assert!((actual - expected).num_seconds().abs() <= 1, "{} vs {}", actual, expected);
+}
+
+#[test]
+fn episodes_go_out_a_fixed_time_after_upstream() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(10), "application/rss+xml");
+    runtime().block_on(async {
+        // Start episode is ignored, the original dates decide
+        let mut racer = create_racer(&dir, &url, RacerType::Delay(DELAY_DAYS), 1).await;
+        assert_eq!(racer.get_num_episodes(), 10);
+        assert_eq!(racer.get_num_to_publish(), 9);
+        assert_close(racer.get_next_episode_pub_date(), newest_fixture_date() + chrono::Duration::days(DELAY_DAYS as i64));
+
+        // Mirrors can't be moved
+        racer.jump_to_episode(10).await.expect("Episode exists");
+        assert_eq!(racer.get_num_to_publish(), 9);
+        assert!(racer.get_caught_up().is_none());
+        assert_eq!(reload(&dir, &racer).get_num_to_publish(), 9);
+    });
+}
+
+#[test]
+fn new_episodes_keep_the_delay() {
+    let dir = TestDir::new();
+    let body = Arc::new(Mutex::new(fixture_rss(10)));
+    let url = serve_shared(body.clone(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Delay(1), 1).await;
+        assert_eq!(racer.get_num_to_publish(), 10);
+
+        let pub_date = chrono::Utc::now() - chrono::Duration::hours(1);
+        let rss = fixture_rss(10).replacen(
+            "<item>",
+            &format!("<item><title>Episode 11</title><guid>episode-11</guid>\
+                      <pubDate>{}</pubDate></item>\n<item>", pub_date.to_rfc2822()),
+            1,
+        );
+        *body.lock().unwrap() = rss;
+        racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        assert_eq!(racer.get_num_episodes(), 11);
+        assert_eq!(racer.get_num_to_publish(), 10);
+        assert_close(racer.get_next_episode_pub_date(), pub_date + chrono::Duration::days(1));
+    });
+}
+
+#[test]
+fn switching_to_a_delay_and_back() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(10), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 3).await;
+        let num_published = racer.get_num_to_publish();
+
+        racer.set_rate_delay(DELAY_DAYS).await.expect("Can set a delay");
+        assert_eq!(racer.get_num_to_publish(), 9);
+        assert!(racer.set_rate_delay(0).await.is_err());
+
+        racer.set_rate_ratio(1.0).await.expect("Can set a rate");
+        assert_eq!(racer.get_num_to_publish(), 9);
+        assert!(num_published < 9);
+    });
+}
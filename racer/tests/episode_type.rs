@@ -0,0 +1,102 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   episode_type.rs
+//
+//  © Zach Nielsen 2020
+//  Trailers and bonus episodes, per itunes:episodeType
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{EpisodeType, EpisodeTypePolicy, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const NUM_ITEMS: usize = 10;
+
+// Episode 1 is a trailer, 4 and 5 are bonus episodes
+fn fixture() -> String {
+    with_item_extras(fixture_rss(NUM_ITEMS), &[
+        (1, "<itunes:episodeType>trailer</itunes:episodeType>"),
+        (2, "<itunes:episodeType>full</itunes:episodeType><itunes:season>1</itunes:season><itunes:episode>1</itunes:episode>"),
+        (3, "<itunes:season>1</itunes:season><itunes:episode>2</itunes:episode>"),
+        (4, "<itunes:episodeType>bonus</itunes:episodeType>"),
+        (5, "<itunes:episodeType>Bonus</itunes:episodeType>"),
+    ])
+}
+
+#[test]
+fn episode_info_is_read_at_ingest() {
+    let dir = TestDir::new();
+    let url = serve(fixture(), "application/rss+xml");
+    runtime().block_on(async {
+        let racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        assert_eq!(racer.get_episode_info(0).unwrap().episode_type, EpisodeType::Trailer);
+        let info = racer.get_episode_info(2).unwrap();
+        assert_eq!(info.episode_type, EpisodeType::Full);
+        assert_eq!(info.season, Some(1));
+        assert_eq!(info.episode, Some(2));
+        assert_eq!(racer.get_episode_info(4).unwrap().episode_type, EpisodeType::Bonus);
+    });
+}
+
+#[test]
+fn schedule_everything_by_default() {
+    let dir = TestDir::new();
+    let url = serve(fixture(), "application/rss+xml");
+    runtime().block_on(async {
+        let racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        assert_eq!(racer.get_num_episodes(), NUM_ITEMS);
+        assert!(racer.get_episode_pub_date(3) < racer.get_episode_pub_date(4));
+    });
+}
+
+#[test]
+fn trailers_are_skipped() {
+    let dir = TestDir::new();
+    let url = serve(fixture(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        racer.set_episode_type_policy(EpisodeTypePolicy {
+            skip_trailers: true,
+            bonus_with_neighbour: false,
+        }).await.expect("Can set policy");
+        assert_eq!(racer.get_num_episodes(), NUM_ITEMS - 1);
+        assert_eq!(racer.get_excluded_titles(), vec!["Episode 1"]);
+
+        let rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE)).unwrap();
+        assert!(!rss.contains("episode-1<"));
+    });
+}
+
+#[test]
+fn bonus_episodes_ride_along() {
+    let dir = TestDir::new();
+    let url = serve(fixture(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        racer.rewind_by_episodes(NUM_ITEMS).await;
+        racer.set_episode_type_policy(EpisodeTypePolicy {
+            skip_trailers: false,
+            bonus_with_neighbour: true,
+        }).await.expect("Can set policy");
+
+        // Both bonus episodes go out with episode 3, and episode 6 takes the next slot
+        let third = racer.get_episode_pub_date(2);
+        assert_eq!(racer.get_episode_pub_date(3), third);
+        assert_eq!(racer.get_episode_pub_date(4), third);
+        assert_eq!(racer.get_episode_pub_date(5), third + chrono::Duration::days(7));
+
+        // Publishing episode 3 publishes the bonus episodes too
+        racer.fastforward_by_episodes(3).await;
+        assert_eq!(racer.get_num_to_publish(), 5);
+        assert_eq!(racer.get_next_ep_title(), Some(String::from("Episode 6")));
+    });
+}
@@ -0,0 +1,111 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   exclude.rs
+//
+//  © Zach Nielsen 2020
+//  Excluded episodes are skipped without leaving a hole in the schedule
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{ExcludeRules, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const NUM_ITEMS: usize = 12;
+
+#[test]
+fn next_episode_takes_the_excluded_slot() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        let third_slot = racer.get_episode_pub_date(2);
+
+        racer.set_exclude_rules(ExcludeRules {
+            guids: vec![String::from("episode-3")],
+            title_patterns: vec![String::from("^Episode 5$")],
+            min_duration_secs: None,
+        }).await.expect("Rules are valid");
+
+        assert_eq!(racer.get_num_episodes(), NUM_ITEMS - 2);
+        // Episode 4 moves up into episode 3's old release date
+        assert_eq!(racer.get_episode_pub_date(2), third_slot);
+        assert_eq!(racer.get_excluded_titles(), vec!["Episode 3", "Episode 5"]);
+
+        // Excluded episodes don't show up in the feed at all
+        let racer = reload(&dir, &racer);
+        racer.get_current_ep_title().expect("Something is published");
+        let rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE)).unwrap();
+        assert!(!rss.contains("episode-3"));
+    });
+}
+
+#[test]
+fn excluding_keeps_the_latest_published_episode() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        racer.jump_to_episode(6).await.expect("Episode 6 exists");
+
+        let mut rules = ExcludeRules::default();
+        rules.guids.push(String::from("episode-3"));
+        racer.set_exclude_rules(rules.clone()).await.expect("Rules are valid");
+        assert_eq!(racer.get_num_to_publish(), 5);
+        assert_eq!(racer.get_current_ep_title(), Some(String::from("Episode 6")));
+
+        // Excluding the latest episode falls back to the one before it
+        rules.guids.push(String::from("episode-6"));
+        racer.set_exclude_rules(rules).await.expect("Rules are valid");
+        assert_eq!(racer.get_current_ep_title(), Some(String::from("Episode 5")));
+        assert_eq!(racer.get_next_ep_title(), Some(String::from("Episode 7")));
+
+        // And clearing the rules brings them back without publishing anything new
+        racer.set_exclude_rules(ExcludeRules::default()).await.expect("Rules are valid");
+        assert_eq!(racer.get_num_to_publish(), 5);
+        assert_eq!(racer.get_next_ep_title(), Some(String::from("Episode 6")));
+    });
+}
+
+#[test]
+fn short_episodes_are_excluded() {
+    let dir = TestDir::new();
+    let rss = with_item_extras(fixture_rss(NUM_ITEMS), &[
+        (2, "<itunes:duration>0:45</itunes:duration>"),
+        (4, "<itunes:duration>1:02:03</itunes:duration>"),
+        (7, "<itunes:duration>59</itunes:duration>"),
+    ]);
+    let url = serve(rss, "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        racer.set_exclude_rules(ExcludeRules {
+            min_duration_secs: Some(60),
+            ..ExcludeRules::default()
+        }).await.expect("Rules are valid");
+        assert_eq!(racer.get_excluded_titles(), vec!["Episode 2", "Episode 7"]);
+        assert_eq!(racer.get_num_episodes(), NUM_ITEMS - 2);
+    });
+}
+
+#[test]
+fn invalid_title_pattern_is_rejected() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        let res = racer.set_exclude_rules(ExcludeRules {
+            title_patterns: vec![String::from("(unclosed")],
+            ..ExcludeRules::default()
+        }).await;
+        assert!(res.is_err());
+        assert!(racer.get_exclude_rules().is_empty());
+    });
+}
@@ -0,0 +1,90 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   footer.rs
+//
+//  © Zach Nielsen 2020
+//  The footer on each published episode
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedFooter, FeedRacer, FooterSettings, RacerType, RssFile};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// The description of the first episode in racer.rss
+fn first_description(racer: &FeedRacer) -> String {
+    let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+        .expect("Racer rss was written");
+    let channel = rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses");
+    channel.items()[0].description().expect("Item has a description").to_owned()
+}
+
+#[test]
+fn default_footer_links_to_this_server() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    runtime().block_on(async {
+        let racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        let description = first_description(&racer);
+        assert!(description.starts_with("Episode 1 description\n\n<hr>"));
+        assert!(description.contains("Originally published on"));
+        assert!(description.contains(&format!("http://localhost:41968/edit_feed/{}", racer.get_uuid_string())));
+        assert!(!description.contains("zachn.me"));
+    });
+}
+
+#[test]
+fn server_settings_set_the_host_and_template() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        let settings = FooterSettings {
+            host: String::from("https://racer.example.com"),
+            template: Some(String::from("Heard via <a href=\"{{ edit_url }}\">{{ host }}</a> ({{ episode_title }})")),
+        };
+        racer::write_footer_settings(dir.as_str(), &settings).expect("Can write settings");
+        racer.update(&RssFile::FromStorage, &reqwest::Client::new()).await.expect("Updates");
+        assert_eq!(
+            first_description(&racer),
+            format!("Episode 1 description\n\nHeard via <a href=\"https://racer.example.com/edit_feed/{}\">\
+                     https://racer.example.com</a> (Episode 1)", racer.get_uuid_string())
+        );
+
+        // The settings file doesn't get in the way of finding racers
+        assert_eq!(racer::get_all_racers(dir.as_str()).expect("Can list racers").len(), 1);
+
+        // Bad server templates are refused
+        let bad = FooterSettings { host: settings.host.clone(), template: Some(String::from("{{ oops")) };
+        assert!(racer::write_footer_settings(dir.as_str(), &bad).is_err());
+    });
+}
+
+#[test]
+fn feeds_can_override_or_disable_the_footer() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+
+        racer.set_footer(FeedFooter::Custom(String::from("{{ podcast_title }}, {{ original_date }}"))).await
+            .expect("Can set a custom footer");
+        assert!(first_description(&racer).starts_with("Episode 1 description\n\nTest Podcast, "));
+        assert_eq!(reload(&dir, &racer).get_footer(), racer.get_footer());
+
+        // Unknown variables fail when the template is set, not on every update
+        assert!(racer.set_footer(FeedFooter::Custom(String::from("{{ nope }}"))).await.is_err());
+        assert!(matches!(racer.get_footer(), FeedFooter::Custom(_)));
+
+        racer.set_footer(FeedFooter::Disabled).await.expect("Can disable the footer");
+        assert_eq!(first_description(&racer), "Episode 1 description");
+    });
+}
@@ -0,0 +1,111 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   formats.rs
+//
+//  © Zach Nielsen 2020
+//  racer.atom and racer.json, checked against what each spec requires
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedRacer, RacerType};
+
+use std::collections::HashSet;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+async fn published_racer(dir: &TestDir) -> FeedRacer {
+    let url = serve(fixture_rss(5), "application/rss+xml");
+    let mut racer = create_racer(dir, &url, RacerType::Ratio(1.0), 1).await;
+    racer.jump_to_episode(3).await.expect("Episode exists");
+    racer
+}
+
+fn read_racer_file(racer: &FeedRacer, file: &str) -> String {
+    std::fs::read_to_string(racer.get_racer_path().join(file)).expect("Racer file was written")
+}
+
+// RFC 4287 - feeds and entries need an id, title and updated date, and an author somewhere
+#[test]
+fn atom_has_required_fields() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let racer = published_racer(&dir).await;
+        let atom = atom_syndication::Feed::read_from(read_racer_file(&racer, racer::RACER_ATOM_FILE).as_bytes())
+            .expect("Atom parses");
+
+        assert_eq!(atom.id(), format!("urn:uuid:{}", racer.get_uuid_string()));
+        assert!(atom.title().as_str().starts_with("Test Podcast"));
+        assert!(!atom.authors().is_empty());
+        assert!(atom.links().iter().any(|link| link.rel() == "self" && link.href() == racer.get_atom_url()));
+
+        assert_eq!(atom.entries().len(), 3);
+        let mut ids = HashSet::new();
+        for entry in atom.entries() {
+            assert!(entry.id().contains(':'), "{} isn't an IRI", entry.id());
+            assert!(ids.insert(entry.id().to_owned()), "Duplicate id {}", entry.id());
+            assert!(!entry.title().as_str().is_empty());
+            assert!(entry.updated() <= &chrono::Utc::now());
+            assert!(entry.links().iter().any(|link| link.rel() == "enclosure" && link.mime_type() == Some("audio/mpeg")));
+        }
+        assert_eq!(atom.updated(), atom.entries().iter().map(|entry| entry.updated()).max().unwrap());
+    });
+}
+
+// JSON Feed 1.1 - version, title and items, and every item needs an id and some content
+#[test]
+fn json_feed_has_required_fields() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let racer = published_racer(&dir).await;
+        let json: serde_json::Value = serde_json::from_str(&read_racer_file(&racer, racer::RACER_JSON_FILE))
+            .expect("JSON Feed parses");
+
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert!(json["title"].as_str().expect("title is a string").starts_with("Test Podcast"));
+        assert_eq!(json["feed_url"], racer.get_json_feed_url());
+
+        let items = json["items"].as_array().expect("items is an array");
+        assert_eq!(items.len(), 3);
+        let mut ids = HashSet::new();
+        for item in items {
+            let id = item["id"].as_str().expect("id is a string");
+            assert!(ids.insert(id.to_owned()), "Duplicate id {}", id);
+            assert!(item["content_html"].is_string() || item["content_text"].is_string());
+            let date = item["date_published"].as_str().expect("Items have dates");
+            assert!(chrono::DateTime::parse_from_rfc3339(date).is_ok(), "{} isn't RFC 3339", date);
+            let attachment = &item["attachments"][0];
+            assert!(attachment["url"].is_string());
+            assert_eq!(attachment["mime_type"], "audio/mpeg");
+        }
+    });
+}
+
+#[test]
+fn formats_follow_the_published_episodes() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let mut racer = published_racer(&dir).await;
+        racer.rewind_by_episodes(2).await;
+
+        let racer_rss = rss::Channel::read_from(read_racer_file(&racer, racer::RACER_RSS_FILE).as_bytes())
+            .expect("Racer rss parses");
+        let atom = atom_syndication::Feed::read_from(read_racer_file(&racer, racer::RACER_ATOM_FILE).as_bytes())
+            .expect("Atom parses");
+        let json: serde_json::Value = serde_json::from_str(&read_racer_file(&racer, racer::RACER_JSON_FILE))
+            .expect("JSON Feed parses");
+
+        assert_eq!(racer_rss.items().len(), 1);
+        assert_eq!(atom.entries().len(), 1);
+        assert_eq!(json["items"].as_array().map(Vec::len), Some(1));
+        assert_eq!(atom.entries()[0].title().as_str(), racer_rss.items()[0].title().unwrap());
+        assert_eq!(json["items"][0]["title"], racer_rss.items()[0].title().unwrap());
+    });
+}
@@ -0,0 +1,87 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   guids.rs
+//
+//  © Zach Nielsen 2020
+//  Namespacing published GUIDs under the racer's uuid
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedRacer, RacerType, RssFile};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+// (guid, pub date) of each published item
+fn published(racer: &FeedRacer) -> Vec<(String, String)> {
+    let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+        .expect("Racer rss was written");
+    let channel = rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses");
+    channel.items().iter()
+        .map(|item| (item.guid().expect("Item has a guid").value().to_owned(), item.pub_date().unwrap_or("").to_owned()))
+        .collect()
+}
+
+#[test]
+fn guids_are_upstream_by_default() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        racer.jump_to_episode(3).await.expect("Episode exists");
+        assert!(!racer.get_namespace_guids());
+        let guids: Vec<String> = published(&racer).into_iter().map(|(guid, _)| guid).collect();
+        assert_eq!(guids, vec!["episode-1", "episode-2", "episode-3"]);
+    });
+}
+
+#[test]
+fn namespaced_guids_are_stable() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(5), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        racer.jump_to_episode(4).await.expect("Episode exists");
+        racer.set_namespace_guids(true).await.expect("Can namespace guids");
+        assert!(reload(&dir, &racer).get_namespace_guids());
+
+        let first = published(&racer);
+        let prefix = format!("urn:podracer:{}:", racer.get_uuid());
+        assert_eq!(first[0].0, format!("{}episode-1", prefix));
+        assert!(first.iter().all(|(guid, _)| guid.starts_with(&prefix)));
+        let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE)).expect("Rss written");
+        assert!(racer_rss.contains(&format!("<guid isPermaLink=\"false\">{}episode-1</guid>", prefix)));
+
+        // The same through more updates, a rewind and a rate change
+        racer.update(&RssFile::FromStorage, &reqwest::Client::new()).await.expect("Updates");
+        assert_eq!(published(&racer), first);
+        racer.rewind_by_episodes(2).await;
+        let rewound = published(&racer);
+        assert_eq!(rewound.len(), first.len() - 2);
+        for ((guid, _), (first_guid, _)) in rewound.iter().zip(&first) {
+            assert_eq!(guid, first_guid);
+        }
+        racer.set_rate_ratio(2.0).await.expect("Can change the rate");
+        let sped_up = published(&racer);
+        assert!(sped_up.iter().zip(&first).all(|((guid, _), (first_guid, _))| guid == first_guid));
+
+        // Another racer of the same show doesn't share them
+        let mut other = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        other.set_namespace_guids(true).await.expect("Can namespace guids");
+        assert_ne!(published(&other)[0].0, first[0].0);
+
+        // Atom ids follow the namespaced GUIDs rather than namespacing them again
+        let atom = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_ATOM_FILE)).expect("Atom written");
+        assert!(atom.contains(&format!("<id>{}episode-1</id>", prefix)));
+
+        racer.set_namespace_guids(false).await.expect("Can go back to upstream guids");
+        assert_eq!(published(&racer)[0].0, "episode-1");
+    });
+}
@@ -0,0 +1,119 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   history.rs
+//
+//  © Zach Nielsen 2020
+//  Feed edits can be undone
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{ExcludeRules, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const NUM_ITEMS: usize = 40;
+
+#[test]
+fn undo_walks_back_through_edits() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 5).await;
+        let start_published = racer.get_num_to_publish();
+
+        let checkpoint = racer.checkpoint("Fast-forward 20 episodes");
+        racer.fastforward_by_episodes(20).await;
+        assert!(racer.record_change(checkpoint));
+
+        let checkpoint = racer.checkpoint("Set rate to 2x");
+        racer.set_rate_ratio(2.0).await.expect("Can set ratio");
+        assert!(racer.record_change(checkpoint));
+
+        let checkpoint = racer.checkpoint("Pause");
+        racer.pause_feed().await;
+        assert!(racer.record_change(checkpoint));
+
+        // The journal is saved with the racer
+        let mut racer = reload(&dir, &racer);
+        assert_eq!(racer.get_history().len(), 3);
+
+        let undone = racer.undo_last_change().await.expect("Can undo pause");
+        assert_eq!(undone.action, "Pause");
+        assert!(!racer.is_paused());
+
+        let undone = racer.undo_last_change().await.expect("Can undo rate");
+        assert_eq!(undone.action, "Set rate to 2x");
+        assert_eq!(racer.get_rate().to_string(), "Days(7)");
+
+        racer.undo_last_change().await.expect("Can undo fast-forward");
+        assert_eq!(racer.get_num_to_publish(), start_published);
+        assert_eq!(reload(&dir, &racer).get_num_to_publish(), start_published);
+
+        assert!(racer.undo_last_change().await.is_err());
+    });
+}
+
+#[test]
+fn no_op_edits_are_not_recorded() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 5).await;
+        let checkpoint = racer.checkpoint("Rewind 0 episodes");
+        racer.rewind_by_episodes(0).await;
+        assert!(!racer.record_change(checkpoint));
+        assert!(racer.get_history().is_empty());
+    });
+}
+
+#[test]
+fn history_is_bounded() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        for days in 0..(racer::MAX_HISTORY + 5) {
+            let checkpoint = racer.checkpoint(&format!("Rewind {} days", days + 1));
+            racer.rewind_by_days(1).await;
+            assert!(racer.record_change(checkpoint));
+        }
+        let history = racer.get_history();
+        assert_eq!(history.len(), racer::MAX_HISTORY);
+        // The oldest entries are the ones dropped
+        assert_eq!(history[0].action, "Rewind 6 days");
+        assert_eq!(history.last().unwrap().action, format!("Rewind {} days", racer::MAX_HISTORY + 5));
+    });
+}
+
+#[test]
+fn settings_edits_cannot_be_undone() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        let checkpoint = racer.checkpoint("Jump to episode 6");
+        racer.jump_to_episode(6).await.expect("Episode 6 exists");
+        assert!(racer.record_change(checkpoint));
+
+        // Excluding an episode re-anchors to keep episode 6 the latest. The old anchors would
+        // put the feed somewhere else with the new rules, so they're forgotten.
+        let mut rules = ExcludeRules::default();
+        rules.guids.push(String::from("episode-3"));
+        racer.set_exclude_rules(rules).await.expect("Rules are valid");
+        let published = racer.get_num_to_publish();
+        assert!(racer.get_history().is_empty());
+
+        assert!(racer.undo_last_change().await.is_err());
+        assert_eq!(racer.get_num_to_publish(), published);
+        assert_eq!(racer.get_current_ep_title(), Some(String::from("Episode 6")));
+        assert!(reload(&dir, &racer).get_history().is_empty());
+    });
+}
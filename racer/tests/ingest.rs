@@ -0,0 +1,150 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   ingest.rs
+//
+//  © Zach Nielsen 2020
+//  Racing Atom and JSON Feed sources
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedFormat, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// Weekly episodes, newest first, the newest 30 days ago - like fixture_rss
+fn episode_dates(num_items: usize) -> Vec<(usize, chrono::DateTime<chrono::Utc>)> {
+    let newest = chrono::Utc::now() - chrono::Duration::days(30);
+    (1..=num_items).rev()
+        .map(|num| (num, newest - chrono::Duration::weeks((num_items - num) as i64)))
+        .collect()
+}
+
+fn fixture_atom(num_items: usize) -> String {
+    let entries: String = episode_dates(num_items).iter()
+        .map(|(num, date)| format!(
+            "<entry><title>Episode {num}</title><id>urn:test:episode-{num}</id>\
+             <updated>{date}</updated><published>{date}</published>\
+             <summary>Episode {num} description</summary>\
+             <link rel=\"enclosure\" href=\"http://127.0.0.1/episode-{num}.mp3\" type=\"audio/mpeg\" length=\"1000\"/>\
+             </entry>\n",
+            num = num, date = date.to_rfc3339()))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Atom Podcast</title><id>urn:test:atom</id>\
+         <updated>{}</updated><link href=\"http://127.0.0.1/\"/>\n{}</feed>\n",
+        chrono::Utc::now().to_rfc3339(), entries)
+}
+
+fn fixture_json_feed(num_items: usize) -> String {
+    let items: Vec<serde_json::Value> = episode_dates(num_items).iter()
+        .map(|(num, date)| serde_json::json!({
+            "id": format!("json-episode-{}", num),
+            "title": format!("Episode {}", num),
+            "content_html": format!("<p>Episode {} description</p>", num),
+            "date_published": date.to_rfc3339(),
+            "attachments": [{
+                "url": format!("http://127.0.0.1/json-episode-{}.mp3", num),
+                "mime_type": "audio/mpeg",
+                "size_in_bytes": 1000,
+                "duration_in_seconds": 1800,
+            }],
+        }))
+        .collect();
+    serde_json::json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": "JSON Podcast",
+        "home_page_url": "http://127.0.0.1/",
+        "items": items,
+    }).to_string()
+}
+
+#[test]
+fn formats_are_detected() {
+    assert_eq!(FeedFormat::detect(fixture_rss(1).as_bytes()), Some(FeedFormat::Rss));
+    assert_eq!(FeedFormat::detect(fixture_atom(1).as_bytes()), Some(FeedFormat::Atom));
+    assert_eq!(FeedFormat::detect(fixture_json_feed(1).as_bytes()), Some(FeedFormat::JsonFeed));
+    assert_eq!(FeedFormat::detect(b"<html><body>Not a feed</body></html>"), None);
+    assert!(racer::parse_feed(b"<html><body>Not a feed</body></html>").is_err());
+    assert!(racer::parse_feed(b"{\"version\": \"1\", \"items\": []}").is_err());
+}
+
+#[test]
+fn rss_1_0_is_read() {
+    let rdf = "<?xml version=\"1.0\"?>\n\
+        <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns=\"http://purl.org/rss/1.0/\">\
+        <channel rdf:about=\"http://127.0.0.1/\"><title>RDF Podcast</title><link>http://127.0.0.1/</link>\
+        <description>An RSS 1.0 feed</description></channel>\
+        <item rdf:about=\"http://127.0.0.1/1\"><title>Episode 1</title><link>http://127.0.0.1/1</link></item>\
+        <item rdf:about=\"http://127.0.0.1/2\"><title>Episode 2</title><link>http://127.0.0.1/2</link></item>\
+        </rdf:RDF>";
+    assert_eq!(FeedFormat::detect(rdf.as_bytes()), Some(FeedFormat::Rss));
+    let channel = racer::parse_feed(rdf.as_bytes()).expect("RSS 1.0 parses");
+    assert_eq!(channel.title(), "RDF Podcast");
+    assert_eq!(channel.items().len(), 2);
+}
+
+#[test]
+fn atom_sources_race_like_rss() {
+    let dir = TestDir::new();
+    let url = serve(fixture_atom(5), "application/atom+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        assert_eq!(racer.get_num_episodes(), 5);
+        assert_eq!(racer.get_num_to_publish(), 1);
+        assert_eq!(racer.get_current_ep_title().unwrap(), "Episode 1");
+
+        racer.jump_to_episode(3).await.expect("Episode exists");
+        let racer_rss = rss::Channel::read_from(
+            std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE)).unwrap().as_bytes()
+        ).expect("Racer rss parses");
+        assert_eq!(racer_rss.items().len(), 3);
+        let ep3 = racer_rss.items().iter().find(|item| item.title() == Some("Episode 3")).expect("Published");
+        assert_eq!(ep3.guid().map(|guid| guid.value()), Some("urn:test:episode-3"));
+        assert_eq!(ep3.enclosure().map(|enclosure| enclosure.url()), Some("http://127.0.0.1/episode-3.mp3"));
+    });
+}
+
+#[test]
+fn json_feed_sources_race_like_rss() {
+    let dir = TestDir::new();
+    let url = serve(fixture_json_feed(5), "application/feed+json");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 2).await;
+        assert_eq!(racer.get_num_episodes(), 5);
+        assert_eq!(racer.get_num_to_publish(), 2);
+        assert_eq!(racer.get_current_ep_title().unwrap(), "Episode 2");
+
+        // Still an rss feed on the way out, with the attachment's duration carried over
+        racer.jump_to_episode(5).await.expect("Episode exists");
+        let racer_rss = rss::Channel::read_from(
+            std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE)).unwrap().as_bytes()
+        ).expect("Racer rss parses");
+        assert_eq!(racer_rss.items().len(), 5);
+        assert!(racer_rss.items().iter().all(|item| item.enclosure().is_some()));
+        assert!(racer_rss.items().iter()
+            .all(|item| item.itunes_ext().and_then(|itunes| itunes.duration()) == Some("1800")));
+    });
+}
+
+#[test]
+fn formats_can_be_mixed() {
+    let dir = TestDir::new();
+    let rss_url = serve(fixture_rss(3), "application/rss+xml");
+    let json_url = serve(fixture_json_feed(3).replace("Episode", "Bonus"), "application/feed+json");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, &rss_url, RacerType::Ratio(1.0), 1);
+        params.extra_urls = vec![json_url];
+        let racer = create_racer_with_params(&dir, params).await;
+        assert_eq!(racer.get_num_episodes(), 6);
+    });
+}
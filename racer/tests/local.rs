@@ -0,0 +1,274 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   local.rs
+//
+//  © Zach Nielsen 2020
+//  Racers made from a directory of audio files
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use id3::TagLike;
+use racer::{read_local_channel, read_tags, AudioTags, MediaSource, RacerType};
+
+use std::path::{Path, PathBuf};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const AUDIO: &[u8] = b"0123456789abcdefghij";
+
+// An mp3 with an ID3 tag. The audio is made up, only the tag is read.
+fn write_mp3(path: &Path, title: &str, track: u32) {
+    std::fs::write(path, AUDIO).expect("Can write audio");
+    let mut tag = id3::Tag::new();
+    tag.set_title(title);
+    tag.set_album("Ripped CD");
+    tag.set_artist("The Band");
+    tag.set_track(track);
+    tag.write_to_path(path, id3::Version::Id3v24).expect("Can tag the mp3");
+}
+
+// A FLAC file that's just a Vorbis comment block
+fn write_flac(path: &Path, comments: &[&str]) {
+    let mut block = Vec::new();
+    let vendor = b"test";
+    block.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    block.extend_from_slice(vendor);
+    block.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in comments {
+        block.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        block.extend_from_slice(comment.as_bytes());
+    }
+    let mut flac = b"fLaC".to_vec();
+    // Last block, type 4
+    flac.push(0x84);
+    flac.extend_from_slice(&(block.len() as u32).to_be_bytes()[1..]);
+    flac.extend_from_slice(&block);
+    std::fs::write(path, flac).expect("Can write flac");
+}
+
+// A racer for the audio in `audio_dir`, with `root` as the local media root
+async fn create_local_racer(dir: &TestDir, audio_dir: &str, root: &str) -> racer::FeedRacer {
+    let mut params = creation_params(dir, audio_dir, RacerType::Ratio(1.0), 2);
+    params.local_media_root = Some(root.to_owned());
+    create_racer_with_params(dir, params).await
+}
+
+fn titles(channel: &rss::Channel) -> Vec<&str> {
+    // Oldest first, like the racer plays them
+    channel.items().iter().rev().map(|item| item.title().unwrap_or("")).collect()
+}
+
+#[test]
+fn tracks_are_ordered_by_tag() {
+    let audio = TestDir::new();
+    let dir = PathBuf::from(audio.as_str());
+    // Filenames disagree with the track numbers
+    write_mp3(&dir.join("a.mp3"), "Third", 3);
+    write_mp3(&dir.join("b.mp3"), "First", 1);
+    write_mp3(&dir.join("c.mp3"), "Second", 2);
+    std::fs::write(dir.join("cover.jpg"), b"not audio").unwrap();
+
+    let channel = read_local_channel(&dir).expect("Directory reads");
+    assert_eq!(titles(&channel), vec!["First", "Second", "Third"]);
+    assert_eq!(channel.title(), "Ripped CD");
+    assert_eq!(channel.description(), "By The Band");
+
+    let first = channel.items().last().unwrap();
+    assert_eq!(first.guid().map(|guid| guid.value()), Some("b.mp3"));
+    let enclosure = first.enclosure().expect("Has an enclosure");
+    assert_eq!(enclosure.mime_type(), "audio/mpeg");
+    assert!(enclosure.url().starts_with(racer::LOCAL_SCHEME));
+    assert!(enclosure.url().ends_with("/b.mp3"));
+    // Each episode is a day after the last
+    let dates: Vec<_> = channel.items().iter().rev()
+        .map(|item| chrono::DateTime::parse_from_rfc2822(item.pub_date().unwrap()).unwrap())
+        .collect();
+    assert_eq!(dates[1] - dates[0], chrono::Duration::days(1));
+    assert_eq!(dates[2] - dates[1], chrono::Duration::days(1));
+}
+
+#[test]
+fn untagged_files_are_ordered_by_name() {
+    let audio = TestDir::new();
+    let dir = PathBuf::from(audio.as_str()).join("My Show");
+    std::fs::create_dir_all(dir.join("disc 2")).unwrap();
+    std::fs::write(dir.join("02 - Middle.mp3"), AUDIO).unwrap();
+    std::fs::write(dir.join("01 - Start.wav"), AUDIO).unwrap();
+    std::fs::write(dir.join("disc 2").join("01 - End.ogg"), AUDIO).unwrap();
+    std::fs::write(dir.join(".hidden.mp3"), AUDIO).unwrap();
+
+    let channel = read_local_channel(&dir).expect("Directory reads");
+    assert_eq!(titles(&channel), vec!["01 - Start", "02 - Middle", "01 - End"]);
+    assert_eq!(channel.title(), "My Show");
+    assert_eq!(channel.items()[0].guid().map(|guid| guid.value()), Some("disc 2/01 - End.ogg"));
+    assert_eq!(channel.items()[0].enclosure().map(|enclosure| enclosure.length()), Some("20"));
+
+    // Nothing to make a feed out of
+    let empty = PathBuf::from(audio.as_str()).join("empty");
+    std::fs::create_dir_all(&empty).unwrap();
+    assert!(read_local_channel(&empty).is_err());
+    assert!(read_local_channel(Path::new("/not/a/real/dir")).is_err());
+}
+
+#[test]
+fn symlinks_are_not_followed() {
+    let audio = TestDir::new();
+    let outside = TestDir::new();
+    let dir = PathBuf::from(audio.as_str());
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("sub").join("01 - Real.mp3"), AUDIO).unwrap();
+    let secret = PathBuf::from(outside.as_str()).join("secret.mp3");
+    std::fs::write(&secret, AUDIO).unwrap();
+    // A loop back up the tree, and a file from somewhere else
+    std::os::unix::fs::symlink(&dir, dir.join("sub").join("loop")).unwrap();
+    std::os::unix::fs::symlink(&secret, dir.join("02 - Linked.mp3")).unwrap();
+
+    let channel = read_local_channel(&dir).expect("Directory reads");
+    assert_eq!(titles(&channel), vec!["01 - Real"]);
+}
+
+#[test]
+fn vorbis_comments_are_read() {
+    let audio = TestDir::new();
+    let path = PathBuf::from(audio.as_str()).join("track.flac");
+    write_flac(&path, &["title=Flac Track", "ALBUM=Lossless", "TRACKNUMBER=4/12", "DISCNUMBER=2", "COMMENT=Hi"]);
+    assert_eq!(read_tags(&path), AudioTags {
+        title: Some(String::from("Flac Track")),
+        album: Some(String::from("Lossless")),
+        artist: None,
+        comment: Some(String::from("Hi")),
+        track: Some(4),
+        disc: Some(2),
+    });
+    // Broken files just have no tags
+    std::fs::write(&path, b"fLaC nonsense").unwrap();
+    assert_eq!(read_tags(&path), AudioTags::default());
+}
+
+#[test]
+fn racers_serve_local_audio() {
+    let dir = TestDir::new();
+    let audio = TestDir::new();
+    let audio_dir = PathBuf::from(audio.as_str());
+    for (num, title) in ["One", "Two", "Three"].iter().enumerate() {
+        write_mp3(&audio_dir.join(format!("{}.mp3", title)), title, num as u32 + 1);
+    }
+    runtime().block_on(async {
+        let mut racer = create_local_racer(&dir, audio.as_str(), audio.as_str()).await;
+        assert_eq!(racer.get_sources()[0].url, format!("{}{}", racer::LOCAL_SCHEME, audio.as_str()));
+        racer.jump_to_episode(3).await.expect("Episode exists");
+
+        // Enclosures go through the media route, without turning proxying on
+        assert!(!racer.get_proxy_enclosures());
+        let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+            .expect("Racer rss was written");
+        let channel = rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses");
+        let enclosure = channel.items().iter()
+            .find(|item| item.title() == Some("Three"))
+            .and_then(|item| item.enclosure())
+            .expect("Enclosure kept");
+        assert_eq!(enclosure.url(), racer.get_media_url("Three.mp3"));
+
+        assert_eq!(racer.get_media_source("Three.mp3"), Some(MediaSource::Local {
+            path: std::fs::canonicalize(audio_dir.join("Three.mp3")).unwrap(),
+            mime_type: String::from("audio/mpeg"),
+        }));
+        let media = racer.open_media("Two.mp3", None, &reqwest::Client::new()).await
+            .expect("Opens").expect("Is served");
+        assert_eq!(media.status, 200);
+
+        // New files show up on the next refresh
+        write_mp3(&audio_dir.join("Four.mp3"), "Four", 4);
+        racer.update(&racer::RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        assert_eq!(racer.get_num_episodes(), 4);
+    });
+}
+
+#[test]
+fn only_scanned_files_are_served() {
+    let dir = TestDir::new();
+    let audio = TestDir::new();
+    let audio_dir = PathBuf::from(audio.as_str());
+    write_mp3(&audio_dir.join("One.mp3"), "One", 1);
+    // Inside the local source, but not something the scan turns into an episode
+    let secret = audio_dir.join("secret.txt");
+    std::fs::write(&secret, AUDIO).unwrap();
+    // A feed merged in alongside, whose enclosure points at that file
+    let rss = fixture_rss(3).replace(
+        "http://127.0.0.1/episode-1.mp3",
+        &format!("{}{}", racer::LOCAL_SCHEME, secret.display()),
+    );
+    let url = serve(rss, "application/rss+xml");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, audio.as_str(), RacerType::Ratio(1.0), 4);
+        params.extra_urls = vec![url];
+        params.local_media_root = Some(audio.as_str().to_owned());
+        let mut racer = create_racer_with_params(&dir, params).await;
+        assert!(racer.get_media_source("One.mp3").is_some());
+        assert_eq!(racer.get_media_source("episode-1"), None);
+
+        // Even with proxying on
+        racer.set_proxy_enclosures(true).await.expect("Can proxy enclosures");
+        assert_eq!(racer.get_media_source("episode-1"), None);
+        assert!(racer.get_media_source("episode-2").is_some());
+    });
+}
+
+#[test]
+fn local_sources_must_be_in_the_media_root() {
+    let dir = TestDir::new();
+    let root = TestDir::new();
+    let outside = TestDir::new();
+    let show = PathBuf::from(root.as_str()).join("show");
+    std::fs::create_dir_all(&show).unwrap();
+    write_mp3(&show.join("One.mp3"), "One", 1);
+    write_mp3(&PathBuf::from(outside.as_str()).join("One.mp3"), "One", 1);
+    let client = reqwest::Client::new();
+    runtime().block_on(async {
+        // No root, no local directories
+        let mut params = creation_params(&dir, show.to_str().unwrap(), RacerType::Ratio(1.0), 1);
+        assert!(racer::create_feed(&mut params, &client).await.is_err());
+        // Outside the root, including by way of ".."
+        let mut params = creation_params(&dir, outside.as_str(), RacerType::Ratio(1.0), 1);
+        params.local_media_root = Some(root.as_str().to_owned());
+        assert!(racer::create_feed(&mut params, &client).await.is_err());
+        let sneaky = format!("{}/show/../../{}", root.as_str(), PathBuf::from(outside.as_str()).file_name().unwrap().to_str().unwrap());
+        let mut params = creation_params(&dir, &sneaky, RacerType::Ratio(1.0), 1);
+        params.local_media_root = Some(root.as_str().to_owned());
+        assert!(racer::create_feed(&mut params, &client).await.is_err());
+
+        let mut racer = create_local_racer(&dir, show.to_str().unwrap(), root.as_str()).await;
+        let root_path = PathBuf::from(root.as_str());
+        let outside_url = format!("{}{}", racer::LOCAL_SCHEME, outside.as_str());
+        assert!(racer.set_sources(&[outside_url.clone()], Some(root_path.as_path()), &client).await.is_err());
+        assert!(racer.set_sources(&[outside_url], None, &client).await.is_err());
+        assert_eq!(racer.get_sources().len(), 1);
+    });
+}
+
+#[test]
+fn podarch_guids_are_kept() {
+    let audio = TestDir::new();
+    let dir = PathBuf::from(audio.as_str());
+    let channel = rss::Channel::read_from(fixture_rss(2).as_bytes()).unwrap();
+    let mut manifest = racer::ArchiveManifest::new("http://127.0.0.1/gone.rss");
+    for (num, item) in channel.items().iter().rev().enumerate() {
+        let file = format!("{:03} - {}.mp3", num + 1, item.title().unwrap());
+        std::fs::write(dir.join(&file), AUDIO).unwrap();
+        manifest.insert(racer::ArchivedEpisode::new(item, &file, None));
+    }
+    manifest.write(&dir).unwrap();
+
+    let channel = read_local_channel(&dir).expect("Directory reads");
+    assert_eq!(titles(&channel), vec!["001 - Episode 1", "002 - Episode 2"]);
+    let guids: Vec<_> = channel.items().iter().rev().filter_map(|item| item.guid()).map(|guid| guid.value()).collect();
+    assert_eq!(guids, vec!["episode-1", "episode-2"]);
+}
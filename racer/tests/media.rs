@@ -0,0 +1,167 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   media.rs
+//
+//  © Zach Nielsen 2020
+//  Serving episode audio through PodRacer
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{parse_range, ByteRange, FeedRacer, Media, MediaBody, MediaSource, RacerType};
+
+use tokio::io::AsyncReadExt;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const AUDIO: &[u8] = b"0123456789abcdefghij";
+
+async fn read_body(media: Media) -> Vec<u8> {
+    match media.body {
+        MediaBody::File(mut file) => {
+            let mut body = Vec::new();
+            file.read_to_end(&mut body).await.expect("Can read the file");
+            body
+        },
+        MediaBody::Upstream(response) => response.bytes().await.expect("Can read upstream").to_vec(),
+        MediaBody::Empty => Vec::new(),
+    }
+}
+
+// The enclosure of episode `num`, all served by `audio_url`
+fn episode_url(audio_url: &str, num: usize) -> String {
+    format!("{}?episode={}", audio_url, num)
+}
+
+// A racer with its episodes at `audio_url`, proxying its enclosures
+async fn proxied_racer(dir: &TestDir, audio_url: &str) -> FeedRacer {
+    let mut rss = fixture_rss(3);
+    for num in 1..=3 {
+        rss = rss.replace(&format!("http://127.0.0.1/episode-{}.mp3", num), &episode_url(audio_url, num));
+    }
+    let url = serve(rss, "application/rss+xml");
+    let mut racer = create_racer(dir, &url, RacerType::Ratio(1.0), 1).await;
+    racer.jump_to_episode(2).await.expect("Episode exists");
+    racer.set_proxy_enclosures(true).await.expect("Can proxy enclosures");
+    racer
+}
+
+#[test]
+fn ranges_are_parsed() {
+    assert_eq!(parse_range("bytes=0-9", 20), ByteRange::Partial(0, 9));
+    assert_eq!(parse_range("bytes=10-", 20), ByteRange::Partial(10, 19));
+    assert_eq!(parse_range("bytes=-5", 20), ByteRange::Partial(15, 19));
+    assert_eq!(parse_range("bytes=-50", 20), ByteRange::Partial(0, 19));
+    assert_eq!(parse_range("bytes=15-100", 20), ByteRange::Partial(15, 19));
+    assert_eq!(parse_range("bytes=20-", 20), ByteRange::Unsatisfiable);
+    assert_eq!(parse_range("bytes=-0", 20), ByteRange::Unsatisfiable);
+    // Multiple ranges, other units and nonsense get the whole file
+    assert_eq!(parse_range("bytes=0-1, 5-6", 20), ByteRange::Full);
+    assert_eq!(parse_range("items=0-1", 20), ByteRange::Full);
+    assert_eq!(parse_range("bytes=9-2", 20), ByteRange::Full);
+    assert_eq!(parse_range("bytes=a-b", 20), ByteRange::Full);
+}
+
+#[test]
+fn enclosures_point_at_the_media_route() {
+    let dir = TestDir::new();
+    let audio_url = serve_with_ranges(AUDIO.to_vec(), "audio/mpeg");
+    runtime().block_on(async {
+        let mut racer = proxied_racer(&dir, &audio_url).await;
+        assert!(reload(&dir, &racer).get_proxy_enclosures());
+        let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+            .expect("Racer rss was written");
+        let channel = rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses");
+        let enclosure = channel.items()[0].enclosure().expect("Enclosure kept");
+        assert_eq!(enclosure.url(), racer.get_media_url("episode-1"));
+        assert!(enclosure.url().ends_with("/media/episode-1"));
+        assert_eq!(enclosure.mime_type(), "audio/mpeg");
+        // GUIDs with characters that aren't safe in a path get encoded
+        assert!(racer.get_media_url("a b/c").ends_with("/media/a%20b%2Fc"));
+
+        // Only episodes in the feed are served
+        assert_eq!(racer.get_media_source("episode-1"), Some(MediaSource::Upstream { url: episode_url(&audio_url, 1) }));
+        assert_eq!(racer.get_media_source("not-an-episode"), None);
+
+        // Nothing is served once proxying is off
+        racer.set_proxy_enclosures(false).await.expect("Can stop proxying");
+        assert_eq!(racer.get_media_source("episode-1"), None);
+        let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+            .expect("Racer rss was written");
+        assert!(racer_rss.contains(&episode_url(&audio_url, 1)));
+    });
+}
+
+#[test]
+fn upstream_media_is_streamed_with_ranges() {
+    let dir = TestDir::new();
+    let audio_url = serve_with_ranges(AUDIO.to_vec(), "audio/mpeg");
+    runtime().block_on(async {
+        let racer = proxied_racer(&dir, &audio_url).await;
+        let client = reqwest::Client::new();
+
+        let media = racer.open_media("episode-1", None, &client).await.expect("Opens").expect("Is served");
+        assert_eq!(media.status, 200);
+        assert_eq!(media.mime_type, "audio/mpeg");
+        assert_eq!(media.content_length, Some(AUDIO.len() as u64));
+        assert_eq!(read_body(media).await, AUDIO);
+
+        let media = racer.open_media("episode-1", Some("bytes=5-9"), &client).await.expect("Opens").expect("Is served");
+        assert_eq!(media.status, 206);
+        assert_eq!(media.content_range.as_deref(), Some("bytes 5-9/20"));
+        assert!(media.accept_ranges);
+        assert_eq!(read_body(media).await, b"56789");
+    });
+}
+
+#[test]
+fn archived_copies_are_served_with_ranges() {
+    let dir = TestDir::new();
+    // Upstream is gone
+    runtime().block_on(async {
+        let racer = proxied_racer(&dir, "http://127.0.0.1:9/gone.mp3").await;
+        let client = reqwest::Client::new();
+        assert!(racer.open_media("episode-2", None, &client).await.is_err());
+
+        let media_dir = racer.get_racer_path().join(racer::MEDIA_DIR);
+        std::fs::create_dir_all(&media_dir).expect("Can make the media dir");
+        std::fs::write(media_dir.join("episode-2"), AUDIO).expect("Can archive an episode");
+
+        let media = racer.open_media("episode-2", None, &client).await.expect("Opens").expect("Is served");
+        assert_eq!((media.status, media.content_length), (200, Some(20)));
+        assert_eq!(read_body(media).await, AUDIO);
+
+        let media = racer.open_media("episode-2", Some("bytes=-3"), &client).await.expect("Opens").expect("Is served");
+        assert_eq!(media.status, 206);
+        assert_eq!(media.content_range.as_deref(), Some("bytes 17-19/20"));
+        assert_eq!(media.content_length, Some(3));
+        assert_eq!(read_body(media).await, b"hij");
+
+        let media = racer.open_media("episode-2", Some("bytes=20-"), &client).await.expect("Opens").expect("Is served");
+        assert_eq!(media.status, 416);
+        assert_eq!(media.content_range.as_deref(), Some("bytes */20"));
+    });
+}
+
+#[test]
+fn bad_racer_dirs_are_not_found() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(3), "application/rss+xml");
+    let racer = runtime().block_on(create_racer(&dir, &url, RacerType::Ratio(1.0), 1));
+    let racer_name = racer.get_racer_name().to_str().unwrap().to_owned();
+    assert!(racer::get_by_dir_name(dir.as_str(), &racer_name).is_some());
+
+    // Media urls come from players, so a broken or made up racer dir is a miss, not a crash
+    std::fs::create_dir_all(std::path::PathBuf::from(dir.as_str()).join("not-a-racer")).unwrap();
+    assert!(racer::get_by_dir_name(dir.as_str(), "not-a-racer").is_none());
+    assert!(racer::get_by_dir_name(dir.as_str(), "..").is_none());
+    assert!(racer::get_by_dir_name(dir.as_str(), &format!("../{}", racer_name)).is_none());
+    assert!(racer::get_by_dir_name(dir.as_str(), "").is_none());
+}
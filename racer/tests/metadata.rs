@@ -0,0 +1,134 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   metadata.rs
+//
+//  © Zach Nielsen 2020
+//  iTunes and Podcasting 2.0 metadata making it through to racer.rss
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedRacer, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const CHANNEL_EXTRAS: &'static str = "\
+    <itunes:image href=\"https://example.com/art.jpg\"/>\
+    <itunes:explicit>yes</itunes:explicit>\
+    <itunes:type>Serial</itunes:type>\
+    <itunes:category text=\"Comedy\"><itunes:category text=\"Improv\"/></itunes:category>\
+    <itunes:category text=\"\"/>\
+    <itunes:new-feed-url>https://example.com/moved.rss</itunes:new-feed-url>\
+    <itunes:owner><itunes:name>Someone</itunes:name></itunes:owner>\
+    <podcast:guid>917393e3-1b1e-5cef-ace4-edaa54e1f810</podcast:guid>\
+    <podcast:funding url=\"https://example.com/support\">Support the show</podcast:funding>";
+
+// A fixture feed with metadata on the channel and on episodes 1-3
+fn metadata_rss() -> String {
+    let rss = with_item_extras(fixture_rss(3), &[
+        (1, "<itunes:duration>1:02:03</itunes:duration><itunes:explicit>clean</itunes:explicit>\
+             <itunes:image href=\"https://example.com/ep1.jpg\"/>\
+             <podcast:chapters url=\"https://example.com/ep1.json\" type=\"application/json+chapters\"/>\
+             <podcast:transcript type=\"text/vtt\"/>"),
+        (2, "<itunes:duration>1234.5</itunes:duration><itunes:episode>0</itunes:episode>\
+             <itunes:episodeType>Bonus</itunes:episodeType>\
+             <media:rights status=\"userCreated\"/>"),
+        (3, "<itunes:duration>an hour</itunes:duration><itunes:season>2</itunes:season>\
+             <itunes:episode>7</itunes:episode><itunes:image href=\"\"/>\
+             <podcast:transcript url=\"https://example.com/ep3.vtt\" type=\"text/vtt\"/>"),
+    ]);
+    rss.replace(
+        "xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">",
+        "xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\" \
+         xmlns:podcast=\"https://podcastindex.org/namespace/1.0\" \
+         xmlns:media=\"http://search.yahoo.com/mrss/\">",
+    )
+    .replace("<title>Test Podcast</title>", &format!("<title>Test Podcast</title>{}", CHANNEL_EXTRAS))
+}
+
+async fn published_racer_rss(dir: &TestDir) -> (FeedRacer, rss::Channel) {
+    let url = serve(metadata_rss(), "application/rss+xml");
+    let mut racer = create_racer(dir, &url, RacerType::Ratio(1.0), 1).await;
+    racer.jump_to_episode(3).await.expect("Episode exists");
+    let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+        .expect("Racer rss was written");
+    let channel = rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses");
+    (racer, channel)
+}
+
+fn item<'a>(channel: &'a rss::Channel, title: &str) -> &'a rss::Item {
+    channel.items().iter().find(|item| item.title() == Some(title)).expect("Item was published")
+}
+
+#[test]
+fn channel_metadata_passes_through() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let (_racer, channel) = published_racer_rss(&dir).await;
+        let itunes = channel.itunes_ext().expect("iTunes data kept");
+        assert_eq!(itunes.image(), Some("https://example.com/art.jpg"));
+        assert_eq!(itunes.explicit(), Some("true"));
+        assert_eq!(itunes.r#type(), Some("serial"));
+        assert_eq!(itunes.categories().len(), 1);
+        assert_eq!(itunes.categories()[0].text(), "Comedy");
+        assert_eq!(itunes.categories()[0].subcategory().map(|sub| sub.text()), Some("Improv"));
+
+        let podcast = channel.extensions().get("podcast").expect("Podcast namespace kept");
+        assert_eq!(podcast["funding"][0].attrs["url"], "https://example.com/support");
+    });
+}
+
+#[test]
+fn known_bad_channel_elements_are_dropped() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let (_racer, channel) = published_racer_rss(&dir).await;
+        let itunes = channel.itunes_ext().expect("iTunes data kept");
+        // Would send subscribers back to the original feed
+        assert!(itunes.new_feed_url().is_none());
+        // No email
+        assert!(itunes.owner().is_none());
+        // The racer isn't the original show
+        assert!(!channel.extensions()["podcast"].contains_key("guid"));
+    });
+}
+
+#[test]
+fn item_metadata_is_kept_or_fixed() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let (_racer, channel) = published_racer_rss(&dir).await;
+
+        let ep1 = item(&channel, "Episode 1");
+        let itunes = ep1.itunes_ext().expect("iTunes data kept");
+        assert_eq!(itunes.duration(), Some("1:02:03"));
+        assert_eq!(itunes.explicit(), Some("false"));
+        assert_eq!(itunes.image(), Some("https://example.com/ep1.jpg"));
+        let podcast = &ep1.extensions()["podcast"];
+        assert_eq!(podcast["chapters"][0].attrs["url"], "https://example.com/ep1.json");
+        // Points nowhere
+        assert!(!podcast.contains_key("transcript"));
+
+        let ep2 = item(&channel, "Episode 2");
+        let itunes = ep2.itunes_ext().expect("iTunes data kept");
+        assert_eq!(itunes.duration(), Some("1234"));
+        assert_eq!(itunes.episode(), None);
+        assert_eq!(itunes.episode_type(), Some("bonus"));
+        assert!(ep2.extensions().get("media").map(|media| media.get("rights").is_none()).unwrap_or(true));
+
+        let ep3 = item(&channel, "Episode 3");
+        let itunes = ep3.itunes_ext().expect("iTunes data kept");
+        assert_eq!(itunes.duration(), None);
+        assert_eq!(itunes.season(), Some("2"));
+        assert_eq!(itunes.episode(), Some("7"));
+        assert_eq!(itunes.image(), None);
+        assert_eq!(ep3.extensions()["podcast"]["transcript"][0].attrs["url"], "https://example.com/ep3.vtt");
+    });
+}
@@ -0,0 +1,130 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   navigation.rs
+//
+//  © Zach Nielsen 2020
+//  Moving by episodes should stop cleanly at the first and last episode
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedRacer, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const NUM_ITEMS: usize = 10;
+
+// A racer with exactly `num_published` episodes out
+async fn racer_with_published(dir: &TestDir, num_published: usize) -> FeedRacer {
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    let mut racer = create_racer(dir, &url, RacerType::Days(7), 1).await;
+    racer.rewind_by_episodes(NUM_ITEMS).await;
+    racer.fastforward_by_episodes(num_published).await;
+    assert_eq!(racer.get_num_to_publish(), num_published);
+    racer
+}
+
+#[test]
+fn nothing_published() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let mut racer = racer_with_published(&dir, 0).await;
+        assert_eq!(racer.get_current_ep_title(), None);
+        assert_eq!(racer.get_next_ep_title(), Some(String::from("Episode 1")));
+
+        let res = racer.rewind_by_episodes(3).await;
+        assert_eq!(res.moved, 0);
+        assert_eq!(res.num_published, 0);
+        assert!(res.clamped);
+
+        let res = racer.fastforward_by_episodes(1).await;
+        assert_eq!(res.moved, 1);
+        assert_eq!(res.num_published, 1);
+        assert!(!res.clamped);
+    });
+}
+
+#[test]
+fn one_published() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let mut racer = racer_with_published(&dir, 1).await;
+        assert_eq!(racer.get_current_ep_title(), Some(String::from("Episode 1")));
+        assert_eq!(racer.get_next_ep_title(), Some(String::from("Episode 2")));
+
+        let res = racer.rewind_by_episodes(5).await;
+        assert_eq!(res.requested, 5);
+        assert_eq!(res.moved, 1);
+        assert_eq!(res.num_published, 0);
+        assert!(res.clamped);
+        assert_eq!(reload(&dir, &racer).get_num_to_publish(), 0);
+    });
+}
+
+#[test]
+fn all_published() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let mut racer = racer_with_published(&dir, NUM_ITEMS).await;
+        assert_eq!(racer.get_current_ep_title(), Some(format!("Episode {}", NUM_ITEMS)));
+        assert_eq!(racer.get_next_ep_title(), None);
+
+        let res = racer.fastforward_by_episodes(2).await;
+        assert_eq!(res.moved, 0);
+        assert_eq!(res.num_published, NUM_ITEMS);
+        assert!(res.clamped);
+
+        let res = racer.rewind_by_episodes(1).await;
+        assert_eq!(res.moved, 1);
+        assert_eq!(res.num_published, NUM_ITEMS - 1);
+        assert!(!res.clamped);
+    });
+}
+
+#[test]
+fn jump_to_episode() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let mut racer = racer_with_published(&dir, 2).await;
+
+        let res = racer.jump_to_episode(7).await.expect("Episode 7 exists");
+        assert_eq!(res.num_published, 7);
+        assert_eq!(res.moved, 5);
+        assert_eq!(racer.get_current_ep_title(), Some(String::from("Episode 7")));
+
+        let res = racer.jump_to_episode(3).await.expect("Episode 3 exists");
+        assert_eq!(res.num_published, 3);
+        assert_eq!(reload(&dir, &racer).get_num_to_publish(), 3);
+
+        assert!(racer.jump_to_episode(0).await.is_err());
+        assert!(racer.jump_to_episode(NUM_ITEMS + 1).await.is_err());
+        assert_eq!(racer.get_num_to_publish(), 3);
+    });
+}
+
+#[test]
+fn jump_to_date() {
+    let dir = TestDir::new();
+    runtime().block_on(async {
+        let mut racer = racer_with_published(&dir, 4).await;
+        let today = chrono::Utc::now().with_timezone(&racer.get_timezone()).date_naive();
+
+        // Weekly episodes, with episode 4 just out. Ten days ago only 1 and 2 were out.
+        let res = racer.jump_to_date(today - chrono::Duration::days(10)).await.expect("Can jump back");
+        assert_eq!(res.num_published, 2);
+
+        let res = racer.jump_to_date(today - chrono::Duration::days(365)).await.expect("Can jump way back");
+        assert_eq!(res.num_published, 0);
+
+        let res = racer.jump_to_date(today + chrono::Duration::days(365)).await.expect("Can jump ahead");
+        assert_eq!(res.num_published, NUM_ITEMS);
+        assert!(racer.get_next_ep_title().is_none());
+    });
+}
@@ -0,0 +1,133 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   order.rs
+//
+//  © Zach Nielsen 2020
+//  Episode ordering strategies
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{EpisodeOrder, FeedRacer, RacerType};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// An archive-style feed where every item has the same date. Items are (title, extra xml), listed
+// in feed order.
+fn archive_rss(items: &[(&str, &str)]) -> String {
+    let date = (chrono::Utc::now() - chrono::Duration::days(365)).to_rfc2822();
+    let items: String = items.iter()
+        .enumerate()
+        .map(|(idx, (title, extra))| format!(
+            "<item><title>{}</title><guid>item-{}</guid>{}<pubDate>{}</pubDate></item>\n",
+            title, idx, extra, date))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\"><channel>\
+         <title>Archive</title><link>http://127.0.0.1/</link><description>Old stuff</description>\n{}\
+         </channel></rss>\n",
+        items
+    )
+}
+
+// Titles in publishing order
+async fn titles(racer: &mut FeedRacer) -> Vec<String> {
+    let mut titles = Vec::new();
+    for num in 1..=racer.get_num_episodes() {
+        racer.jump_to_episode(num).await.expect("Episode exists");
+        titles.push(racer.get_current_ep_title().expect("Episode is published"));
+    }
+    titles
+}
+
+#[test]
+fn identical_dates_use_feed_position() {
+    let dir = TestDir::new();
+    let url = serve(archive_rss(&[("Gamma", ""), ("Beta", ""), ("Alpha", "")]), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        assert_eq!(titles(&mut racer).await, vec!["Alpha", "Beta", "Gamma"]);
+        // Same answer after a round trip through the update path
+        let mut racer = reload(&dir, &racer);
+        assert_eq!(titles(&mut racer).await, vec!["Alpha", "Beta", "Gamma"]);
+    });
+}
+
+#[test]
+fn undated_items_can_be_raced() {
+    let dir = TestDir::new();
+    let date = |days: i64| (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc2822();
+    // Newest first, like a real feed. Only the first and last have usable dates.
+    let rss = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\"><channel><title>Undated</title><link>http://127.0.0.1/</link>\
+         <description>Some dates missing</description>\n\
+         <item><title>Fourth</title><guid>item-4</guid><pubDate>{}</pubDate></item>\n\
+         <item><title>Third</title><guid>item-3</guid><pubDate>not a date</pubDate></item>\n\
+         <item><title>Second</title><guid>item-2</guid></item>\n\
+         <item><title>First</title><guid>item-1</guid><pubDate>{}</pubDate></item>\n\
+         </channel></rss>\n",
+        date(10), date(40));
+    let url = serve(rss, "application/rss+xml");
+    runtime().block_on(async {
+        // Undated items share the slot of the item before them when racing by ratio
+        let racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 2).await;
+        assert_eq!(racer.get_num_to_publish(), 3);
+
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        assert_eq!(racer.get_num_episodes(), 4);
+        assert_eq!(titles(&mut racer).await, vec!["First", "Second", "Third", "Fourth"]);
+        let racer_rss = std::fs::read_to_string(racer.get_racer_path().join(racer::RACER_RSS_FILE))
+            .expect("Racer rss was written");
+        let channel = rss::Channel::read_from(racer_rss.as_bytes()).expect("Racer rss parses");
+        assert_eq!(channel.items().len(), 4);
+        assert!(channel.items().iter().all(|item| item.pub_date().is_some()));
+    });
+}
+
+#[test]
+fn order_by_number() {
+    let dir = TestDir::new();
+    let url = serve(archive_rss(&[
+        ("Part 2", "<itunes:season>1</itunes:season><itunes:episode>2</itunes:episode>"),
+        ("Part 10", "<itunes:season>2</itunes:season><itunes:episode>1</itunes:episode>"),
+        ("Part 1", "<itunes:season>1</itunes:season><itunes:episode>1</itunes:episode>"),
+        ("Intermission", ""),
+    ]), "application/rss+xml");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, &url, RacerType::Days(7), 1);
+        params.order = EpisodeOrder::TitleNumber;
+        let mut racer = create_racer_with_params(&dir, params).await;
+        assert_eq!(racer.get_episode_order(), EpisodeOrder::TitleNumber);
+        // Unnumbered items stay next to the item before them
+        assert_eq!(titles(&mut racer).await, vec!["Intermission", "Part 1", "Part 2", "Part 10"]);
+
+        racer.set_episode_order(EpisodeOrder::EpisodeNumber).await.expect("Can set order");
+        assert_eq!(titles(&mut racer).await, vec!["Intermission", "Part 1", "Part 2", "Part 10"]);
+
+        racer.set_episode_order(EpisodeOrder::FeedOrder).await.expect("Can set order");
+        assert_eq!(titles(&mut racer).await, vec!["Intermission", "Part 1", "Part 10", "Part 2"]);
+    });
+}
+
+#[test]
+fn changing_order_keeps_published_count() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(10), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 4).await;
+        let published = racer.get_num_to_publish();
+        racer.set_episode_order(EpisodeOrder::TitleNumber).await.expect("Can set order");
+        assert_eq!(racer.get_num_to_publish(), published);
+        assert_eq!(reload(&dir, &racer).get_episode_order(), EpisodeOrder::TitleNumber);
+    });
+}
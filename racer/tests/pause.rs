@@ -0,0 +1,277 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   pause.rs
+//
+//  © Zach Nielsen 2020
+//  Pausing, rate changes and days-based navigation shouldn't lose our place
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use proptest::prelude::*;
+use racer::RacerType;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const NUM_ITEMS: usize = 60;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Pause,
+    Unpause,
+    SetRatio(f64),
+    SetDays(u32),
+    RewindDays(usize),
+    FastForwardDays(usize),
+    RewindEpisodes(usize),
+    FastForwardEpisodes(usize),
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        Just(Op::Pause),
+        Just(Op::Unpause),
+        prop::sample::select(vec![0.5, 1.0, 1.5, 2.0, 4.0]).prop_map(Op::SetRatio),
+        (1u32..14).prop_map(Op::SetDays),
+        (0usize..60).prop_map(Op::RewindDays),
+        (0usize..60).prop_map(Op::FastForwardDays),
+        (0usize..70).prop_map(Op::RewindEpisodes),
+        (0usize..70).prop_map(Op::FastForwardEpisodes),
+    ]
+}
+
+fn rate_strategy() -> impl Strategy<Value = RacerType> {
+    prop_oneof![
+        prop::sample::select(vec![0.5, 1.0, 2.0, 4.0]).prop_map(RacerType::Ratio),
+        (1u32..14).prop_map(RacerType::Days),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn published_count_survives_any_sequence(
+        rate in rate_strategy(),
+        start_ep in 1usize..40,
+        ops in prop::collection::vec(op_strategy(), 1..10),
+    ) {
+        let dir = TestDir::new();
+        let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+        runtime().block_on(async {
+            let mut racer = create_racer(&dir, &url, rate, start_ep).await;
+
+            for op in ops {
+                let before = racer.get_num_to_publish();
+                let was_paused = racer.is_paused();
+                let mut moved_to = None;
+                match op {
+                    Op::Pause => racer.pause_feed().await,
+                    Op::Unpause => { racer.unpause_feed().await; },
+                    Op::SetRatio(rate) => racer.set_rate_ratio(rate).await.expect("Can set ratio"),
+                    Op::SetDays(days) => racer.set_rate_days(days).await.expect("Can set days"),
+                    Op::RewindDays(days) => racer.rewind_by_days(days).await,
+                    Op::FastForwardDays(days) => racer.fastforward_by_days(days).await,
+                    Op::RewindEpisodes(num) => moved_to = Some(racer.rewind_by_episodes(num).await),
+                    Op::FastForwardEpisodes(num) => moved_to = Some(racer.fastforward_by_episodes(num).await),
+                }
+                let after = racer.get_num_to_publish();
+
+                match op {
+                    Op::Pause => {
+                        assert!(racer.is_paused());
+                        assert_eq!(before, after, "pausing changed the published count");
+                    },
+                    Op::Unpause => {
+                        assert!(!racer.is_paused());
+                        assert_eq!(before, after, "unpausing changed the published count");
+                    },
+                    Op::SetRatio(_) | Op::SetDays(_) => {
+                        assert_eq!(was_paused, racer.is_paused(), "setting the rate changed the pause state");
+                        assert_eq!(before, after, "setting the rate changed the published count");
+                    },
+                    Op::RewindDays(_) => assert!(after <= before, "rewinding published more episodes"),
+                    Op::FastForwardDays(_) => assert!(after >= before, "fast-forwarding unpublished episodes"),
+                    // Episode moves are exact, short of running off either end
+                    Op::RewindEpisodes(num) => assert_eq!(after, before.saturating_sub(num), "rewound the wrong number of episodes"),
+                    Op::FastForwardEpisodes(num) => assert_eq!(after, std::cmp::min(before + num, racer.get_num_episodes()),
+                                                               "fast-forwarded the wrong number of episodes"),
+                }
+                if let Some(res) = moved_to {
+                    assert_eq!(was_paused, racer.is_paused(), "moving by episodes changed the pause state");
+                    assert_eq!(res.num_published, after);
+                    assert_eq!(res.moved, before.abs_diff(after));
+                }
+                assert!(after <= racer.get_num_episodes());
+
+                // What's on disk should match what we have in hand
+                assert_eq!(after, reload(&dir, &racer).get_num_to_publish());
+            }
+        });
+    }
+}
+
+#[test]
+fn rate_change_while_paused_keeps_place() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 10).await;
+        racer.fastforward_by_days(20).await;
+        let published = racer.get_num_to_publish();
+
+        racer.pause_feed().await;
+        racer.set_rate_days(2).await.expect("Can set days");
+        racer.set_rate_ratio(3.0).await.expect("Can set ratio");
+        // The rate is just the rate - no sentinel values while paused
+        assert_eq!(racer.get_rate().to_string(), "Ratio(3)");
+        assert_eq!(published, racer.get_num_to_publish());
+
+        racer.unpause_feed().await.expect("Feed was paused");
+        assert_eq!(published, racer.get_num_to_publish());
+        assert_eq!(racer.get_rate().to_string(), "Ratio(3)");
+    });
+}
+
+#[test]
+fn legacy_paused_racer_gets_its_rate_back() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    let racer = runtime().block_on(create_racer(&dir, &url, RacerType::Days(3), 1));
+
+    // Write the racer file the way 1.1 paused feeds looked
+    let racer_file = racer.get_racer_path().join(racer::RACER_FILE);
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&racer_file).unwrap()).unwrap();
+    json["schema_version"] = serde_json::json!("1.1");
+    json["rate"] = serde_json::json!({ "Days": 1000 });
+    json["old_rate"] = serde_json::json!({ "Days": 3 });
+    json["pause_date"] = serde_json::json!(chrono::Utc::now());
+    std::fs::write(&racer_file, json.to_string()).unwrap();
+
+    let racer = reload(&dir, &racer);
+    assert!(racer.is_paused());
+    assert_eq!(racer.get_rate().to_string(), "Days(3)");
+}
+
+// Writes scheduled pauses straight into the racer file, so windows can start and end in the past
+fn with_scheduled_pauses(dir: &TestDir, racer: &racer::FeedRacer, pauses: serde_json::Value) -> racer::FeedRacer {
+    let racer_file = racer.get_racer_path().join(racer::RACER_FILE);
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&racer_file).unwrap()).unwrap();
+    json["scheduled_pauses"] = pauses;
+    std::fs::write(&racer_file, json.to_string()).unwrap();
+    reload(dir, racer)
+}
+
+#[test]
+fn scheduled_pauses_start_and_end_on_time() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        // An episode a day, with releases half a day away from the window's edges
+        let mut racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        racer.fastforward_by_days(20).await;
+        let anchor = racer.get_anchor_date();
+        let published = racer.get_num_to_publish();
+        let now = chrono::Utc::now();
+        let start = now - chrono::Duration::hours(10 * 24 + 12);
+        let end = now - chrono::Duration::hours(4 * 24 + 12);
+
+        // The update thread got to the window late, but the feed is paused from when it opened
+        let mut racer = with_scheduled_pauses(&dir, &racer, serde_json::json!([
+            { "start": start, "end": now + chrono::Duration::days(1), "active": false },
+        ]));
+        assert!(racer.apply_scheduled_pauses().await);
+        assert_eq!(racer.get_pause_date(), Some(start));
+        assert_eq!(racer.get_num_to_publish(), published - 11);
+
+        // Same for the end. The anchor moves by the length of the window, and everything due
+        // since then comes out.
+        let mut racer = with_scheduled_pauses(&dir, &racer, serde_json::json!([
+            { "start": start, "end": end, "active": true },
+        ]));
+        assert!(racer.apply_scheduled_pauses().await);
+        assert!(!racer.is_paused());
+        assert!(racer.get_scheduled_pauses().is_empty());
+        assert_eq!(racer.get_anchor_date(), anchor + (end - start));
+        assert_eq!(racer.get_num_to_publish(), published - 6);
+        assert_eq!(reload(&dir, &racer).get_anchor_date(), racer.get_anchor_date());
+    });
+}
+
+#[test]
+fn missed_scheduled_pauses_are_dropped() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        racer.fastforward_by_days(20).await;
+        let anchor = racer.get_anchor_date();
+        let published = racer.get_num_to_publish();
+        let now = chrono::Utc::now();
+
+        // The server was down for the whole window
+        let mut racer = with_scheduled_pauses(&dir, &racer, serde_json::json!([
+            { "start": now - chrono::Duration::days(10), "end": now - chrono::Duration::days(4), "active": false },
+        ]));
+        assert!(!racer.apply_scheduled_pauses().await);
+        assert!(!racer.is_paused());
+        assert_eq!(racer.get_anchor_date(), anchor);
+        assert_eq!(racer.get_num_to_publish(), published);
+        assert!(reload(&dir, &racer).get_scheduled_pauses().is_empty());
+    });
+}
+
+#[test]
+fn closing_a_window_leaves_manual_pauses_alone() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        racer.pause_feed().await;
+        let pause_date = racer.get_pause_date();
+        let now = chrono::Utc::now();
+
+        let mut racer = with_scheduled_pauses(&dir, &racer, serde_json::json!([
+            { "start": now - chrono::Duration::days(2), "end": now - chrono::Duration::days(1), "active": true },
+        ]));
+        assert!(!racer.apply_scheduled_pauses().await);
+        assert!(racer.is_paused());
+        assert_eq!(racer.get_pause_date(), pause_date);
+        assert!(racer.get_scheduled_pauses().is_empty());
+
+        // Only unpausing by hand lifts it
+        racer.unpause_feed().await.expect("Feed was paused");
+        assert!(!racer.is_paused());
+    });
+}
+
+#[test]
+fn long_pauses_still_report_the_next_episode() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        let now = chrono::Utc::now();
+        let start = now - chrono::Duration::hours(10 * 24 + 12);
+
+        // Paused since well before the next few releases would have gone out
+        let mut racer = with_scheduled_pauses(&dir, &racer, serde_json::json!([
+            { "start": start, "end": now + chrono::Duration::days(1), "active": false },
+        ]));
+        assert!(racer.apply_scheduled_pauses().await);
+        assert_eq!(racer.get_feed_status(), Ok(racer::FeedStatus::Paused));
+
+        // The next episode is the one the pause stopped in front of, not nothing at all
+        let next = racer.get_next_episode_pub_date();
+        assert!(start < next && next < now, "next episode at {}", next);
+    });
+}
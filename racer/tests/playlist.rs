@@ -0,0 +1,148 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   playlist.rs
+//
+//  © Zach Nielsen 2020
+//  Shuffled and curated episode orders
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{EpisodeOrder, FeedRacer, NewEpisodePlacement, Playlist, RacerType, RssFile};
+
+use std::sync::{Arc, Mutex};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// The first `num` episodes of a fixture feed with `total` episodes
+fn first_episodes(total: usize, num: usize) -> String {
+    fixture_rss(total)
+        .lines()
+        .filter(|line| match line.strip_prefix("<item><title>Episode ") {
+            Some(rest) => rest.split('<').next().unwrap().parse::<usize>().unwrap() <= num,
+            None => true,
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+// Titles in publishing order. Leaves the racer with everything published.
+async fn titles(racer: &mut FeedRacer) -> Vec<String> {
+    let mut titles = Vec::new();
+    for num in 1..=racer.get_num_episodes() {
+        racer.jump_to_episode(num).await.expect("Episode exists");
+        titles.push(racer.get_current_ep_title().expect("Episode is published"));
+    }
+    titles
+}
+
+fn shuffled(seed: u64, new_episodes: NewEpisodePlacement) -> Playlist {
+    Playlist { seed, new_episodes, ..Playlist::default() }
+}
+
+async fn create_shuffled(dir: &TestDir, url: &str, playlist: Playlist) -> FeedRacer {
+    let mut params = creation_params(dir, url, RacerType::Days(1), 1);
+    params.order = EpisodeOrder::Shuffle;
+    params.playlist = playlist;
+    create_racer_with_params(dir, params).await
+}
+
+#[test]
+fn shuffle_is_seeded_and_stable() {
+    let dir = TestDir::new();
+    let other_dir = TestDir::new();
+    let url = serve(fixture_rss(12), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_shuffled(&dir, &url, shuffled(42, NewEpisodePlacement::End)).await;
+        let mut same_seed = create_shuffled(&other_dir, &url, shuffled(42, NewEpisodePlacement::End)).await;
+        let order = titles(&mut racer).await;
+        assert_eq!(titles(&mut same_seed).await, order);
+
+        // Every episode, just not by date
+        let by_date: Vec<String> = (1..=12).map(|num| format!("Episode {}", num)).collect();
+        let mut sorted = order.clone();
+        sorted.sort_by_key(|title| title[8..].parse::<usize>().unwrap());
+        assert_eq!(sorted, by_date);
+        assert_ne!(order, by_date);
+
+        // Updates don't reshuffle
+        racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        let mut racer = reload(&dir, &racer);
+        assert_eq!(titles(&mut racer).await, order);
+
+        // A different seed gives a different order
+        racer.set_playback_order(EpisodeOrder::Shuffle, shuffled(7, NewEpisodePlacement::End)).await
+            .expect("Can reshuffle");
+        assert_ne!(titles(&mut racer).await, order);
+    });
+}
+
+#[test]
+fn new_episodes_go_at_the_end() {
+    let dir = TestDir::new();
+    let body = Arc::new(Mutex::new(first_episodes(11, 10)));
+    let url = serve_shared(body.clone(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_shuffled(&dir, &url, shuffled(3, NewEpisodePlacement::End)).await;
+        let order = titles(&mut racer).await;
+
+        *body.lock().unwrap() = fixture_rss(11);
+        racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        let mut expected = order.clone();
+        expected.push(String::from("Episode 11"));
+        assert_eq!(titles(&mut racer).await, expected);
+    });
+}
+
+#[test]
+fn new_episodes_interleave_after_published() {
+    let dir = TestDir::new();
+    let body = Arc::new(Mutex::new(first_episodes(14, 10)));
+    let url = serve_shared(body.clone(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_shuffled(&dir, &url, shuffled(3, NewEpisodePlacement::Interleave)).await;
+        let order = titles(&mut racer).await;
+        racer.jump_to_episode(4).await.expect("Episode exists");
+
+        *body.lock().unwrap() = fixture_rss(14);
+        racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        assert_eq!(racer.get_num_episodes(), 14);
+        assert_eq!(racer.get_num_to_publish(), 4);
+        let new_order = titles(&mut racer).await;
+
+        // What was already out is untouched, and the old episodes keep their order
+        assert_eq!(new_order[..4], order[..4]);
+        let old: Vec<&String> = new_order.iter().filter(|title| order.contains(title)).collect();
+        assert_eq!(old, order.iter().collect::<Vec<&String>>());
+    });
+}
+
+#[test]
+fn curated_order() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(6), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        let curated = Playlist {
+            curated: vec![String::from("episode-5"), String::from("episode-2")],
+            ..Playlist::default()
+        };
+        racer.set_playback_order(EpisodeOrder::Curated, curated).await.expect("Can curate");
+        assert_eq!(racer.get_episode_order(), EpisodeOrder::Curated);
+        assert_eq!(titles(&mut racer).await,
+                   vec!["Episode 5", "Episode 2", "Episode 1", "Episode 3", "Episode 4", "Episode 6"]);
+
+        // Typos are caught, and nothing changes
+        let typo = Playlist { curated: vec![String::from("episode-50")], ..Playlist::default() };
+        assert!(racer.set_playback_order(EpisodeOrder::Curated, typo).await.is_err());
+        assert_eq!(reload(&dir, &racer).get_playlist().curated, vec!["episode-5", "episode-2"]);
+    });
+}
@@ -0,0 +1,190 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   schedule.rs
+//
+//  © Zach Nielsen 2020
+//  Timezones, publish times and pushing release dates out of excluded windows
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use racer::{BlackoutRange, FeedRacer, QuietHours, RacerType, ScheduleConstraints};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const TZ: Tz = chrono_tz::America::Los_Angeles;
+
+fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+    TZ.with_ymd_and_hms(y, m, d, h, min, 0).single().expect("Unambiguous local time")
+}
+
+fn time(h: u32, min: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, min, 0).unwrap()
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+// Release dates of a few episodes still to come, starting at `first`
+fn release_dates(racer: &FeedRacer, first: usize) -> Vec<DateTime<chrono::Utc>> {
+    (first..first + 5).map(|num| racer.get_episode_pub_date(num)).collect()
+}
+
+fn skip_weekends() -> ScheduleConstraints {
+    ScheduleConstraints { skip_weekends: true, ..Default::default() }
+}
+
+#[test]
+fn weekends_push_to_monday() {
+    let constraints = skip_weekends();
+    // Saturday January 9th, 2021
+    let saturday = local(2021, 1, 9, 15, 0);
+    assert_eq!(constraints.next_allowed(saturday, Some(time(9, 0))), local(2021, 1, 11, 9, 0));
+    // Without a publish time, the day starts at midnight
+    assert_eq!(constraints.next_allowed(saturday, None), local(2021, 1, 11, 0, 0));
+    // Weekdays are left alone
+    let friday = local(2021, 1, 8, 23, 30);
+    assert_eq!(constraints.next_allowed(friday, Some(time(9, 0))), friday);
+}
+
+#[test]
+fn blackouts_spanning_a_weekend_push_past_both() {
+    // Friday through Monday is blacked out, and the weekend is skipped anyway
+    let constraints = ScheduleConstraints {
+        blackouts: vec![BlackoutRange { start: date(2021, 1, 8), end: date(2021, 1, 11) }],
+        ..skip_weekends()
+    };
+    let tuesday = local(2021, 1, 12, 9, 0);
+    assert_eq!(constraints.next_allowed(local(2021, 1, 8, 10, 0), Some(time(9, 0))), tuesday);
+    assert_eq!(constraints.next_allowed(local(2021, 1, 10, 10, 0), Some(time(9, 0))), tuesday);
+
+    // A blackout ending on Friday runs straight into the weekend
+    let constraints = ScheduleConstraints {
+        blackouts: vec![BlackoutRange { start: date(2021, 1, 6), end: date(2021, 1, 8) }],
+        ..skip_weekends()
+    };
+    assert_eq!(constraints.next_allowed(local(2021, 1, 6, 10, 0), Some(time(9, 0))), local(2021, 1, 11, 9, 0));
+}
+
+#[test]
+fn dst_gaps_are_stepped_over() {
+    // Clocks jump from 2:00 to 3:00 on Sunday March 14th, 2021, so 2:30 doesn't exist that day
+    let constraints = ScheduleConstraints {
+        blackouts: vec![BlackoutRange { start: date(2021, 3, 13), end: date(2021, 3, 13) }],
+        ..Default::default()
+    };
+    let pushed = constraints.next_allowed(local(2021, 3, 13, 12, 0), Some(time(2, 30)));
+    assert_eq!(pushed, local(2021, 3, 14, 3, 30));
+    assert_eq!(pushed.date_naive(), date(2021, 3, 14));
+
+    // Quiet hours ending inside the gap land just after it
+    let constraints = ScheduleConstraints {
+        quiet_hours: Some(QuietHours { start: time(1, 0), end: time(2, 30) }),
+        ..Default::default()
+    };
+    assert_eq!(constraints.next_allowed(local(2021, 3, 14, 1, 30), None), local(2021, 3, 14, 3, 30));
+}
+
+#[test]
+fn episodes_pile_up_on_monday_in_order() {
+    let constraints = skip_weekends();
+    let day_start = Some(time(9, 0));
+    let monday = local(2021, 1, 11, 9, 0);
+    let dates = [
+        local(2021, 1, 8, 17, 0),
+        local(2021, 1, 9, 8, 0),
+        local(2021, 1, 9, 20, 0),
+        local(2021, 1, 10, 12, 0),
+        local(2021, 1, 11, 9, 0),
+        local(2021, 1, 11, 10, 0),
+    ];
+    let pushed: Vec<DateTime<Tz>> = dates.iter().map(|date| constraints.next_allowed(*date, day_start)).collect();
+
+    // Everything from the weekend drops together on Monday morning, and nothing is reordered
+    assert_eq!(pushed[0], dates[0]);
+    assert!(pushed[1..5].iter().all(|date| *date == monday));
+    assert_eq!(pushed[5], dates[5]);
+    assert!(pushed.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+fn releases_snap_to_the_local_publish_time() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(30), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        // Snapping can hold back the episode that just came out, so compare the same episodes
+        let first = racer.get_next_episode_num();
+        let raw_dates = release_dates(&racer, first);
+
+        let tokyo = chrono_tz::Asia::Tokyo;
+        racer.set_publish_schedule("Asia/Tokyo", Some(time(6, 30))).await.expect("Can set the schedule");
+        assert_eq!(racer.get_timezone(), tokyo);
+        assert_eq!(racer.get_publish_time(), Some(time(6, 30)));
+
+        // Every release moves forward to the next 6:30 in Tokyo, which is 21:30 UTC
+        for (raw, snapped) in raw_dates.iter().zip(release_dates(&racer, first)) {
+            assert_eq!(snapped.with_timezone(&tokyo).time(), time(6, 30));
+            assert_eq!(snapped.time(), time(21, 30));
+            assert!(*raw <= snapped && snapped < *raw + chrono::Duration::days(1), "{} snapped to {}", raw, snapped);
+        }
+
+        // Survives a reload
+        let reloaded = reload(&dir, &racer);
+        assert_eq!(release_dates(&reloaded, first), release_dates(&racer, first));
+    });
+}
+
+#[test]
+fn unknown_timezones_fall_back_to_the_default() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(30), "application/rss+xml");
+    runtime().block_on(async {
+        let default_tz: Tz = racer::DEFAULT_TIMEZONE.parse().unwrap();
+        let mut params = creation_params(&dir, &url, RacerType::Days(1), 1);
+        params.timezone = String::from("Mars/Olympus_Mons");
+        params.publish_time = Some(time(6, 30));
+        let mut racer = create_racer_with_params(&dir, params).await;
+
+        // A bad saved timezone still renders, at the publish time in the default timezone
+        assert_eq!(racer.get_timezone(), default_tz);
+        for date in release_dates(&racer, racer.get_next_episode_num()) {
+            assert_eq!(date.with_timezone(&default_tz).time(), time(6, 30));
+        }
+
+        // Setting one is refused outright, and nothing changes
+        assert!(racer.set_publish_schedule("Not/A_Zone", Some(time(9, 0))).await.is_err());
+        assert_eq!(racer.get_timezone(), default_tz);
+        assert_eq!(racer.get_publish_time(), Some(time(6, 30)));
+    });
+}
+
+#[test]
+fn publishing_now_puts_out_one_episode_at_the_publish_time() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(30), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(1), 1).await;
+        racer.set_publish_schedule("Asia/Tokyo", Some(time(6, 30))).await.expect("Can set the schedule");
+        racer.set_schedule_constraints(skip_weekends()).await.expect("Can set the constraints");
+
+        // Snapping and weekends don't move release dates one for one with the anchor, so each
+        // publish should still put out exactly one more
+        for _ in 0..8 {
+            let published = racer.get_num_to_publish();
+            racer.publish_next_ep_now().await;
+            assert_eq!(racer.get_num_to_publish(), published + 1);
+            assert_eq!(reload(&dir, &racer).get_num_to_publish(), published + 1);
+        }
+    });
+}
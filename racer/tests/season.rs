@@ -0,0 +1,108 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   season.rs
+//
+//  © Zach Nielsen 2020
+//  Racing season by season
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use chrono::Duration;
+use common::*;
+use racer::{FeedRacer, RacerType, SeasonConfig, SeasonRate, SeasonSource};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+const NUM_ITEMS: usize = 12;
+
+// Seasons of four episodes. Only the first episode of each season is tagged.
+fn fixture() -> String {
+    with_item_extras(fixture_rss(NUM_ITEMS), &[
+        (1, "<itunes:season>1</itunes:season>"),
+        (5, "<itunes:season>2</itunes:season>"),
+        (9, "<itunes:season>3</itunes:season>"),
+    ])
+}
+
+// Time between episode `num` and the one before it (0 indexed)
+fn gap_before(racer: &FeedRacer, num: usize) -> Duration {
+    racer.get_episode_pub_date(num).signed_duration_since(racer.get_episode_pub_date(num - 1))
+}
+
+#[test]
+fn per_season_rates_and_breaks() {
+    let dir = TestDir::new();
+    let url = serve(fixture(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, &url, RacerType::Days(7), 1);
+        params.seasons = Some(SeasonConfig {
+            source: SeasonSource::ITunes,
+            rates: vec![SeasonRate { season: 2, rate: RacerType::Days(2) }],
+            gap_days: 10,
+        });
+        let mut racer = create_racer_with_params(&dir, params).await;
+        // Put everything in the future so we can see the release dates
+        racer.rewind_by_episodes(NUM_ITEMS).await;
+
+        assert_eq!(gap_before(&racer, 1), Duration::days(7));
+        // Last step of season 1 is at season 1's rate, then the break
+        assert_eq!(gap_before(&racer, 4), Duration::days(7 + 10));
+        assert_eq!(gap_before(&racer, 5), Duration::days(2));
+        assert_eq!(gap_before(&racer, 8), Duration::days(2 + 10));
+        assert_eq!(gap_before(&racer, 9), Duration::days(7));
+    });
+}
+
+#[test]
+fn start_from_a_season() {
+    let dir = TestDir::new();
+    let url = serve(fixture(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, &url, RacerType::Ratio(1.0), 1);
+        params.seasons = Some(SeasonConfig {
+            source: SeasonSource::ITunes,
+            rates: Vec::new(),
+            gap_days: 0,
+        });
+        params.start_season = Some(2);
+        let racer = create_racer_with_params(&dir, params).await;
+        assert_eq!(racer.get_num_to_publish(), 5);
+        assert_eq!(racer.get_current_ep_title(), Some(String::from("Episode 5")));
+    });
+}
+
+#[test]
+fn seasons_from_title_pattern() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(NUM_ITEMS), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Days(7), 1).await;
+        racer.rewind_by_episodes(NUM_ITEMS).await;
+
+        // Every episode is its own season here
+        racer.set_season_config(Some(SeasonConfig {
+            source: SeasonSource::TitlePattern(String::from(r"Episode (\d+)")),
+            rates: Vec::new(),
+            gap_days: 1,
+        })).await.expect("Config is valid");
+        assert_eq!(racer.get_num_to_publish(), 0);
+        assert_eq!(gap_before(&racer, 3), Duration::days(8));
+
+        let res = racer.set_season_config(Some(SeasonConfig {
+            source: SeasonSource::TitlePattern(String::from(r"Episode \d+")),
+            rates: Vec::new(),
+            gap_days: 1,
+        })).await;
+        assert!(res.is_err(), "Patterns need a capture group");
+
+        racer.set_season_config(None).await.expect("Can turn seasons off");
+        assert_eq!(gap_before(&racer, 3), Duration::days(7));
+    });
+}
@@ -0,0 +1,203 @@
+////////////////////////////////////////////////////////////////////////////////
+//  File:   sources.rs
+//
+//  © Zach Nielsen 2020
+//  Racers built from more than one upstream feed
+//
+
+////////////////////////////////////////////////////////////////////////////////
+//  Included Modules
+////////////////////////////////////////////////////////////////////////////////
+mod common;
+
+////////////////////////////////////////////////////////////////////////////////
+//  Namespaces
+////////////////////////////////////////////////////////////////////////////////
+use common::*;
+use racer::{FeedRacer, RacerType, RssFile};
+
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+////////////////////////////////////////////////////////////////////////////////
+//  Code
+////////////////////////////////////////////////////////////////////////////////
+
+// A fixture feed with only some of its episodes left in
+fn only_episodes(rss: String, keep: RangeInclusive<usize>) -> String {
+    rss.lines()
+        .filter(|line| match line.strip_prefix("<item><title>Episode ") {
+            Some(rest) => {
+                let num: usize = rest.split('<').next().unwrap().parse().unwrap();
+                keep.contains(&num)
+            },
+            None => true,
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+async fn titles(racer: &mut FeedRacer) -> Vec<String> {
+    let mut titles = Vec::new();
+    for num in 1..=racer.get_num_episodes() {
+        racer.jump_to_episode(num).await.expect("Episode exists");
+        titles.push(racer.get_current_ep_title().expect("Episode is published"));
+    }
+    titles
+}
+
+#[test]
+fn sources_are_merged_without_duplicates() {
+    let dir = TestDir::new();
+    // The current host only has the recent episodes. Episode 6 is on both.
+    let main_url = serve(only_episodes(fixture_rss(10), 6..=10), "application/rss+xml");
+    let archive_url = serve(only_episodes(fixture_rss(10), 1..=6), "application/rss+xml");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, &main_url, RacerType::Ratio(1.0), 1);
+        params.extra_urls = vec![archive_url.clone()];
+        let mut racer = create_racer_with_params(&dir, params).await;
+
+        let expected: Vec<String> = (1..=10).map(|num| format!("Episode {}", num)).collect();
+        assert_eq!(racer.get_num_episodes(), 10);
+        assert_eq!(titles(&mut racer).await, expected);
+
+        let sources = racer.get_sources();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].url, main_url);
+        assert_eq!(sources[0].num_items, 5);
+        assert_eq!(sources[1].num_items, 6);
+        assert!(sources.iter().all(|source| source.last_success.is_some() && source.last_error.is_none()));
+    });
+}
+
+#[test]
+fn archive_keeps_episodes_that_drop_off_upstream() {
+    let dir = TestDir::new();
+    let body = Arc::new(Mutex::new(only_episodes(fixture_rss(11), 1..=10)));
+    let url = serve_shared(body.clone(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &url, RacerType::Ratio(1.0), 1).await;
+        assert_eq!(racer.get_num_episodes(), 10);
+
+        // A new episode comes out and the feed only lists the newest 8
+        *body.lock().unwrap() = only_episodes(fixture_rss(11), 4..=11);
+        let new_episodes = racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        assert!(new_episodes);
+        assert_eq!(racer.get_num_episodes(), 11);
+        assert_eq!(racer.get_sources()[0].num_items, 11);
+    });
+}
+
+#[test]
+fn failing_source_is_reported_and_skipped() {
+    let dir = TestDir::new();
+    let main_url = serve(only_episodes(fixture_rss(10), 6..=10), "application/rss+xml");
+    let archive = Arc::new(Mutex::new(only_episodes(fixture_rss(10), 1..=5)));
+    let archive_url = serve_shared(archive.clone(), "application/rss+xml");
+    runtime().block_on(async {
+        let mut params = creation_params(&dir, &main_url, RacerType::Ratio(1.0), 1);
+        params.extra_urls = vec![archive_url];
+        let mut racer = create_racer_with_params(&dir, params).await;
+
+        // The archive host goes down. Its episodes stay, and the error is noted.
+        *archive.lock().unwrap() = String::from("Service unavailable");
+        racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        assert_eq!(racer.get_num_episodes(), 10);
+        let racer = reload(&dir, &racer);
+        assert!(racer.get_sources()[0].last_error.is_none());
+        assert!(racer.get_sources()[1].last_error.is_some());
+
+        // And clears once it's back
+        let mut racer = racer;
+        *archive.lock().unwrap() = only_episodes(fixture_rss(10), 1..=5);
+        racer.update(&RssFile::Download, &reqwest::Client::new()).await.expect("Updates");
+        assert!(racer.get_sources()[1].last_error.is_none());
+    });
+}
+
+#[test]
+fn changing_sources_keeps_latest_episode() {
+    let dir = TestDir::new();
+    let main_url = serve(only_episodes(fixture_rss(10), 6..=10), "application/rss+xml");
+    let archive_url = serve(only_episodes(fixture_rss(10), 1..=5), "application/rss+xml");
+    let bad_url = serve(String::from("Not a feed"), "text/plain");
+    let client = reqwest::Client::new();
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &main_url, RacerType::Ratio(1.0), 1).await;
+        racer.jump_to_episode(3).await.expect("Episode exists");
+        assert_eq!(racer.get_current_ep_title().unwrap(), "Episode 8");
+
+        racer.set_sources(&[main_url.clone(), archive_url.clone()], None, &client).await.expect("Can add a source");
+        assert_eq!(racer.get_num_episodes(), 10);
+        assert_eq!(racer.get_num_to_publish(), 8);
+        assert_eq!(racer.get_current_ep_title().unwrap(), "Episode 8");
+
+        // Sources that don't download are turned away
+        assert!(racer.set_sources(&[main_url.clone(), bad_url], None, &client).await.is_err());
+        assert!(racer.set_sources(&[String::from(" ")], None, &client).await.is_err());
+        assert_eq!(racer.get_sources().len(), 2);
+
+        racer.set_sources(std::slice::from_ref(&main_url), None, &client).await.expect("Can drop a source");
+        assert_eq!(racer.get_num_episodes(), 5);
+        assert_eq!(racer.get_current_ep_title().unwrap(), "Episode 8");
+        assert_eq!(reload(&dir, &racer).get_sources().len(), 1);
+    });
+}
+
+#[test]
+fn new_sources_are_downloaded_once() {
+    let dir = TestDir::new();
+    let main_url = serve(only_episodes(fixture_rss(10), 6..=10), "application/rss+xml");
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let archive_fetches = fetches.clone();
+    let archive_rss = only_episodes(fixture_rss(10), 1..=5);
+    let archive_url = serve_with(move |_| {
+        archive_fetches.fetch_add(1, Ordering::SeqCst);
+        full_response(archive_rss.clone().into_bytes(), "application/rss+xml")
+    });
+    let client = reqwest::Client::new();
+    runtime().block_on(async {
+        let mut racer = create_racer(&dir, &main_url, RacerType::Ratio(1.0), 1).await;
+        racer.set_sources(&[main_url.clone(), archive_url.clone()], None, &client).await.expect("Can add a source");
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(racer.get_num_episodes(), 10);
+        assert_eq!(racer.get_sources()[1].num_items, 5);
+        assert!(racer.get_sources()[1].last_success.is_some());
+    });
+}
+
+#[test]
+fn legacy_racers_migrate_to_one_archive() {
+    let dir = TestDir::new();
+    let url = serve(fixture_rss(5), "application/rss+xml");
+    let racer = runtime().block_on(create_racer(&dir, &url, RacerType::Days(3), 1));
+    let racer_path = racer.get_racer_path().to_owned();
+    let archives = || -> Vec<String> {
+        std::fs::read_dir(&racer_path).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("source_"))
+            .collect()
+    };
+    for archive in archives() {
+        std::fs::remove_file(racer_path.join(archive)).unwrap();
+    }
+
+    // Write the racer file the way 1.2 racers looked, with a single source_url
+    let racer_file = racer_path.join(racer::RACER_FILE);
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&racer_file).unwrap()).unwrap();
+    json["schema_version"] = serde_json::json!("1.2");
+    json["source_url"] = serde_json::json!(url);
+    json.as_object_mut().unwrap().remove("sources");
+    std::fs::write(&racer_file, json.to_string()).unwrap();
+
+    // Loading without saving, over and over, leaves one archive behind
+    for _ in 0..3 {
+        let racer = reload(&dir, &racer);
+        assert_eq!(racer.get_sources().len(), 1);
+        assert_eq!(racer.get_sources()[0].url, url);
+        assert_eq!(racer.get_sources()[0].archive_file, racer::MIGRATED_SOURCE_ARCHIVE_FILE);
+    }
+    assert_eq!(archives(), vec![String::from(racer::MIGRATED_SOURCE_ARCHIVE_FILE)]);
+}
@@ -41,6 +41,13 @@ struct PodRacerRocketConfig {
     podracer_dir: String,
     host: String,
     port: u32,
+    // Tera template file for the footer on every episode. Optional.
+    #[serde(default)]
+    footer_template: Option<String>,
+    // Directory that feeds made from local audio files (and podarch archives) have to be in.
+    // Optional - without it, feeds can't use directories on this machine.
+    #[serde(default)]
+    local_media_root: Option<String>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -71,6 +78,10 @@ async fn rocket() -> rocket::Rocket<rocket::Build> {
         .mount("/", routes![update_all_handler])
         .mount("/", routes![list_feeds_handler])
         .mount("/", routes![serve_rss_handler])
+        .mount("/", routes![serve_atom_handler])
+        .mount("/", routes![serve_json_feed_handler])
+        .mount("/", routes![serve_artwork_handler])
+        .mount("/", routes![serve_media_handler])
         .mount("/", routes![create_feed_handler])
         .mount("/", routes![create_feed_cli_handler])
         .mount("/", routes![create_feed_cli_ep_handler])
@@ -83,6 +94,7 @@ async fn rocket() -> rocket::Rocket<rocket::Build> {
                 podracer_dir: config_for_closure.podracer_dir,
                 host: config_for_closure.host,
                 port: config_for_closure.port,
+                local_media_root: config_for_closure.local_media_root,
             };
 
             // Add custom configs to the State manager - only one of each type is allowed
@@ -91,12 +103,44 @@ async fn rocket() -> rocket::Rocket<rocket::Build> {
                 .manage(routes::UpdateFactor(config_for_closure.update_factor))
         }));
 
+    // Feeds link back to this server from their episode footers
+    let footer_template = match &custom_config.footer_template {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(val) => match racer::validate_footer_template(&val) {
+                Ok(_) => Some(val),
+                Err(e) => {
+                    println!("Error in footer template {}, using the default: {}", path, e);
+                    None
+                },
+            },
+            Err(e) => {
+                println!("Error reading footer template {}, using the default: {}", path, e);
+                None
+            },
+        },
+        None => None,
+    };
+    let footer_settings = racer::FooterSettings {
+        host: custom_config.host.clone(),
+        template: footer_template,
+    };
+    match racer::write_footer_settings(&custom_config.podracer_dir, &footer_settings) {
+        Ok(_) => (),
+        Err(e) => println!("Error saving footer settings: {}", e),
+    };
+
     let client = reqwest::Client::new();
+    // Catch up on any scheduled pauses we missed while down
+    match racer::apply_all_scheduled_pauses(&custom_config.podracer_dir).await {
+        Ok(num_changed) => println!("Applied scheduled pauses on boot. {} feeds paused or resumed.", num_changed),
+        Err(string) => println!("Error in apply_all_scheduled_pauses on boot: {}", string),
+    };
     // Manually update on start
     match racer::update_all(&custom_config.podracer_dir, &client).await {
         Ok(update_metadata) => println!(
-            "Manually updated on boot. Did {} feeds in {:?} ({} feeds with new episodes).",
-            update_metadata.num_updated, update_metadata.time, update_metadata.num_with_new_eps
+            "Manually updated on boot. Did {} feeds in {:?} ({} feeds with new episodes, {} caught up).",
+            update_metadata.num_updated, update_metadata.time, update_metadata.num_with_new_eps,
+            update_metadata.num_caught_up
         ),
         Err(string) => println!("Error in update_all on boot: {}", string),
     };
@@ -109,14 +153,25 @@ async fn rocket() -> rocket::Rocket<rocket::Build> {
         let client = reqwest::Client::new();
         loop {
             std::thread::sleep(std::time::Duration::from_secs(duration as u64));
+            match racer::apply_all_scheduled_pauses(&custom_config.podracer_dir).await {
+                Ok(num_changed) => {
+                    if num_changed > 0 {
+                        println!("Applied scheduled pauses. {} feeds paused or resumed.", num_changed);
+                    }
+                }
+                Err(string) => {
+                    println!("Error in apply_all_scheduled_pauses in update thread: {}", string);
+                }
+            };
             print!("Updating all feeds... ");
             match racer::update_all(&custom_config.podracer_dir, &client).await {
                 Ok(update_metadata) => {
                     println!(
-                        "Done. Did {} feeds in {:?} ({} feeds with new episodes).",
+                        "Done. Did {} feeds in {:?} ({} feeds with new episodes, {} caught up).",
                         update_metadata.num_updated,
                         update_metadata.time,
-                        update_metadata.num_with_new_eps
+                        update_metadata.num_with_new_eps,
+                        update_metadata.num_caught_up
                     );
                 }
                 Err(string) => {
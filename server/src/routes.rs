@@ -13,9 +13,12 @@
 ////////////////////////////////////////////////////////////////////////////////
 use super::racer;
 
-use rocket::response::Redirect;
+use rocket::response::{self, Redirect, Responder, Response};
+use rocket::response::stream::ReaderStream;
+use rocket::request::{self, FromRequest};
 use rocket::serde::uuid::Uuid;
 use rocket::form::Form;
+use rocket::http::{ContentType, Status};
 use rocket::fs::NamedFile;
 use rocket::{Request, State};
 
@@ -44,6 +47,7 @@ pub struct RocketConfig {
     pub podracer_dir: String,
     pub host: String,
     pub port: u32,
+    pub local_media_root: Option<String>,
 }
 pub struct UpdateFactor(pub u32);
 
@@ -60,9 +64,26 @@ struct FeedFunFacts {
 #[derive(FromForm)]
 pub struct CreateFeedForm {
     pub url: String,
+    // More feeds to merge in, one url per line
+    pub extra_urls: Option<String>,
     pub rate_ratio: Option<f64>,
     pub rate_days: Option<u32>,
+    // Mirror the feed this many days late instead of racing it
+    pub rate_delay: Option<u32>,
     pub start_ep: usize,
+    pub timezone: Option<String>,
+    pub publish_time: Option<String>,
+    pub season_source: Option<String>,
+    pub season_pattern: Option<String>,
+    pub season_gap_days: Option<u32>,
+    pub season_rates: Option<String>,
+    pub start_season: Option<u32>,
+    pub order: Option<String>,
+    pub shuffle_seed: Option<u64>,
+    pub curated_guids: Option<String>,
+    pub new_episodes: Option<String>,
+    pub catch_up: Option<String>,
+    pub catch_up_delay_days: Option<u32>,
 }
 
 #[derive(FromFormField)]
@@ -70,6 +91,7 @@ pub enum FeedAction {
     EditFeed,
     EditRate,
     EditRateDays,
+    EditRateDelay,
     Pause,
     Unpause,
     FastForwardDays,
@@ -77,6 +99,26 @@ pub enum FeedAction {
     FastForwardEps,
     RewindEps,
     PublishNextEp,
+    JumpToEpisode,
+    JumpToDate,
+    EditPublishSchedule,
+    EditConstraints,
+    AddBlackout,
+    RemoveBlackout,
+    SchedulePause,
+    CancelScheduledPause,
+    EditExclusions,
+    EditEpisodeTypes,
+    EditSeasons,
+    EditOrder,
+    EditSources,
+    EditCatchUp,
+    EditFooter,
+    EditTitleAndArtwork,
+    EditGuids,
+    EditEnclosures,
+    EditArchive,
+    Undo,
 }
 
 #[derive(FromForm)]
@@ -87,7 +129,107 @@ pub struct EditFeedForm {
     pub rate_days: Option<u32>,
     #[field(validate = with(|rate| rate.unwrap_or(0.0) > 0.0 || *rate == None, "rate must be > 0"))]
     pub rate_ratio: Option<f64>,
-    pub next_episode_num: Option<usize>,
+    pub rate_delay: Option<u32>,
+    pub episode_num: Option<usize>,
+    pub jump_date: Option<String>,
+    pub timezone: Option<String>,
+    pub publish_time: Option<String>,
+    pub quiet_start: Option<String>,
+    pub quiet_end: Option<String>,
+    pub skip_weekends: bool,
+    pub blackout_start: Option<String>,
+    pub blackout_end: Option<String>,
+    pub blackout_idx: Option<usize>,
+    pub pause_start: Option<String>,
+    pub pause_end: Option<String>,
+    pub pause_idx: Option<usize>,
+    pub exclude_guids: Option<String>,
+    pub exclude_titles: Option<String>,
+    pub exclude_min_duration: Option<u64>,
+    pub skip_trailers: bool,
+    pub bonus_with_neighbour: bool,
+    pub season_source: Option<String>,
+    pub season_pattern: Option<String>,
+    pub season_gap_days: Option<u32>,
+    pub season_rates: Option<String>,
+    pub order: Option<String>,
+    pub shuffle_seed: Option<u64>,
+    pub curated_guids: Option<String>,
+    pub new_episodes: Option<String>,
+    pub source_urls: Option<String>,
+    pub catch_up: Option<String>,
+    pub catch_up_delay_days: Option<u32>,
+    // "server", "custom" or "none"
+    pub footer_mode: Option<String>,
+    pub footer_template: Option<String>,
+    // "suffix", "custom" or "original"
+    pub title_style: Option<String>,
+    pub custom_title: Option<String>,
+    pub next_episode_line: bool,
+    pub artwork_url: Option<String>,
+    // "off", "rate", "podracer" or "text"
+    pub badge: Option<String>,
+    pub badge_text: Option<String>,
+    pub namespace_guids: bool,
+    pub proxy_enclosures: bool,
+    // A podarch directory, blank for none
+    pub archive_dir: Option<String>,
+}
+
+// The Range header of a request, if it has one
+pub struct RangeHeader(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RangeHeader {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(RangeHeader(req.headers().get_one("Range").map(str::to_owned)))
+    }
+}
+
+// Episode audio, from an archived copy or streamed from upstream
+pub struct MediaResponse(racer::Media);
+
+impl<'r> Responder<'r, 'static> for MediaResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let media = self.0;
+        let mut response = Response::build();
+        response.status(Status::new(media.status));
+        response.raw_header("Content-Type", media.mime_type);
+        if let Some(content_range) = media.content_range {
+            response.raw_header("Content-Range", content_range);
+        }
+        if media.accept_ranges {
+            response.raw_header("Accept-Ranges", "bytes");
+        }
+        // Streamed bodies don't get a length of their own, and players need one to seek
+        if let Some(content_length) = media.content_length {
+            response.raw_header("Content-Length", content_length.to_string());
+        }
+        match media.body {
+            racer::MediaBody::File(file) => {
+                response.streamed_body(file);
+            },
+            racer::MediaBody::Upstream(upstream) => {
+                let chunks = rocket::futures::stream::unfold(upstream, |mut upstream| async move {
+                    match upstream.chunk().await {
+                        Ok(Some(chunk)) => Some((std::io::Cursor::new(chunk), upstream)),
+                        Ok(None) => None,
+                        Err(e) => {
+                            println!("Error streaming media: {}", e);
+                            None
+                        },
+                    }
+                });
+                response.streamed_body(ReaderStream::from(Box::pin(chunks)));
+            },
+            racer::MediaBody::Empty => {
+                response.sized_body(0, std::io::Cursor::new(Vec::new()));
+            },
+        }
+        response.ok()
+    }
 }
 
 //
@@ -149,7 +291,7 @@ pub async fn create_feed_form_handler(config: &State<RocketConfig>) -> NamedFile
 pub async fn create_feed_handler(config: &State<RocketConfig>, form_data: Form<CreateFeedForm>) -> Template {
     let mut context = Context::new();
 
-    match rate_validator(&form_data.rate_ratio, &form_data.rate_days) {
+    match rate_validator(&form_data.rate_ratio, &form_data.rate_days, &form_data.rate_delay) {
         Ok(_) => (),
         Err(e) => {
             println!("Error validating rate: {}", &e);
@@ -161,9 +303,61 @@ pub async fn create_feed_handler(config: &State<RocketConfig>, form_data: Form<C
     let rate = if form_data.rate_ratio.is_some() {
         racer::RacerType::Ratio(form_data.rate_ratio.unwrap())
     }
+    else if form_data.rate_delay.is_some() {
+        racer::RacerType::Delay(form_data.rate_delay.unwrap())
+    }
     else {
         racer::RacerType::Days(form_data.rate_days.unwrap())
     };
+    let (timezone, publish_time) = match publish_schedule_validator(&form_data.timezone, &form_data.publish_time) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("Error validating publish schedule: {}", &e);
+            let mut ctx = Context::new();
+            ctx.insert("text", &e);
+            return Template::render(GENERIC_TEXT_FILE, ctx.into_json());
+        },
+    };
+
+    let seasons = match season_config_validator(&form_data.season_source, &form_data.season_pattern,
+                                                &form_data.season_gap_days, &form_data.season_rates) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("Error validating seasons: {}", &e);
+            let mut ctx = Context::new();
+            ctx.insert("text", &e);
+            return Template::render(GENERIC_TEXT_FILE, ctx.into_json());
+        },
+    };
+
+    let order = match episode_order_validator(&form_data.order) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("Error validating episode order: {}", &e);
+            let mut ctx = Context::new();
+            ctx.insert("text", &e);
+            return Template::render(GENERIC_TEXT_FILE, ctx.into_json());
+        },
+    };
+    let playlist = match playlist_validator(&form_data.shuffle_seed, &form_data.curated_guids,
+                                            &form_data.new_episodes, None) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("Error validating playlist: {}", &e);
+            let mut ctx = Context::new();
+            ctx.insert("text", &e);
+            return Template::render(GENERIC_TEXT_FILE, ctx.into_json());
+        },
+    };
+    let catch_up_policy = match catch_up_validator(&form_data.catch_up, &form_data.catch_up_delay_days) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("Error validating catch up policy: {}", &e);
+            let mut ctx = Context::new();
+            ctx.insert("text", &e);
+            return Template::render(GENERIC_TEXT_FILE, ctx.into_json());
+        },
+    };
 
     match create_feed(
         racer::RacerCreationParams {
@@ -174,6 +368,15 @@ pub async fn create_feed_handler(config: &State<RocketConfig>, form_data: Form<C
             rate,
             port: config.port,
             url: form_data.url.clone(),
+            extra_urls: lines_from_form(&form_data.extra_urls),
+            timezone,
+            publish_time,
+            start_season: if seasons.is_some() { form_data.start_season } else { None },
+            seasons,
+            order,
+            playlist,
+            catch_up_policy,
+            local_media_root: config.local_media_root.clone(),
         },
         &reqwest::Client::new()
     ).await {
@@ -197,12 +400,13 @@ pub async fn create_feed_handler(config: &State<RocketConfig>, form_data: Form<C
         }
     }
 }
-fn rate_validator(rate_ratio: &Option<f64>, rate_days: &Option<u32>) -> Result<(), String> {
-    if rate_ratio.is_none() && rate_days.is_none() {
-        return Err(format!("Error validating rate: Must specify a Rate in either a Ratio or Days"));
+fn rate_validator(rate_ratio: &Option<f64>, rate_days: &Option<u32>, rate_delay: &Option<u32>) -> Result<(), String> {
+    let num_rates = [rate_ratio.is_some(), rate_days.is_some(), rate_delay.is_some()].iter().filter(|set| **set).count();
+    if num_rates == 0 {
+        return Err(format!("Error validating rate: Must specify a Rate in either a Ratio, Days or Delay"));
     }
-    if rate_ratio.is_some() && rate_days.is_some() {
-        return Err(format!("Error validating rate: Must only specify one Rate, in either a Ratio or Days"));
+    if num_rates > 1 {
+        return Err(format!("Error validating rate: Must only specify one Rate, in either a Ratio, Days or Delay"));
     }
     if let Some(rate) = rate_ratio {
         if *rate <= 0.0 {
@@ -214,9 +418,260 @@ fn rate_validator(rate_ratio: &Option<f64>, rate_days: &Option<u32>) -> Result<(
             return Err(format!("Error validating rate: rate must be > 0"));
         }
     }
+    if let Some(delay) = rate_delay {
+        if *delay == 0 {
+            return Err(String::from("Error validating rate: delay must be > 0 days"));
+        }
+    }
 
     Ok(())
 }
+fn publish_schedule_validator(timezone: &Option<String>, publish_time: &Option<String>)
+    -> Result<(String, Option<chrono::NaiveTime>), String> {
+    let timezone = match timezone {
+        Some(tz) if !tz.trim().is_empty() => tz.trim().to_owned(),
+        _ => racer::DEFAULT_TIMEZONE.to_owned(),
+    };
+    if let Err(e) = timezone.parse::<chrono_tz::Tz>() {
+        return Err(format!("Error validating timezone: {}", e));
+    }
+    let publish_time = match publish_time {
+        Some(time) if !time.trim().is_empty() => {
+            match chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M") {
+                Ok(val) => Some(val),
+                Err(e) => return Err(format!("Error validating publish time {}: {}", time, e)),
+            }
+        },
+        _ => None,
+    };
+
+    Ok((timezone, publish_time))
+}
+fn quiet_hours_validator(quiet_start: &Option<String>, quiet_end: &Option<String>)
+    -> Result<Option<racer::QuietHours>, String> {
+    let parse = |time: &Option<String>| -> Result<Option<chrono::NaiveTime>, String> {
+        match time {
+            Some(time) if !time.trim().is_empty() => {
+                match chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M") {
+                    Ok(val) => Ok(Some(val)),
+                    Err(e) => Err(format!("Error validating quiet hours {}: {}", time, e)),
+                }
+            },
+            _ => Ok(None),
+        }
+    };
+    match (parse(quiet_start)?, parse(quiet_end)?) {
+        (Some(start), Some(end)) => {
+            if start == end {
+                return Err(String::from("Error validating quiet hours: start and end must differ"));
+            }
+            Ok(Some(racer::QuietHours { start, end }))
+        },
+        (None, None) => Ok(None),
+        _ => Err(String::from("Error validating quiet hours: must specify both a start and an end")),
+    }
+}
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   season_config_validator
+//
+//  NOTES:
+//      Builds a season config from form fields. Rates are a list like "1: 2x, 3: 7d" - a
+//      multiplier or days between episodes for each season that shouldn't use the feed's rate.
+//  ARGS:
+//      source - "itunes", "title" or nothing to race without seasons
+//      pattern - For "title", a regex with a capture group for the season number
+//      gap_days - Days of break between seasons
+//      rates - Per-season rates
+//  RETURN: The config, None if not racing by season, or an error describing the bad field
+//
+fn season_config_validator(source: &Option<String>, pattern: &Option<String>, gap_days: &Option<u32>,
+                           rates: &Option<String>) -> Result<Option<racer::SeasonConfig>, String> {
+    let source = match source.as_deref().map(|source| source.trim()) {
+        Some("itunes") => racer::SeasonSource::ITunes,
+        Some("title") => match pattern.as_deref().map(|pattern| pattern.trim()) {
+            Some(pattern) if !pattern.is_empty() => racer::SeasonSource::TitlePattern(pattern.to_owned()),
+            _ => return Err(String::from("Error validating seasons: a title pattern is required")),
+        },
+        _ => return Ok(None),
+    };
+
+    let mut season_rates = Vec::new();
+    for entry in rates.as_deref().unwrap_or("").split([',', '\n']) {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let (season, rate) = match entry.split_once(':') {
+            Some(val) => val,
+            None => return Err(format!("Error validating season rate {}: expected season: rate", entry)),
+        };
+        let season: u32 = match season.trim().parse() {
+            Ok(val) => val,
+            Err(e) => return Err(format!("Error validating season rate {}: {}", entry, e)),
+        };
+        let rate = rate.trim().to_lowercase();
+        let rate = if let Some(days) = rate.strip_suffix('d') {
+            match days.trim().parse() {
+                Ok(val) => racer::RacerType::Days(val),
+                Err(e) => return Err(format!("Error validating season rate {}: {}", entry, e)),
+            }
+        }
+        else {
+            match rate.trim_end_matches('x').trim().parse() {
+                Ok(val) => racer::RacerType::Ratio(val),
+                Err(e) => return Err(format!("Error validating season rate {}: {}", entry, e)),
+            }
+        };
+        season_rates.push(racer::SeasonRate { season, rate });
+    }
+
+    let config = racer::SeasonConfig {
+        source,
+        rates: season_rates,
+        gap_days: gap_days.unwrap_or(0),
+    };
+    config.validate()?;
+    Ok(Some(config))
+}
+
+// Nothing picked means the default, oldest pubDate first
+fn episode_order_validator(order: &Option<String>) -> Result<racer::EpisodeOrder, String> {
+    match order.as_deref() {
+        Some(order) if !order.trim().is_empty() => order.parse(),
+        _ => Ok(racer::EpisodeOrder::default()),
+    }
+}
+
+// One GUID/pattern per line. Duration comes in as minutes.
+fn exclusions_from_form(form: &EditFeedForm) -> racer::ExcludeRules {
+    let lines = |text: &Option<String>| -> Vec<String> {
+        text.as_deref()
+            .unwrap_or("")
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_owned())
+            .collect()
+    };
+    racer::ExcludeRules {
+        guids: lines(&form.exclude_guids),
+        title_patterns: lines(&form.exclude_titles),
+        min_duration_secs: form.exclude_min_duration.filter(|&mins| mins > 0).map(|mins| mins * 60),
+    }
+}
+fn blackout_validator(blackout_start: &Option<String>, blackout_end: &Option<String>)
+    -> Result<racer::BlackoutRange, String> {
+    let (start, end) = date_range_validator(blackout_start, blackout_end)?;
+    if end < start {
+        return Err(String::from("Error validating blackout: end is before start"));
+    }
+    Ok(racer::BlackoutRange { start, end })
+}
+fn date_range_validator(start: &Option<String>, end: &Option<String>)
+    -> Result<(chrono::NaiveDate, chrono::NaiveDate), String> {
+    if start.is_none() || end.is_none() {
+        return Err(String::from("Error validating dates: must specify both a start and an end"));
+    }
+    Ok((date_validator(start)?, date_validator(end)?))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   playlist_validator
+//
+//  NOTES:
+//      Builds the playlist settings for shuffled and curated orders. Without a seed we keep the
+//      one we had, or pick a random one for new feeds.
+//  ARGS:
+//      seed - Shuffle seed
+//      curated_guids - One GUID per line, in order
+//      new_episodes - "end" or "interleave"
+//      current_seed - The feed's seed, if it has one already
+//  RETURN: The playlist or an error string
+//
+fn playlist_validator(seed: &Option<u64>, curated_guids: &Option<String>, new_episodes: &Option<String>,
+                      current_seed: Option<u64>) -> Result<racer::Playlist, String> {
+    let new_episodes = match new_episodes.as_deref() {
+        Some(placement) if !placement.trim().is_empty() => placement.parse()?,
+        _ => racer::NewEpisodePlacement::default(),
+    };
+    Ok(racer::Playlist {
+        seed: seed.or(current_seed).unwrap_or_else(|| Uuid::new_v4().as_u128() as u64),
+        curated: lines_from_form(curated_guids),
+        new_episodes,
+        ids: Vec::new(),
+    })
+}
+
+// "live", "stop" or "delay", with the delay in days
+fn catch_up_validator(policy: &Option<String>, delay_days: &Option<u32>) -> Result<racer::CatchUpPolicy, String> {
+    match policy.as_deref().map(str::trim) {
+        None | Some("") => Ok(racer::CatchUpPolicy::default()),
+        Some("delay") => format!("delay:{}", delay_days.unwrap_or(0)).parse(),
+        Some(policy) => policy.parse(),
+    }
+}
+fn footer_validator(mode: &Option<String>, template: &Option<String>) -> Result<racer::FeedFooter, String> {
+    match mode.as_deref().map(str::trim) {
+        None | Some("") | Some("server") => Ok(racer::FeedFooter::Server),
+        Some("none") => Ok(racer::FeedFooter::Disabled),
+        Some("custom") => {
+            let template = template.as_deref().unwrap_or("").replace("\r\n", "\n");
+            if template.trim().is_empty() {
+                return Err(String::from("Error validating footer: custom footers need a template"));
+            }
+            Ok(racer::FeedFooter::Custom(template))
+        },
+        Some(other) => Err(format!("Error validating footer: unknown footer {}", other)),
+    }
+}
+fn decoration_validator(form: &EditFeedForm) -> Result<racer::ChannelDecoration, String> {
+    let title = match form.title_style.as_deref().map(str::trim) {
+        None | Some("") | Some("suffix") => racer::TitleStyle::Suffix,
+        Some("original") => racer::TitleStyle::Original,
+        Some("custom") => match form.custom_title.as_deref().map(str::trim) {
+            Some(title) if !title.is_empty() => racer::TitleStyle::Custom(title.to_owned()),
+            _ => return Err(String::from("Error validating title: custom titles need a title")),
+        },
+        Some(other) => return Err(format!("Error validating title: unknown title style {}", other)),
+    };
+    let badge = match form.badge.as_deref().map(str::trim) {
+        None | Some("") | Some("off") => racer::ArtworkBadge::Off,
+        Some("rate") => racer::ArtworkBadge::Rate,
+        Some("podracer") => racer::ArtworkBadge::PodRacer,
+        Some("text") => match form.badge_text.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => racer::ArtworkBadge::Text(text.to_owned()),
+            _ => return Err(String::from("Error validating badge: text badges need some text")),
+        },
+        Some(other) => return Err(format!("Error validating badge: unknown badge {}", other)),
+    };
+    Ok(racer::ChannelDecoration {
+        title,
+        next_episode_line: form.next_episode_line,
+        artwork: form.artwork_url.clone(),
+        badge,
+    })
+}
+
+// One entry (url, GUID, ...) per line, blank lines ignored
+fn lines_from_form(lines: &Option<String>) -> Vec<String> {
+    match lines {
+        Some(lines) => lines.lines()
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn date_validator(date: &Option<String>) -> Result<chrono::NaiveDate, String> {
+    match date {
+        Some(date) => match chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+            Ok(val) => Ok(val),
+            Err(e) => Err(format!("Error validating date {}: {}", date, e)),
+        },
+        None => Err(String::from("Error validating date: no date given")),
+    }
+}
 
 ////////////////////////////////////////////////////////////////////////////////
 //  NAME:   edit_feed_handler
@@ -244,6 +699,7 @@ pub async fn edit_feed_post_handler_uri(config: &State<RocketConfig>, edit_form:
     };
 
     // Parse by action
+    let checkpoint = racer.checkpoint(&describe_feed_action(&edit_form));
     match edit_form.racer_action {
         FeedAction::EditFeed => (), // Just requesting page, don't need to do anything else.
         FeedAction::EditRate => {
@@ -264,6 +720,16 @@ pub async fn edit_feed_post_handler_uri(config: &State<RocketConfig>, edit_form:
             let new_rate = racer.get_rate();
             ctx.insert("top_text", &format!("Rate has been changed. Old rate: {}, new rate: {}.", old_rate, new_rate));
         },
+        FeedAction::EditRateDelay => {
+            let days = edit_form.rate_delay.expect("Form has rate_delay");
+            match racer.set_rate_delay(days).await {
+                Ok(_) => ctx.insert("top_text", &format!("Feed now mirrors the original {} days late.", days)),
+                Err(e) => {
+                    println!("Error setting delay: {}", e);
+                    ctx.insert("top_text", &e);
+                },
+            }
+        },
         FeedAction::Pause => {
             racer.pause_feed().await;
             ctx.insert("top_text", "Feed has been paused. No new episodes will be published \
@@ -278,14 +744,32 @@ pub async fn edit_feed_post_handler_uri(config: &State<RocketConfig>, edit_form:
             }
         }
         FeedAction::PublishNextEp => {
-            if let Some(next_ep_num) = edit_form.next_episode_num {
-                println!("publishing next episode, number {}", next_ep_num);
-                racer.publish_episode_num(next_ep_num).await;
+            racer.publish_next_ep_now().await;
+            ctx.insert("top_text", "Feed has been fast forwarded to the next episode.");
+        }
+        FeedAction::JumpToEpisode => {
+            match racer.jump_to_episode(edit_form.episode_num.expect("Form has episode_num")).await {
+                Ok(res) => ctx.insert("top_text", &format!("Feed has jumped to episode {}.", res.num_published)),
+                Err(e) => {
+                    println!("Error jumping to episode: {}", e);
+                    ctx.insert("top_text", &format!("Error jumping to episode: {}", e));
+                }
             }
-            else {
-                racer.publish_next_ep_now().await;
+        }
+        FeedAction::JumpToDate => {
+            let res = match date_validator(&edit_form.jump_date) {
+                Ok(date) => racer.jump_to_date(date).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(res) => ctx.insert("top_text", &format!("Feed has jumped to {}. {} episodes are published.",
+                                                           edit_form.jump_date.as_ref().expect("Form has jump_date").trim(),
+                                                           res.num_published)),
+                Err(e) => {
+                    println!("Error jumping to date: {}", e);
+                    ctx.insert("top_text", &format!("Error jumping to date: {}", e));
+                }
             }
-            ctx.insert("top_text", "Feed has been fast forwarded to the next episode.");
         }
         FeedAction::RewindDays => {
             racer.rewind_by_days(edit_form.slide_units.expect("Form has days")).await;
@@ -300,18 +784,271 @@ pub async fn edit_feed_post_handler_uri(config: &State<RocketConfig>, edit_form:
             ctx.insert("top_text", &format!("Feed has been fast forwarded {} {}.", edit_form.slide_units.unwrap(), pluralization));
         }
         FeedAction::RewindEps => {
-            racer.rewind_by_episodes(edit_form.slide_units.expect("Form has episodes")).await;
-            let pluralization = if edit_form.slide_units.unwrap() == 1 { "episode".to_owned() }
+            let res = racer.rewind_by_episodes(edit_form.slide_units.expect("Form has episodes")).await;
+            let pluralization = if res.moved == 1 { "episode".to_owned() }
                                 else { "episodes".to_owned() };
-            ctx.insert("top_text", &format!("Feed has been rewound {} {}.", edit_form.slide_units.unwrap(), pluralization));
+            let note = if res.clamped { " Nothing is published before that." } else { "" };
+            ctx.insert("top_text", &format!("Feed has been rewound {} {}.{}", res.moved, pluralization, note));
         }
         FeedAction::FastForwardEps   => {
-            racer.fastforward_by_episodes(edit_form.slide_units.expect("Form has episodes")).await;
-            let pluralization = if edit_form.slide_units.unwrap() == 1 { "episode".to_owned() }
+            let res = racer.fastforward_by_episodes(edit_form.slide_units.expect("Form has episodes")).await;
+            let pluralization = if res.moved == 1 { "episode".to_owned() }
                                 else { "episodes".to_owned() };
-            ctx.insert("top_text", &format!("Feed has been fast forwarded {} {}.", edit_form.slide_units.unwrap(), pluralization));
+            let note = if res.clamped { " All episodes are published." } else { "" };
+            ctx.insert("top_text", &format!("Feed has been fast forwarded {} {}.{}", res.moved, pluralization, note));
+        }
+        FeedAction::EditPublishSchedule => {
+            let res = match publish_schedule_validator(&edit_form.timezone, &edit_form.publish_time) {
+                Ok((timezone, publish_time)) => racer.set_publish_schedule(&timezone, publish_time).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", "Publish schedule has been changed."),
+                Err(e) => {
+                    println!("Error setting publish schedule: {}", e);
+                    ctx.insert("top_text", &format!("Error setting publish schedule: {}", e));
+                }
+            }
+        }
+        FeedAction::EditConstraints => {
+            let mut constraints = racer.get_schedule_constraints().clone();
+            constraints.skip_weekends = edit_form.skip_weekends;
+            let res = match quiet_hours_validator(&edit_form.quiet_start, &edit_form.quiet_end) {
+                Ok(quiet_hours) => {
+                    constraints.quiet_hours = quiet_hours;
+                    racer.set_schedule_constraints(constraints).await
+                },
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", "Schedule constraints have been changed."),
+                Err(e) => {
+                    println!("Error setting schedule constraints: {}", e);
+                    ctx.insert("top_text", &format!("Error setting schedule constraints: {}", e));
+                }
+            }
+        }
+        FeedAction::AddBlackout => {
+            let mut constraints = racer.get_schedule_constraints().clone();
+            let res = match blackout_validator(&edit_form.blackout_start, &edit_form.blackout_end) {
+                Ok(blackout) => {
+                    constraints.blackouts.push(blackout);
+                    racer.set_schedule_constraints(constraints).await
+                },
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", "Blackout dates have been added."),
+                Err(e) => {
+                    println!("Error adding blackout: {}", e);
+                    ctx.insert("top_text", &format!("Error adding blackout: {}", e));
+                }
+            }
+        }
+        FeedAction::RemoveBlackout => {
+            let mut constraints = racer.get_schedule_constraints().clone();
+            let idx = edit_form.blackout_idx.expect("Form has blackout_idx");
+            if idx < constraints.blackouts.len() {
+                let removed = constraints.blackouts.remove(idx);
+                match racer.set_schedule_constraints(constraints).await {
+                    Ok(_) => ctx.insert("top_text", &format!("Blackout {} has been removed.", removed)),
+                    Err(e) => {
+                        println!("Error removing blackout: {}", e);
+                        ctx.insert("top_text", &format!("Error removing blackout: {}", e));
+                    }
+                }
+            }
+            else {
+                ctx.insert("top_text", "Error removing blackout: no such blackout.");
+            }
+        }
+        FeedAction::SchedulePause => {
+            let res = match date_range_validator(&edit_form.pause_start, &edit_form.pause_end) {
+                Ok((start, end)) => racer.add_scheduled_pause(start, end).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", "Pause has been scheduled."),
+                Err(e) => {
+                    println!("Error scheduling pause: {}", e);
+                    ctx.insert("top_text", &format!("Error scheduling pause: {}", e));
+                }
+            }
+        }
+        FeedAction::CancelScheduledPause => {
+            match racer.cancel_scheduled_pause(edit_form.pause_idx.expect("Form has pause_idx")).await {
+                Ok(pause) => {
+                    let resumed = if pause.active { " Feed has been resumed." } else { "" };
+                    ctx.insert("top_text", &format!("Scheduled pause has been cancelled.{}", resumed));
+                },
+                Err(e) => {
+                    println!("Error cancelling scheduled pause: {}", e);
+                    ctx.insert("top_text", &format!("Error cancelling scheduled pause: {}", e));
+                }
+            }
+        }
+        FeedAction::EditExclusions => {
+            let exclusions = exclusions_from_form(&edit_form);
+            match racer.set_exclude_rules(exclusions).await {
+                Ok(_) => ctx.insert("top_text", "Excluded episodes have been changed."),
+                Err(e) => {
+                    println!("Error setting exclusions: {}", e);
+                    ctx.insert("top_text", &format!("Error setting exclusions: {}", e));
+                }
+            }
+        }
+        FeedAction::EditEpisodeTypes => {
+            let policy = racer::EpisodeTypePolicy {
+                skip_trailers: edit_form.skip_trailers,
+                bonus_with_neighbour: edit_form.bonus_with_neighbour,
+            };
+            match racer.set_episode_type_policy(policy).await {
+                Ok(_) => ctx.insert("top_text", "Trailer and bonus episode handling has been changed."),
+                Err(e) => {
+                    println!("Error setting episode type policy: {}", e);
+                    ctx.insert("top_text", &format!("Error setting episode type policy: {}", e));
+                }
+            }
+        }
+        FeedAction::EditSeasons => {
+            let res = match season_config_validator(&edit_form.season_source, &edit_form.season_pattern,
+                                                    &edit_form.season_gap_days, &edit_form.season_rates) {
+                Ok(seasons) => racer.set_season_config(seasons).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", "Season settings have been changed."),
+                Err(e) => {
+                    println!("Error setting seasons: {}", e);
+                    ctx.insert("top_text", &format!("Error setting seasons: {}", e));
+                }
+            }
+        }
+        FeedAction::EditOrder => {
+            let res = match (episode_order_validator(&edit_form.order),
+                             playlist_validator(&edit_form.shuffle_seed, &edit_form.curated_guids,
+                                                &edit_form.new_episodes, Some(racer.get_playlist().seed))) {
+                (Ok(order), Ok(playlist)) => racer.set_playback_order(order, playlist).await,
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", &format!("Episodes are now ordered by {}.", racer.get_episode_order())),
+                Err(e) => {
+                    println!("Error setting episode order: {}", e);
+                    ctx.insert("top_text", &format!("Error setting episode order: {}", e));
+                }
+            }
+        }
+        FeedAction::EditSources => {
+            let urls = lines_from_form(&edit_form.source_urls);
+            let local_media_root = config.local_media_root.as_ref().map(std::path::PathBuf::from);
+            match racer.set_sources(&urls, local_media_root.as_deref(), &reqwest::Client::new()).await {
+                Ok(_) => ctx.insert("top_text", &format!("Feed sources have been changed. {} episodes are published.",
+                                                         racer.get_num_to_publish())),
+                Err(e) => {
+                    println!("Error setting sources: {}", e);
+                    ctx.insert("top_text", &format!("Error setting sources: {}", e));
+                }
+            }
+        }
+        FeedAction::EditCatchUp => {
+            let res = match catch_up_validator(&edit_form.catch_up, &edit_form.catch_up_delay_days) {
+                Ok(policy) => racer.set_catch_up_policy(policy).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", &format!("Once caught up, new episodes will be published: {}.",
+                                                         racer.get_catch_up_policy())),
+                Err(e) => {
+                    println!("Error setting catch up policy: {}", e);
+                    ctx.insert("top_text", &format!("Error setting catch up policy: {}", e));
+                }
+            }
+        }
+        FeedAction::EditFooter => {
+            let res = match footer_validator(&edit_form.footer_mode, &edit_form.footer_template) {
+                Ok(footer) => racer.set_footer(footer).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", &format!("Episode footer is now: {}.", racer.get_footer())),
+                Err(e) => {
+                    println!("Error setting footer: {}", e);
+                    ctx.insert("top_text", &format!("Error setting footer: {}", e));
+                }
+            }
+        }
+        FeedAction::EditEnclosures => {
+            match racer.set_proxy_enclosures(edit_form.proxy_enclosures).await {
+                Ok(_) => match racer.get_proxy_enclosures() {
+                    true => ctx.insert("top_text", "Episodes are now served through this server."),
+                    false => ctx.insert("top_text", "Episodes are now served from the original host."),
+                },
+                Err(e) => {
+                    println!("Error setting enclosures: {}", e);
+                    ctx.insert("top_text", &format!("Error setting enclosures: {}", e));
+                }
+            }
+        }
+        FeedAction::EditArchive => {
+            let local_media_root = config.local_media_root.as_ref().map(std::path::PathBuf::from);
+            match racer.set_archive_dir(edit_form.archive_dir.as_deref(), local_media_root.as_deref()).await {
+                Ok(_) => match racer.get_archive_dir() {
+                    Some(dir) => ctx.insert("top_text", &format!("Archived episodes are now served from {}.", dir.display())),
+                    None => ctx.insert("top_text", "This feed no longer uses an archive."),
+                },
+                Err(e) => {
+                    println!("Error setting archive: {}", e);
+                    ctx.insert("top_text", &format!("Error setting archive: {}", e));
+                }
+            }
+        }
+        FeedAction::EditGuids => {
+            match racer.set_namespace_guids(edit_form.namespace_guids).await {
+                Ok(_) => match racer.get_namespace_guids() {
+                    true => ctx.insert("top_text", "Episode GUIDs are now unique to this feed."),
+                    false => ctx.insert("top_text", "Episode GUIDs now match the original feed."),
+                },
+                Err(e) => {
+                    println!("Error setting GUIDs: {}", e);
+                    ctx.insert("top_text", &format!("Error setting GUIDs: {}", e));
+                }
+            }
+        }
+        FeedAction::EditTitleAndArtwork => {
+            let res = match decoration_validator(&edit_form) {
+                Ok(decoration) => racer.set_decoration(decoration).await,
+                Err(e) => Err(e),
+            };
+            match res {
+                Ok(_) => ctx.insert("top_text", &format!("Feed title is now: {}.", racer.get_decoration().title)),
+                Err(e) => {
+                    println!("Error setting title and artwork: {}", e);
+                    ctx.insert("top_text", &format!("Error setting title and artwork: {}", e));
+                }
+            }
+        }
+        FeedAction::Undo => {
+            match racer.undo_last_change().await {
+                Ok(entry) => ctx.insert("top_text", &format!("Undid \"{}\".", entry.action)),
+                Err(e) => {
+                    println!("Error undoing last change: {}", e);
+                    ctx.insert("top_text", &format!("Error undoing last change: {}", e));
+                }
+            }
         }
     }
+    // Only the anchor, rate and pause state are journaled, so settings edits can't be undone
+    match edit_form.racer_action {
+        FeedAction::EditRate | FeedAction::EditRateDays | FeedAction::EditRateDelay
+        | FeedAction::Pause | FeedAction::Unpause
+        | FeedAction::FastForwardDays | FeedAction::RewindDays
+        | FeedAction::FastForwardEps | FeedAction::RewindEps
+        | FeedAction::PublishNextEp | FeedAction::JumpToEpisode | FeedAction::JumpToDate => {
+            racer.record_change(checkpoint);
+        },
+        _ => (),
+    }
 
     fill_edit_feed_data_from_racer(&mut ctx, &racer);
     Template::render(EDIT_FEED_FILE, &ctx.into_json())
@@ -357,8 +1094,17 @@ pub async fn create_feed_cli_handler(
             host: config.host.clone(),
             port: config.port,
             url,
+            extra_urls: Vec::new(),
             rate: racer::RacerType::Ratio(rate),
             start_ep: 1,
+            timezone: racer::DEFAULT_TIMEZONE.to_owned(),
+            publish_time: None,
+            seasons: None,
+            start_season: None,
+            order: racer::EpisodeOrder::default(),
+            playlist: racer::Playlist::default(),
+            catch_up_policy: racer::CatchUpPolicy::default(),
+            local_media_root: config.local_media_root.clone(),
         },
         &reqwest::Client::new()
     ).await {
@@ -396,8 +1142,17 @@ pub async fn create_feed_cli_ep_handler(
             host: config.host.clone(),
             port: config.port,
             url,
+            extra_urls: Vec::new(),
             rate: racer::RacerType::Ratio(rate),
             start_ep,
+            timezone: racer::DEFAULT_TIMEZONE.to_owned(),
+            publish_time: None,
+            seasons: None,
+            start_season: None,
+            order: racer::EpisodeOrder::default(),
+            playlist: racer::Playlist::default(),
+            catch_up_policy: racer::CatchUpPolicy::default(),
+            local_media_root: config.local_media_root.clone(),
         },
         &reqwest::Client::new()
     ).await {
@@ -515,40 +1270,302 @@ pub async fn serve_rss_handler(config: &State<RocketConfig>, podcast: String) ->
     NamedFile::open(&path).await
 }
 
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   serve_atom_handler
 //
-// Helper Functions
+//  NOTES:  Serves the racer.atom file for the specified podcast. Same episodes as racer.rss.
+//  ARGS:   podcast - The podcast to serve. Format is the folder name
+//  RETURN: Our PodRacer Atom file
 //
+#[get("/podcasts/<podcast>/racer.atom")]
+pub async fn serve_atom_handler(config: &State<RocketConfig>, podcast: String)
+    -> Result<(ContentType, NamedFile), std::io::Error> {
+    serve_racer_file(config, &podcast, racer::RACER_ATOM_FILE, racer::ATOM_MIME_TYPE).await
+}
 
-fn fill_edit_feed_data_from_racer(cx: &mut Context, racer: &racer::FeedRacer) {
-    let next = racer.get_next_episode_pub_date();
-    let now = chrono::Utc::now();
-    let next_pub_date_string = if next <= now {
-        String::from("Caught up, whenever they publish another one")
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   serve_json_feed_handler
+//
+//  NOTES:  Serves the racer.json file (JSON Feed 1.1) for the specified podcast. Same episodes
+//          as racer.rss.
+//  ARGS:   podcast - The podcast to serve. Format is the folder name
+//  RETURN: Our PodRacer JSON Feed file
+//
+#[get("/podcasts/<podcast>/racer.json")]
+pub async fn serve_json_feed_handler(config: &State<RocketConfig>, podcast: String)
+    -> Result<(ContentType, NamedFile), std::io::Error> {
+    serve_racer_file(config, &podcast, racer::RACER_JSON_FILE, racer::JSON_FEED_MIME_TYPE).await
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   serve_artwork_handler
+//
+//  NOTES:  Serves the badged artwork for the specified podcast, if it has a badge
+//  ARGS:   podcast - The podcast to serve. Format is the folder name
+//  RETURN: Our PodRacer artwork
+//
+#[get("/podcasts/<podcast>/racer.png")]
+pub async fn serve_artwork_handler(config: &State<RocketConfig>, podcast: String)
+    -> Result<(ContentType, NamedFile), std::io::Error> {
+    serve_racer_file(config, &podcast, racer::RACER_ARTWORK_FILE, racer::ARTWORK_MIME_TYPE).await
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//  NAME:   serve_media_handler
+//
+//  NOTES:
+//      Serves an episode's audio for feeds that proxy their enclosures. Archived copies are
+//      served from disk, anything else is streamed from the upstream host. Range requests are
+//      supported so players can seek.
+//  ARGS:
+//      podcast - The podcast to serve. Format is the folder name
+//      guid - The episode's upstream GUID
+//      range - The Range header, if any
+//  RETURN: The audio, or 404 if this feed doesn't serve that episode
+//
+#[get("/podcasts/<podcast>/media/<guid>")]
+pub async fn serve_media_handler(config: &State<RocketConfig>, podcast: String, guid: String, range: RangeHeader)
+    -> Result<MediaResponse, Status> {
+    let racer = match racer::get_by_dir_name(&config.podracer_dir, &podcast) {
+        Some(val) => val,
+        None => return Err(Status::NotFound),
+    };
+    let client = reqwest::Client::new();
+    match racer.open_media(&guid, range.0.as_deref(), &client).await {
+        Ok(Some(media)) => Ok(MediaResponse(media)),
+        Ok(None) => Err(Status::NotFound),
+        Err(e) => {
+            println!("Error serving media {} for {}: {}", guid, podcast, e);
+            Err(Status::BadGateway)
+        },
     }
-    else if let Some(_) = racer.get_pause_date() {
-        String::from("Feed paused, unpause to publish next episode")
+}
+
+//
+// Helper Functions
+//
+
+// Serves a file from a racer's directory with the given mime type
+async fn serve_racer_file(config: &State<RocketConfig>, podcast: &str, file: &str, mime_type: &str)
+    -> Result<(ContentType, NamedFile), std::io::Error> {
+    let path: PathBuf = [&config.podracer_dir, podcast, file].iter().collect();
+    println!("Serving {:?} at {}", path, chrono::Utc::now().to_rfc3339());
+    let content_type = ContentType::parse_flexible(mime_type).unwrap_or(ContentType::Plain);
+    Ok((content_type, NamedFile::open(&path).await?))
+}
+
+// A short description of a form action, for the history table
+fn describe_feed_action(form: &EditFeedForm) -> String {
+    let units = form.slide_units.unwrap_or(0);
+    match form.racer_action {
+        FeedAction::EditFeed             => String::from("View feed"),
+        FeedAction::EditRate             => format!("Set rate to {}x", form.rate_ratio.unwrap_or(0.0)),
+        FeedAction::EditRateDays         => format!("Set rate to every {} days", form.rate_days.unwrap_or(0)),
+        FeedAction::EditRateDelay        => format!("Mirror {} days late", form.rate_delay.unwrap_or(0)),
+        FeedAction::Pause                => String::from("Pause"),
+        FeedAction::Unpause              => String::from("Resume"),
+        FeedAction::FastForwardDays      => format!("Fast-forward {} days", units),
+        FeedAction::RewindDays           => format!("Rewind {} days", units),
+        FeedAction::FastForwardEps       => format!("Fast-forward {} episodes", units),
+        FeedAction::RewindEps            => format!("Rewind {} episodes", units),
+        FeedAction::PublishNextEp        => String::from("Publish next episode"),
+        FeedAction::JumpToEpisode        => format!("Jump to episode {}", form.episode_num.unwrap_or(0)),
+        FeedAction::JumpToDate           => format!("Jump to {}", form.jump_date.as_deref().unwrap_or("").trim()),
+        FeedAction::EditPublishSchedule  => String::from("Change publish schedule"),
+        FeedAction::EditConstraints      => String::from("Change quiet hours/weekends"),
+        FeedAction::AddBlackout          => String::from("Add blackout"),
+        FeedAction::RemoveBlackout       => String::from("Remove blackout"),
+        FeedAction::SchedulePause        => String::from("Schedule pause"),
+        FeedAction::CancelScheduledPause => String::from("Cancel scheduled pause"),
+        FeedAction::EditExclusions       => String::from("Change excluded episodes"),
+        FeedAction::EditEpisodeTypes     => String::from("Change trailer/bonus handling"),
+        FeedAction::EditSeasons          => String::from("Change seasons"),
+        FeedAction::EditOrder            => format!("Order by {}", form.order.as_deref().unwrap_or("").trim()),
+        FeedAction::EditSources          => String::from("Change sources"),
+        FeedAction::EditCatchUp          => String::from("Change catch up policy"),
+        FeedAction::EditFooter           => String::from("Change episode footer"),
+        FeedAction::EditTitleAndArtwork  => String::from("Change title and artwork"),
+        FeedAction::EditGuids            => String::from("Change episode GUIDs"),
+        FeedAction::EditEnclosures       => String::from("Change where episodes are served from"),
+        FeedAction::EditArchive          => String::from("Change archive"),
+        FeedAction::Undo                 => String::from("Undo"),
     }
-    else {
-        next.with_timezone(&chrono_tz::US::Pacific).to_rfc2822()
+}
+
+fn fill_edit_feed_data_from_racer(cx: &mut Context, racer: &racer::FeedRacer) {
+    let tz = racer.get_timezone();
+    let next_pub_date_string = match racer.get_feed_status() {
+        Ok(racer::FeedStatus::NextEpisode(date)) => date.to_rfc2822(),
+        Ok(racer::FeedStatus::Paused) => String::from("Feed paused, unpause to publish next episode"),
+        Ok(racer::FeedStatus::CaughtUp) => String::from("Caught up, whenever they publish another one"),
+        Ok(racer::FeedStatus::Stopped) => String::from("Caught up, new episodes won't be published"),
+        Err(e) => {
+            println!("Error getting feed status for {}: {}", racer.get_podcast_title(), e);
+            String::from("Unknown")
+        }
     };
     cx.insert("next_pub_date_string", &next_pub_date_string);
-    cx.insert("current_ep_title",     &racer.get_current_ep_title());
-    cx.insert("next_ep_title",        &racer.get_next_ep_title());
+    cx.insert("current_ep_title",     &racer.get_current_ep_title().unwrap_or_else(|| String::from("None published yet")));
+    cx.insert("next_ep_title",        &racer.get_next_ep_title().unwrap_or_else(|| String::from("All episodes published")));
     cx.insert("podcast_title",        &racer.get_podcast_title());
     cx.insert("subscribe_url",        &racer.get_subscribe_url());
-    cx.insert("first_pubdate",        &racer.get_first_pubdate().with_timezone(&chrono_tz::US::Pacific).to_rfc2822());
+    cx.insert("atom_url",             &racer.get_atom_url());
+    cx.insert("json_feed_url",        &racer.get_json_feed_url());
+    cx.insert("first_pubdate",        &racer.get_first_pubdate().with_timezone(&tz).to_rfc2822());
     cx.insert("num_published",        &racer.get_num_to_publish());
     cx.insert("num_episodes",         &racer.get_num_episodes());
-    cx.insert("anchor_date",          &racer.get_anchor_date().with_timezone(&chrono_tz::US::Pacific).to_rfc2822());
+    cx.insert("anchor_date",          &racer.get_anchor_date().with_timezone(&tz).to_rfc2822());
     cx.insert("source_url",           &racer.get_source_url());
+    let sources: Vec<std::collections::HashMap<&str, String>> = racer.get_sources().iter()
+        .map(|source| {
+            let mut row = std::collections::HashMap::new();
+            row.insert("url", source.url.clone());
+            row.insert("num_items", source.num_items.to_string());
+            row.insert("last_success", match source.last_success {
+                Some(date) => date.with_timezone(&tz).to_rfc2822(),
+                None => String::from("Never"),
+            });
+            row.insert("last_error", source.last_error.clone().unwrap_or_default());
+            row
+        })
+        .collect();
+    cx.insert("sources", &sources);
+    let source_urls: Vec<&str> = racer.get_sources().iter().map(|source| source.url.as_str()).collect();
+    cx.insert("source_urls", &source_urls.join("\n"));
     cx.insert("rate_ratio",           &format!("{:.2}", racer.get_rate()));
     cx.insert("rate_days",            &format!("{:.2}", racer.get_rate()));
     cx.insert("uuid",                 &racer.get_uuid_string());
-    if let Some(old_rate) = racer.get_old_rate() {
-        cx.insert("old_rate", &format!("{:.2}", old_rate));
+    cx.insert("timezone",             &tz.name());
+    if let Some(publish_time) = racer.get_publish_time() {
+        cx.insert("publish_time", &publish_time.format("%H:%M").to_string());
+    }
+    let constraints = racer.get_schedule_constraints();
+    if let Some(quiet_hours) = &constraints.quiet_hours {
+        cx.insert("quiet_start", &quiet_hours.start.format("%H:%M").to_string());
+        cx.insert("quiet_end",   &quiet_hours.end.format("%H:%M").to_string());
+    }
+    cx.insert("skip_weekends", &constraints.skip_weekends);
+    let blackouts: Vec<String> = constraints.blackouts.iter().map(|b| b.to_string()).collect();
+    cx.insert("blackouts", &blackouts);
+    let scheduled_pauses: Vec<std::collections::HashMap<&str, String>> = racer.get_scheduled_pauses().iter()
+        .map(|pause| {
+            let mut row = std::collections::HashMap::new();
+            row.insert("start", pause.start.with_timezone(&tz).format("%a, %d %b %Y").to_string());
+            row.insert("end", pause.end.with_timezone(&tz).format("%a, %d %b %Y").to_string());
+            row.insert("status", String::from(if pause.active { "Active" } else { "Scheduled" }));
+            row
+        })
+        .collect();
+    cx.insert("scheduled_pauses", &scheduled_pauses);
+    // Newest first
+    let history: Vec<std::collections::HashMap<&str, String>> = racer.get_history()
+        .iter()
+        .rev()
+        .map(|entry| {
+            let mut row = std::collections::HashMap::new();
+            row.insert("timestamp", entry.timestamp.with_timezone(&tz).to_rfc2822());
+            row.insert("action", entry.action.clone());
+            row.insert("anchor_date", entry.anchor_date.with_timezone(&tz).to_rfc2822());
+            row.insert("rate", format!("{:.2}", entry.rate));
+            row.insert("paused", match entry.pause_date {
+                Some(date) => date.with_timezone(&tz).to_rfc2822(),
+                None => String::from("No"),
+            });
+            row
+        })
+        .collect();
+    cx.insert("history", &history);
+    let exclusions = racer.get_exclude_rules();
+    cx.insert("exclude_guids", &exclusions.guids.join("\n"));
+    cx.insert("exclude_titles", &exclusions.title_patterns.join("\n"));
+    if let Some(min_duration_secs) = exclusions.min_duration_secs {
+        cx.insert("exclude_min_duration", &(min_duration_secs / 60));
+    }
+    cx.insert("excluded_titles", &racer.get_excluded_titles());
+    let policy = racer.get_episode_type_policy();
+    cx.insert("skip_trailers", &policy.skip_trailers);
+    cx.insert("bonus_with_neighbour", &policy.bonus_with_neighbour);
+    cx.insert("order", racer.get_episode_order().as_str());
+    cx.insert("order_name", &racer.get_episode_order().to_string());
+    match racer.get_catch_up_policy() {
+        racer::CatchUpPolicy::Live => cx.insert("catch_up", "live"),
+        racer::CatchUpPolicy::Stop => cx.insert("catch_up", "stop"),
+        racer::CatchUpPolicy::Delay(days) => {
+            cx.insert("catch_up", "delay");
+            cx.insert("catch_up_delay_days", &days);
+        },
+    }
+    cx.insert("catch_up_name", &racer.get_catch_up_policy().to_string());
+    match racer.get_footer() {
+        racer::FeedFooter::Server => {
+            cx.insert("footer_mode", "server");
+            cx.insert("footer_template", racer::DEFAULT_FOOTER_TEMPLATE);
+        },
+        racer::FeedFooter::Custom(template) => {
+            cx.insert("footer_mode", "custom");
+            cx.insert("footer_template", template);
+        },
+        racer::FeedFooter::Disabled => {
+            cx.insert("footer_mode", "none");
+            cx.insert("footer_template", racer::DEFAULT_FOOTER_TEMPLATE);
+        },
+    }
+    let decoration = racer.get_decoration();
+    match &decoration.title {
+        racer::TitleStyle::Suffix => cx.insert("title_style", "suffix"),
+        racer::TitleStyle::Custom(title) => {
+            cx.insert("title_style", "custom");
+            cx.insert("custom_title", title);
+        },
+        racer::TitleStyle::Original => cx.insert("title_style", "original"),
+    }
+    cx.insert("title_suffix", racer::TITLE_SUFFIX);
+    cx.insert("next_episode_line", &decoration.next_episode_line);
+    if let Some(artwork) = &decoration.artwork {
+        cx.insert("artwork_url", artwork);
+    }
+    cx.insert("namespace_guids", &racer.get_namespace_guids());
+    cx.insert("proxy_enclosures", &racer.get_proxy_enclosures());
+    if let Some(dir) = racer.get_archive_dir() {
+        cx.insert("archive_dir", &dir.display().to_string());
+    }
+    match &decoration.badge {
+        racer::ArtworkBadge::Off => cx.insert("badge", "off"),
+        racer::ArtworkBadge::Rate => cx.insert("badge", "rate"),
+        racer::ArtworkBadge::PodRacer => cx.insert("badge", "podracer"),
+        racer::ArtworkBadge::Text(text) => {
+            cx.insert("badge", "text");
+            cx.insert("badge_text", text);
+        },
+    }
+    if let Some(caught_up) = racer.get_caught_up() {
+        cx.insert("caught_up_date", &caught_up.date.with_timezone(&tz).to_rfc2822());
+    }
+    let playlist = racer.get_playlist();
+    cx.insert("shuffle_seed", &playlist.seed);
+    cx.insert("curated_guids", &playlist.curated.join("\n"));
+    cx.insert("new_episodes", playlist.new_episodes.as_str());
+    cx.insert("season_source", "none");
+    if let Some(seasons) = racer.get_season_config() {
+        match &seasons.source {
+            racer::SeasonSource::ITunes => cx.insert("season_source", "itunes"),
+            racer::SeasonSource::TitlePattern(pattern) => {
+                cx.insert("season_source", "title");
+                cx.insert("season_pattern", pattern);
+            },
+        }
+        cx.insert("season_gap_days", &seasons.gap_days);
+        let rates: Vec<String> = seasons.rates.iter()
+            .map(|season_rate| match season_rate.rate {
+                racer::RacerType::Ratio(rate) => format!("{}: {}x", season_rate.season, rate),
+                racer::RacerType::Days(days) => format!("{}: {}d", season_rate.season, days),
+                racer::RacerType::Delay(days) => format!("{}: {} days late", season_rate.season, days),
+            })
+            .collect();
+        cx.insert("season_rates", &rates.join(", "));
     }
     if let Some(pause_date) = racer.get_pause_date() {
-        cx.insert("pause_date", &pause_date.with_timezone(&chrono_tz::US::Pacific).to_rfc2822());
+        cx.insert("pause_date", &pause_date.with_timezone(&tz).to_rfc2822());
     }
 }
 